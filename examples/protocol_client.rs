@@ -0,0 +1,181 @@
+//! A reference agent that plays Starstruck over the JSON-lines protocol.
+//!
+//! It starts the game with `--protocol`, reads each observation, and picks one of the
+//! legal actions with a simple scripted policy: heal when low on health, otherwise
+//! cast the strongest damaging move it can, otherwise attack.
+//!
+//! Build the game first, then give this the path to it along with any other options:
+//!
+//! ```sh
+//! cargo build
+//! cargo run --example protocol_client -- target/debug/starstruck --seed 3
+//! ```
+use std::{
+    error::Error,
+    io::{BufRead, BufReader, Write},
+    process::{self, Command, Stdio},
+};
+
+use starstruck::entity_components::{item::ItemEffect, moves::MoveEffect, player::LevelUpType};
+use starstruck::protocol::{ActionMessage, GameMessage, Observation, PROTOCOL_VERSION};
+
+const DEFAULT_GAME_PATH: &str = "target/debug/starstruck";
+const MAX_ACTIONS: u32 = 500; // stop here, in case the agent never dies
+const LOW_HEALTH: f64 = 0.35; // the fraction of max health to heal below
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let mut args = std::env::args().skip(1);
+    let game_path = args
+        .next()
+        .unwrap_or_else(|| String::from(DEFAULT_GAME_PATH));
+
+    let mut game = Command::new(&game_path)
+        .arg("--protocol")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("could not start {}: {}", game_path, err))?;
+    let mut to_game = game.stdin.take().ok_or("the game has no stdin")?;
+    let mut from_game = BufReader::new(game.stdout.take().ok_or("the game has no stdout")?);
+
+    let mut observation = read_observation(&mut from_game)?;
+    if observation.version != PROTOCOL_VERSION {
+        return Err(format!(
+            "the game speaks protocol version {}, but this agent speaks {}",
+            observation.version, PROTOCOL_VERSION
+        )
+        .into());
+    }
+
+    let mut num_actions = 0;
+    while num_actions < MAX_ACTIONS {
+        let action = match choose_action(&observation) {
+            Some(action) => action,
+            None => break, // nothing is legal once the player has died
+        };
+        writeln!(to_game, "{}", serde_json::to_string(&action)?)?;
+        to_game.flush()?;
+        num_actions += 1;
+
+        observation = read_observation(&mut from_game)?;
+        let player = &observation.player.combatant;
+        println!(
+            "{:>3}: {:<45} health {}, mana {}, {} event(s)",
+            num_actions,
+            format!("{:?}", action),
+            player.health,
+            player.mana,
+            observation.events.len()
+        );
+    }
+
+    let player = &observation.player.combatant;
+    println!(
+        "{} reached level {} after {} action(s), and is {}.",
+        player.name,
+        player.level,
+        num_actions,
+        if observation.legal_actions.is_empty() {
+            "dead"
+        } else {
+            "still standing"
+        }
+    );
+
+    // closing the game's input ends it
+    drop(to_game);
+    game.wait()?;
+
+    Ok(())
+}
+
+/// Reads the next observation from the game, failing on anything else.
+fn read_observation(from_game: &mut impl BufRead) -> Result<Observation, Box<dyn Error>> {
+    let mut line = String::new();
+    if from_game.read_line(&mut line)? == 0 {
+        return Err("the game stopped without sending an observation".into());
+    }
+
+    match serde_json::from_str(&line)? {
+        GameMessage::Observation(observation) => Ok(*observation),
+        GameMessage::Error { message, .. } => Err(format!("the game refused: {}", message).into()),
+    }
+}
+
+/// Picks one of the legal actions of an observation.
+///
+/// # Returns
+/// - The action to take, or `None` if there are none.
+fn choose_action(observation: &Observation) -> Option<ActionMessage> {
+    let legal = &observation.legal_actions;
+
+    // put every level into magic
+    if let Some(level_up) = legal.iter().find(|action| {
+        matches!(
+            action,
+            ActionMessage::LevelUp {
+                stat: LevelUpType::Magic
+            }
+        )
+    }) {
+        return Some(level_up.clone());
+    }
+
+    // heal with a move or an item when low on health
+    if observation.player.combatant.health.fraction() < LOW_HEALTH {
+        let heal = legal.iter().find(|action| match action {
+            ActionMessage::Magic { move_name, .. } => observation.moves.iter().any(|the_move| {
+                the_move.name == *move_name && the_move.effect == MoveEffect::HealSelf
+            }),
+            ActionMessage::Item { item, .. } => observation.items.iter().any(|stack| {
+                stack.name == *item && matches!(stack.effect, ItemEffect::Heal { .. })
+            }),
+            _ => false,
+        });
+        if let Some(heal) = heal {
+            return Some(heal.clone());
+        }
+    }
+
+    // aim at the enemy closest to dying
+    let weakest = observation
+        .enemies
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, enemy)| enemy.health.current())
+        .map(|(idx, _)| idx)
+        .unwrap_or(0);
+
+    // the strongest damaging move that can be cast at it
+    let strongest_spell = legal
+        .iter()
+        .filter_map(|action| match action {
+            ActionMessage::Magic { move_name, target } if *target == weakest => observation
+                .moves
+                .iter()
+                .find(|the_move| {
+                    the_move.name == *move_name && the_move.effect == MoveEffect::Damage
+                })
+                .map(|the_move| (the_move.base_amount, action)),
+            _ => None,
+        })
+        .max_by_key(|(base_amount, _)| *base_amount)
+        .map(|(_, action)| action);
+    if let Some(spell) = strongest_spell {
+        return Some(spell.clone());
+    }
+
+    legal
+        .iter()
+        .find(|action| matches!(action, ActionMessage::Attack { target } if *target == weakest))
+        .or_else(|| legal.first())
+        .cloned()
+}
@@ -0,0 +1,1190 @@
+//headless turn-based battle logic
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::event::BattleEvent;
+use super::initiative::{Combatant, Timeline};
+use super::outcome::{ActionError, ActionOutcome};
+use super::rules::{self, RuleSet};
+use crate::content::Content;
+use crate::entity_components::enemy::Enemy;
+use crate::entity_components::equipment::EquipSlot;
+use crate::entity_components::item::Loot;
+use crate::entity_components::moves::{Move, MoveTarget, MoveType};
+use crate::entity_components::stats::{DefendStance, ModifierSource};
+use crate::entity_components::{entity::Entity, player::LevelUpType, player::Player};
+use crate::rng::{create_rng, GameRng};
+
+/// How many levels above the player an enemy can be for the player to fight it.
+pub const MAX_ENEMY_LEVELS_ABOVE_PLAYER: u32 = 2;
+
+/// An action that an entity can take on its turn.
+///
+/// The player chooses which enemy an action is aimed at separately (see `Battle::take_turn`),
+/// while enemies always aim at the player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Attack,               // physical attack against the target
+    Magic(usize), // magic move, by index into the available moves (or the enemy's own moves)
+    Defend(DefendStance), // defend in a stance until the end of the next turn
+    Item(usize),  // use an item, by index into the player's inventory
+}
+
+impl Action {
+    /// Gets the kind of move this action is.
+    pub fn move_type(&self) -> MoveType {
+        match self {
+            Action::Attack => MoveType::AttackMove,
+            Action::Magic(_) => MoveType::MagicMove,
+            Action::Defend(_) => MoveType::DefendMove,
+            Action::Item(_) => MoveType::ItemMove,
+        }
+    }
+}
+
+/// The state of the battle, which tells a front end what input it is waiting on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BattleState {
+    Fighting,   // waiting on the player's next action
+    LevelingUp, // waiting on the player to choose a stat to level up
+    PlayerDied, // the player died, so the battle is over
+}
+
+///Struct to hold the state of a battle.
+///
+/// Only the state of the fight is saved. The game content is given again
+/// when a battle is loaded, so changes to the data files apply to old saves.
+#[derive(Clone, Serialize)]
+pub struct Battle {
+    player: Player,
+    enemies: Vec<Enemy>,  // the living enemies of the current encounter
+    defeated: Vec<Enemy>, // the enemies of the current encounter that have died
+    timeline: Timeline,   // decides who acts next
+    state: BattleState,
+    rules: RuleSet,
+    free_defend: bool, // the legacy rule where defending does not use up the player's turn
+    #[serde(skip)]
+    content: Content, // all game statuses, moves and enemies
+    #[serde(skip)]
+    move_list_available_len: usize, // the length of available moves to the player
+    #[serde(skip)]
+    events: Vec<BattleEvent>, // events that have not been taken by the front end yet
+    seed: u64,         // the seed that the rng was created with
+    rng: GameRng,      // used for every random roll in the battle
+}
+
+/// The saved state of a `Battle`, which is loaded back into a full `Battle`
+/// with `Battle::from_saved`.
+#[derive(Deserialize)]
+pub struct SavedBattle {
+    player: Player,
+    enemies: Vec<Enemy>,
+    defeated: Vec<Enemy>,
+    #[serde(default)]
+    timeline: Timeline,
+    state: BattleState,
+    #[serde(default)]
+    rules: RuleSet,
+    #[serde(default)]
+    free_defend: bool,
+    seed: u64,
+    rng: GameRng,
+}
+
+impl Battle {
+    ///Create new Battle object
+    ///
+    /// # Params
+    /// - `player` - The `Player` fighting in this battle.
+    /// - `enemies` - The group of enemies to start the battle with, or `None` to start with a
+    ///   random encounter.
+    /// - `seed` - The seed for every random roll in the battle.
+    /// - `content` - The statuses, moves and enemies of the game.
+    pub fn new(player: Player, enemies: Option<Vec<Enemy>>, seed: u64, content: Content) -> Self {
+        let mut rng = create_rng(seed);
+        let state = if player.is_dead() {
+            BattleState::PlayerDied
+        } else {
+            BattleState::Fighting
+        };
+
+        let move_list_available_len = Move::get_move_list(content.moves(), player.level());
+
+        let the_enemies = match enemies {
+            //if we were given enemies, use these
+            Some(the_enemies) if !the_enemies.is_empty() => the_enemies,
+            //otherwise, create a new random encounter
+            _ => create_random_encounter(content.enemies(), player.level(), &mut rng),
+        };
+
+        let mut events = Vec::new();
+        push_spawn_events(&the_enemies, &mut events);
+
+        let mut battle = Battle {
+            player,
+            timeline: Timeline::new(the_enemies.len()),
+            enemies: the_enemies,
+            defeated: Vec::new(),
+            state,
+            rules: RuleSet::default(),
+            free_defend: false,
+            content,
+            move_list_available_len,
+            events,
+            seed,
+            rng,
+        };
+
+        // faster enemies get to act before the player
+        battle.run_until_player_turn();
+
+        battle
+    }
+
+    /// Loads a saved battle.
+    ///
+    /// # Params
+    /// - `saved` - The saved state of the battle.
+    /// - `content` - The statuses, moves and enemies of the game.
+    pub fn from_saved(saved: SavedBattle, content: Content) -> Self {
+        let move_list_available_len = Move::get_move_list(content.moves(), saved.player.level());
+
+        // saves from before the timeline start everyone from nothing
+        let timeline = if saved.timeline.num_enemies() == saved.enemies.len() {
+            saved.timeline
+        } else {
+            Timeline::new(saved.enemies.len())
+        };
+
+        Battle {
+            player: saved.player,
+            enemies: saved.enemies,
+            defeated: saved.defeated,
+            timeline,
+            state: saved.state,
+            rules: saved.rules,
+            free_defend: saved.free_defend,
+            content,
+            move_list_available_len,
+            events: Vec::new(),
+            seed: saved.seed,
+            rng: saved.rng,
+        }
+    }
+
+    /// Builder function for setting the rules of the battle.
+    ///
+    /// # Params
+    /// - `rules` - The rule set to play with.
+    ///
+    /// # Returns
+    /// - The `Battle` object.
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Builder function for turning on the legacy rule where defending does not use up
+    /// the player's turn.
+    ///
+    /// # Params
+    /// - `free_defend` - If defending is free.
+    ///
+    /// # Returns
+    /// - The `Battle` object.
+    pub fn with_free_defend(mut self, free_defend: bool) -> Self {
+        self.free_defend = free_defend;
+        self
+    }
+
+    /// Makes the player take their turn with the given action, then lets the
+    /// enemies act until it is the player's turn again.
+    ///
+    /// # Params
+    /// - `action` - The `Action` the player is taking.
+    /// - `target` - The index of the enemy the action is aimed at (ignored when defending,
+    ///   or using an item on the player).
+    ///
+    /// # Returns
+    /// - What the action did, or why it could not be done (such as when the player
+    ///   does not have enough mana), in which case the player can choose again.
+    pub fn take_turn(
+        &mut self,
+        action: Action,
+        target: usize,
+    ) -> Result<ActionOutcome, ActionError> {
+        self.check_action(action, target)?;
+
+        let (outcome, clashed) = match self.rules {
+            RuleSet::Classic => (self.do_player_turn(action, target)?, false),
+            RuleSet::Triangle => self.do_clash(action, target)?,
+        };
+
+        // defending is only free under the legacy rule, and never when the target acted at the same time
+        let is_free = self.free_defend && !clashed && action.move_type() == MoveType::DefendMove;
+        if !is_free {
+            self.end_turn(Combatant::Player);
+            if clashed && !self.enemies[target].is_dead() {
+                self.end_turn(Combatant::Enemy(target));
+            }
+            self.check_entities();
+            self.run_until_player_turn();
+        }
+
+        Ok(outcome)
+    }
+
+    /// Checks if the player could take their turn with an action, without taking it.
+    ///
+    /// # Params
+    /// - `action` - The `Action` the player would take.
+    /// - `target` - The index of the enemy the action would be aimed at.
+    ///
+    /// # Returns
+    /// - Why the action could not be done, if it couldn't.
+    pub fn check_action(&self, action: Action, target: usize) -> Result<(), ActionError> {
+        if self.state != BattleState::Fighting {
+            return Err(ActionError::AlreadyActed);
+        }
+        if target >= self.enemies.len() {
+            return Err(ActionError::InvalidTarget { target });
+        }
+
+        match action {
+            Action::Magic(move_idx) => {
+                if move_idx >= self.move_list_available_len {
+                    return Err(ActionError::UnknownMove { move_idx });
+                }
+                self.player.check_cast(&self.content.moves()[move_idx])
+            }
+            Action::Item(stack_idx) => self.player.check_item(stack_idx).map(|_| ()),
+            Action::Attack | Action::Defend(_) => Ok(()),
+        }
+    }
+
+    /// Levels up the player with the chosen stat, returning to the fight.
+    ///
+    /// # Params
+    /// - `level_type` - The stat to increase.
+    ///
+    /// # Returns
+    /// - `true` if the player leveled up, `false` if the player was not leveling up.
+    pub fn level_up(&mut self, level_type: LevelUpType) -> bool {
+        if self.state != BattleState::LevelingUp {
+            return false;
+        }
+
+        self.player.level_up(level_type);
+        self.events.push(BattleEvent::LevelUp {
+            name: self.player.name(),
+            level: self.player.level(),
+        });
+
+        // since we leveled up, we can now check what moves that are available.
+        self.move_list_available_len =
+            Move::get_move_list(self.content.moves(), self.player.level());
+        self.state = BattleState::Fighting;
+
+        // the next encounter was waiting on the level up
+        self.run_until_player_turn();
+
+        true
+    }
+
+    /// Makes the player put on a piece of gear from their bag. Changing
+    /// equipment does not use up the player's turn.
+    ///
+    /// # Params
+    /// - `bag_idx` - The index of the gear in the player's bag.
+    ///
+    /// # Returns
+    /// - `true` if the gear was equipped, `false` if there is no such gear or the player has died.
+    pub fn equip(&mut self, bag_idx: usize) -> bool {
+        self.is_playing() && self.player.equip(bag_idx)
+    }
+
+    /// Makes the player take off the gear in a slot, putting it in their bag.
+    ///
+    /// # Params
+    /// - `slot` - The slot to empty.
+    ///
+    /// # Returns
+    /// - `true` if the gear was taken off, `false` if the slot was empty or the player has died.
+    pub fn unequip(&mut self, slot: EquipSlot) -> bool {
+        self.is_playing() && self.player.unequip(slot)
+    }
+
+    /// Gets the rules the battle is played with.
+    pub fn rules(&self) -> RuleSet {
+        self.rules
+    }
+
+    /// Gets the current state of the battle.
+    pub fn state(&self) -> BattleState {
+        self.state
+    }
+
+    /// Checks if the battle is still being played.
+    ///
+    /// # Returns
+    /// - `true` if the player is still alive, `false` otherwise.
+    pub fn is_playing(&self) -> bool {
+        self.state != BattleState::PlayerDied
+    }
+
+    /// Gets the seed that this battle was created with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Gets the statuses, moves and enemies of the game.
+    pub fn content(&self) -> &Content {
+        &self.content
+    }
+
+    /// Gets the `Player` in this battle.
+    pub fn player(&self) -> &Player {
+        &self.player
+    }
+
+    /// Gets the living enemies the player is currently fighting.
+    pub fn enemies(&self) -> &[Enemy] {
+        &self.enemies
+    }
+
+    /// Gets who acts in the turns after the player's current one.
+    ///
+    /// # Params
+    /// - `count` - How many turns to look ahead.
+    ///
+    /// # Returns
+    /// - The `Combatant` of each upcoming turn, soonest first.
+    pub fn turn_order(&self, count: usize) -> Vec<Combatant> {
+        self.timeline
+            .preview(self.player.speed(), &self.enemy_speeds(), count)
+    }
+
+    /// Gets the name of a combatant in this battle.
+    ///
+    /// # Params
+    /// - `combatant` - The combatant, such as one from `turn_order`.
+    pub fn combatant_name(&self, combatant: Combatant) -> String {
+        match combatant {
+            Combatant::Player => self.player.name(),
+            Combatant::Enemy(i) => self.enemies[i].name(),
+        }
+    }
+
+    /// Gets the moves that are available to the player at their current level.
+    pub fn available_moves(&self) -> &[Move] {
+        &self.content.moves()[..self.move_list_available_len]
+    }
+
+    /// Takes the events that have happened since the last call, oldest first.
+    ///
+    /// Front ends should call this after every action, as events are kept
+    /// until they are taken.
+    pub fn take_events(&mut self) -> Vec<BattleEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Lets the enemies take their turns, in the order decided by the timeline,
+    /// until it is the player's turn again.
+    fn run_until_player_turn(&mut self) {
+        while self.state == BattleState::Fighting {
+            let next = self
+                .timeline
+                .next(self.player.speed(), &self.enemy_speeds());
+
+            // a stunned combatant loses its turn, but its statuses still tick
+            let entity: &dyn Entity = match next {
+                Combatant::Player => &self.player,
+                Combatant::Enemy(i) => &self.enemies[i],
+            };
+            if let Some(status) = entity.stunned_by() {
+                self.events.push(BattleEvent::TurnSkipped {
+                    name: entity.name(),
+                    status,
+                });
+                self.end_turn(next);
+                self.check_entities();
+                continue;
+            }
+
+            match next {
+                Combatant::Player => break,
+                Combatant::Enemy(enemy_idx) => {
+                    self.do_enemy_turn(enemy_idx);
+                    self.end_turn(next);
+                    self.check_entities();
+                }
+            }
+        }
+    }
+
+    /// Gets the speed of each enemy, in the same order as the enemies.
+    fn enemy_speeds(&self) -> Vec<u32> {
+        self.enemies.iter().map(|enemy| enemy.speed()).collect()
+    }
+
+    /// Ends a combatant's turn and does any required activities before the turn is over.
+    ///
+    /// # Params
+    /// - `combatant` - The combatant whose turn is ending.
+    fn end_turn(&mut self, combatant: Combatant) {
+        let entity: &mut dyn Entity = match combatant {
+            Combatant::Player => &mut self.player,
+            Combatant::Enemy(i) => &mut self.enemies[i],
+        };
+
+        entity.tick_statuses(&mut self.rng, &mut self.events);
+        entity.tick_stance(&mut self.events);
+        entity.regen_mana(&mut self.events);
+        // modifiers run out after the statuses, so defending still softens this turn's ticks
+        entity.tick_modifiers(&mut self.events);
+    }
+
+    /// Does the player's turn based on the player's choice of action.
+    ///
+    /// # Params
+    /// - `action` - The action the player is taking.
+    /// - `target` - The index of the enemy the action is aimed at.
+    fn do_player_turn(
+        &mut self,
+        action: Action,
+        target: usize,
+    ) -> Result<ActionOutcome, ActionError> {
+        // do the action that the player wishes.
+        // It is possible that these actions fail, such as when the player doesn't have the mana.
+        // In this case, nothing occurs.
+        match action {
+            Action::Attack => {
+                self.player
+                    .attack_move(&mut self.enemies[target], &mut self.rng, &mut self.events)
+            }
+
+            Action::Magic(move_idx) => {
+                if move_idx >= self.move_list_available_len {
+                    return Err(ActionError::UnknownMove { move_idx });
+                }
+
+                let the_move = &self.content.moves()[move_idx];
+                // aim the move at the enemies its target rule allows
+                let targets: Vec<&mut dyn Entity> = match the_move.target() {
+                    MoveTarget::User => Vec::new(),
+                    MoveTarget::SingleEnemy => vec![&mut self.enemies[target]],
+                    MoveTarget::AllEnemies => self
+                        .enemies
+                        .iter_mut()
+                        .map(|enemy| enemy as &mut dyn Entity)
+                        .collect(),
+                };
+
+                self.player
+                    .magic_move(targets, the_move, &mut self.rng, &mut self.events)
+            }
+
+            Action::Defend(stance) => self.player.defend_move(stance, &mut self.events),
+
+            Action::Item(stack_idx) => {
+                self.player
+                    .item_move(stack_idx, &mut self.enemies[target], &mut self.events)
+            }
+        }
+    }
+
+    /// Does the player's turn under `RuleSet::Triangle`. The enemy the player aims at
+    /// commits its action at the same time, and the two actions clash.
+    ///
+    /// # Params
+    /// - `action` - The action the player is taking.
+    /// - `target` - The index of the enemy the action is aimed at.
+    ///
+    /// # Returns
+    /// - What the player's action did, and if the target acted in the clash.
+    fn do_clash(
+        &mut self,
+        action: Action,
+        target: usize,
+    ) -> Result<(ActionOutcome, bool), ActionError> {
+        // the action was checked in `take_turn`, so it can go ahead before the target commits to anything.
+        // items are outside of the triangle
+        if let Action::Item(_) = action {
+            return Ok((self.do_player_turn(action, target)?, false));
+        }
+
+        // a stunned target can't commit to an action
+        if self.enemies[target].stunned_by().is_some() {
+            return Ok((self.do_player_turn(action, target)?, false));
+        }
+        let enemy_action = self.choose_enemy_action(target);
+
+        // the target spent its next turn acting in the clash
+        self.timeline.spend_turn(Combatant::Enemy(target));
+
+        let player_move = action.move_type();
+        let enemy_move = enemy_action.move_type();
+        let player_won = rules::beats(player_move, enemy_move);
+        let enemy_won = rules::beats(enemy_move, player_move);
+        if player_won || enemy_won {
+            let (winner, winning_move, loser, losing_move) = if player_won {
+                (
+                    self.player.name(),
+                    player_move,
+                    self.enemies[target].name(),
+                    enemy_move,
+                )
+            } else {
+                (
+                    self.enemies[target].name(),
+                    enemy_move,
+                    self.player.name(),
+                    player_move,
+                )
+            };
+            self.events.push(BattleEvent::Clash {
+                winner,
+                winning_move,
+                loser,
+                losing_move,
+            });
+        }
+
+        // both guards go up before any blows land
+        let mut outcome = ActionOutcome::Beaten;
+        if enemy_move == MoveType::DefendMove {
+            self.do_enemy_action(target, enemy_action);
+        }
+        if let Action::Defend(stance) = action {
+            outcome = self.player.defend_move(stance, &mut self.events)?;
+        }
+
+        // magic goes straight through the guard it beats
+        if player_won && player_move == MoveType::MagicMove {
+            self.enemies[target].remove_modifiers(&ModifierSource::Defending);
+        }
+        if enemy_won && enemy_move == MoveType::MagicMove {
+            self.player.remove_modifiers(&ModifierSource::Defending);
+        }
+
+        // a beaten attack or spell never happens
+        if player_move != MoveType::DefendMove && !enemy_won {
+            outcome = self.do_player_turn(action, target)?;
+        }
+        if enemy_move != MoveType::DefendMove
+            && !player_won
+            && !self.enemies[target].is_dead()
+            && !self.player.is_dead()
+        {
+            self.do_enemy_action(target, enemy_action);
+        }
+
+        Ok((outcome, true))
+    }
+
+    /// Does an enemy's turn, allowing the enemy to choose
+    /// what to do in this turn.
+    ///
+    /// # Params
+    /// - `enemy_idx` - The index of the enemy taking its turn.
+    fn do_enemy_turn(&mut self, enemy_idx: usize) {
+        //get the turn type
+        let action = self.choose_enemy_action(enemy_idx);
+        self.do_enemy_action(enemy_idx, action);
+    }
+
+    /// Lets an enemy choose its action. An enemy that chooses a move it can't cast
+    /// attacks instead, which is decided before any clash is judged.
+    ///
+    /// # Params
+    /// - `enemy_idx` - The index of the enemy choosing.
+    ///
+    /// # Returns
+    /// - The action the enemy commits to.
+    fn choose_enemy_action(&mut self, enemy_idx: usize) -> Action {
+        let enemy = &self.enemies[enemy_idx];
+
+        match enemy.get_turn_type(&self.player, self.content.ai_registry(), &mut self.rng) {
+            Action::Magic(move_idx) if enemy.check_magic(move_idx).is_err() => Action::Attack,
+            action => action,
+        }
+    }
+
+    /// Does the action an enemy chose.
+    ///
+    /// # Params
+    /// - `enemy_idx` - The index of the enemy acting.
+    /// - `action` - The action the enemy chose.
+    fn do_enemy_action(&mut self, enemy_idx: usize, action: Action) {
+        let enemy = &mut self.enemies[enemy_idx];
+
+        // the action was checked when the enemy chose it, so there is nothing to report.
+        // a move that can no longer be cast, such as after being silenced in a clash, fizzles
+        let _ = match action {
+            Action::Attack => enemy.attack_move(&mut self.player, &mut self.rng, &mut self.events),
+
+            Action::Magic(move_idx) => {
+                enemy.magic_move(&mut self.player, move_idx, &mut self.rng, &mut self.events)
+            }
+
+            Action::Defend(stance) => enemy.defend_move(stance, &mut self.events),
+
+            // enemies don't carry items
+            Action::Item(_) => return,
+        };
+    }
+
+    ///Checks if entities are dead and creates
+    ///a new random encounter once every enemy has died.
+    ///
+    ///If the player dies, the game is over.
+    fn check_entities(&mut self) {
+        if self.player.is_dead() {
+            self.state = BattleState::PlayerDied;
+            self.events.push(BattleEvent::EntityDied {
+                name: self.player.name(),
+            });
+        } else {
+            // move the dead enemies out of the fight
+            let mut i = 0;
+            while i < self.enemies.len() {
+                if self.enemies[i].is_dead() {
+                    let enemy = self.enemies.remove(i);
+                    self.timeline.remove_enemy(i);
+                    self.events
+                        .push(BattleEvent::EntityDied { name: enemy.name() });
+                    self.player.restore_kill_mana(&mut self.events);
+                    self.defeated.push(enemy);
+                } else {
+                    i += 1;
+                }
+            }
+
+            // the xp is only dropped once the whole group is defeated
+            if self.enemies.is_empty() {
+                let mut xp_dropped = 0;
+                for enemy in self.defeated.drain(..) {
+                    xp_dropped += enemy.drop_xp(self.player.level(), &mut self.events);
+
+                    for loot in enemy.drop_loot(&mut self.rng, &mut self.events) {
+                        match loot {
+                            Loot::Item(item) => self.player.add_item(item, 1),
+                            Loot::Gear(gear) => self.player.add_gear(gear),
+                        }
+                    }
+                }
+
+                if self.player.gain_xp(xp_dropped) {
+                    self.state = BattleState::LevelingUp;
+                }
+
+                // create the encounter after the xp is dropped
+                self.enemies = create_random_encounter(
+                    self.content.enemies(),
+                    self.player.level(),
+                    &mut self.rng,
+                );
+                push_spawn_events(&self.enemies, &mut self.events);
+
+                // everyone starts the new encounter from nothing
+                self.timeline = Timeline::new(self.enemies.len());
+            }
+        }
+    }
+}
+
+///Creates a new random encounter, which is a group of one kind of monster
+///
+/// # Params
+/// - `enemy_list` - All of the enemies in the game.
+/// - `player_level` - The level of the player, to pick enemies that they can fight.
+/// - `rng` - The random number generator of the game.
+fn create_random_encounter(
+    enemy_list: &[Enemy],
+    player_level: u32,
+    rng: &mut GameRng,
+) -> Vec<Enemy> {
+    let possible_enemies = get_possible_enemies(enemy_list, player_level);
+    // pick a random enemy from the list
+    let random_index = rng.gen_range(0..possible_enemies.len());
+    let enemy = &possible_enemies[random_index];
+
+    let group_size = rng.gen_range(1..=enemy.max_group_size());
+    if group_size == 1 {
+        return vec![enemy.clone()];
+    }
+
+    // give each enemy in the group its own name, so the log can tell them apart
+    (0..group_size)
+        .map(|i| {
+            let letter = char::from(b'A' + (i % 26) as u8);
+            enemy
+                .clone()
+                .with_name(format!("{} {}", enemy.name(), letter))
+        })
+        .collect()
+}
+
+/// Pushes the event for each enemy of a new encounter appearing.
+///
+/// # Params
+/// - `enemies` - The enemies that appeared.
+/// - `events` - The events of the battle.
+fn push_spawn_events(enemies: &[Enemy], events: &mut Vec<BattleEvent>) {
+    for enemy in enemies {
+        events.push(BattleEvent::EnemySpawned {
+            name: enemy.name(),
+            level: enemy.level(),
+        });
+    }
+}
+
+/// Gets the possible enemies that the player can fight.
+///
+/// # Returns
+/// - A list of enemies that the player can fight, based on level.
+fn get_possible_enemies(enemy_list: &[Enemy], player_level: u32) -> Vec<Enemy> {
+    // we can fight an enemy if it is below or close to the player's level
+    enemy_list
+        .iter()
+        .filter(|enemy| enemy.level() <= player_level + MAX_ENEMY_LEVELS_ABOVE_PLAYER)
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity_components::{
+        ai::{AiPolicy, EnemyAi},
+        moves::ElementType,
+        stats::Stats,
+        status::Status,
+    };
+
+    const SEED: u64 = 42;
+    const MAX_TURNS: u32 = 50; // stops a broken test from looping forever
+
+    /// Creates a player that hits hard, starting with some xp.
+    fn strong_player(xp: u32) -> Player {
+        Player::new(
+            String::from("Hero"),
+            Stats::new(10, 10, 10, 50, 10, 0),
+            1,
+            xp,
+        )
+    }
+
+    /// Creates an enemy with 5 health that never hurts anyone.
+    fn weak_enemy() -> Enemy {
+        Enemy::new(String::from("Dummy"), Stats::new(1, 0, 1, 0, 0, 0), 1)
+    }
+
+    /// Attacks the first enemy until the player has to level up or the encounter
+    /// is over, returning every event of the battle.
+    fn attack_until_kill(battle: &mut Battle) -> Vec<BattleEvent> {
+        let mut events = battle.take_events();
+        for _ in 0..MAX_TURNS {
+            battle.take_turn(Action::Attack, 0).unwrap();
+            events.extend(battle.take_events());
+            if events
+                .iter()
+                .any(|event| matches!(event, BattleEvent::XpGained { .. }))
+            {
+                return events;
+            }
+        }
+        panic!("the enemy never died");
+    }
+
+    /// Plays a random battle with a fixed list of actions, returning every event.
+    fn play_scripted(seed: u64) -> Vec<BattleEvent> {
+        let player = Player::default().with_inventory(Content::built_in().starting_inventory());
+        let mut battle = Battle::new(player, None, seed, Content::built_in());
+        let actions = [
+            Action::Attack,
+            Action::Magic(0),
+            Action::Defend(DefendStance::Parry),
+            Action::Attack,
+            Action::Item(0),
+            Action::Attack,
+            Action::Magic(0),
+            Action::Attack,
+        ];
+
+        let mut events = battle.take_events();
+        for action in actions.iter().cycle().take(30) {
+            match battle.state() {
+                BattleState::Fighting => {
+                    // an action that can't be done is skipped the same way in every run
+                    let _ = battle.take_turn(*action, 0);
+                }
+                BattleState::LevelingUp => {
+                    battle.level_up(LevelUpType::Magic);
+                }
+                BattleState::PlayerDied => break,
+            }
+            events.extend(battle.take_events());
+        }
+
+        events
+    }
+
+    #[test]
+    fn same_seed_plays_out_the_same() {
+        let events = play_scripted(SEED);
+
+        assert!(!events.is_empty());
+        assert_eq!(events, play_scripted(SEED));
+    }
+
+    #[test]
+    fn different_seed_plays_out_differently() {
+        assert_ne!(play_scripted(SEED), play_scripted(SEED + 1));
+    }
+
+    #[test]
+    fn killing_an_enemy_gives_xp() {
+        let mut battle = Battle::new(
+            strong_player(0),
+            Some(vec![weak_enemy()]),
+            SEED,
+            Content::built_in(),
+        );
+
+        let events = attack_until_kill(&mut battle);
+
+        assert!(events.contains(&BattleEvent::EntityDied {
+            name: String::from("Dummy"),
+        }));
+        assert!(events.contains(&BattleEvent::XpGained {
+            source: String::from("Dummy"),
+            amount: 20,
+        }));
+        assert_eq!(battle.player().experience(), 20);
+        assert_eq!(battle.state(), BattleState::Fighting);
+        // a new encounter takes the place of the dead enemy
+        assert!(!battle.enemies().is_empty());
+    }
+
+    #[test]
+    fn group_gives_xp_once_the_last_enemy_dies() {
+        let group = vec![
+            weak_enemy().with_name(String::from("Dummy A")),
+            weak_enemy().with_name(String::from("Dummy B")),
+        ];
+        let mut battle = Battle::new(strong_player(0), Some(group), SEED, Content::built_in());
+        battle.take_events();
+
+        let mut events = Vec::new();
+        for _ in 0..MAX_TURNS {
+            battle.take_turn(Action::Attack, 0).unwrap();
+            events.extend(battle.take_events());
+            if battle.enemies().len() < 2 {
+                break;
+            }
+        }
+
+        // the first kill leaves the rest of the group to fight, without any xp
+        assert!(events.contains(&BattleEvent::EntityDied {
+            name: String::from("Dummy A"),
+        }));
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, BattleEvent::XpGained { .. })));
+        assert_eq!(battle.enemies()[0].name(), "Dummy B");
+        assert_eq!(battle.player().experience(), 0);
+
+        let events = attack_until_kill(&mut battle);
+        let xp_sources: Vec<&str> = events
+            .iter()
+            .filter_map(|event| match event {
+                BattleEvent::XpGained { source, .. } => Some(source.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(xp_sources, ["Dummy A", "Dummy B"]);
+        assert_eq!(battle.player().experience(), 40);
+    }
+
+    #[test]
+    fn enough_xp_moves_to_leveling_up() {
+        let mut battle = Battle::new(
+            strong_player(90),
+            Some(vec![weak_enemy()]),
+            SEED,
+            Content::built_in(),
+        );
+
+        attack_until_kill(&mut battle);
+
+        assert_eq!(battle.state(), BattleState::LevelingUp);
+        assert_eq!(
+            battle.take_turn(Action::Attack, 0),
+            Err(ActionError::AlreadyActed)
+        );
+
+        assert!(battle.level_up(LevelUpType::Strength));
+        assert_eq!(battle.player().level(), 2);
+        assert_eq!(battle.state(), BattleState::Fighting);
+        assert!(!battle.level_up(LevelUpType::Strength));
+    }
+
+    #[test]
+    fn player_death_moves_to_player_died() {
+        let player = Player::new(String::from("Hero"), Stats::new(1, 0, 1, 0, 0, 0), 1, 0);
+        let brute = Enemy::new(String::from("Brute"), Stats::new(50, 0, 50, 50, 0, 0), 1);
+        let mut battle = Battle::new(player, Some(vec![brute]), SEED, Content::built_in());
+
+        let mut turns = 0;
+        while battle.state() == BattleState::Fighting && turns < MAX_TURNS {
+            battle
+                .take_turn(Action::Defend(DefendStance::Guard), 0)
+                .unwrap();
+            turns += 1;
+        }
+
+        assert_eq!(battle.state(), BattleState::PlayerDied);
+        assert!(!battle.is_playing());
+        assert!(battle.take_events().contains(&BattleEvent::EntityDied {
+            name: String::from("Hero"),
+        }));
+        assert_eq!(
+            battle.take_turn(Action::Attack, 0),
+            Err(ActionError::AlreadyActed)
+        );
+    }
+
+    #[test]
+    fn invalid_target_does_not_use_the_turn() {
+        let mut battle = Battle::new(
+            strong_player(0),
+            Some(vec![weak_enemy()]),
+            SEED,
+            Content::built_in(),
+        );
+        battle.take_events();
+
+        assert_eq!(
+            battle.take_turn(Action::Attack, 3),
+            Err(ActionError::InvalidTarget { target: 3 })
+        );
+        assert!(battle.take_events().is_empty());
+    }
+
+    #[test]
+    fn enemy_that_cant_cast_commits_to_an_attack() {
+        // enemies can't build focus, so this move can never be cast
+        let content = Content::built_in();
+        let starfall = content
+            .moves()
+            .iter()
+            .find(|the_move| the_move.focus_cost() > 0)
+            .unwrap()
+            .clone();
+        let caster = Enemy::new(String::from("Caster"), Stats::new(20, 0, 1, 5, 0, 0), 1)
+            .with_moves(vec![starfall])
+            .with_ai(AiPolicy::Caster);
+        let mut battle = Battle::new(strong_player(0), Some(vec![caster]), SEED, content)
+            .with_rules(RuleSet::Triangle);
+        battle.take_events();
+
+        battle
+            .take_turn(Action::Defend(DefendStance::default()), 0)
+            .unwrap();
+
+        // the guard beats the attack, rather than being pierced by a spell that never happens
+        let clash = battle
+            .take_events()
+            .into_iter()
+            .find(|event| matches!(event, BattleEvent::Clash { .. }));
+        assert!(matches!(
+            clash,
+            Some(BattleEvent::Clash {
+                winning_move: MoveType::DefendMove,
+                losing_move: MoveType::AttackMove,
+                ..
+            })
+        ));
+    }
+
+    /// Creates a sturdy enemy that attacks as fast as the player.
+    fn brute() -> Enemy {
+        Enemy::new(String::from("Brute"), Stats::new(50, 20, 10, 5, 0, 0), 1)
+    }
+
+    /// Defends in a stance every turn, returning every event once `is_done` finds the one
+    /// it is waiting for.
+    fn defend_until(
+        battle: &mut Battle,
+        stance: DefendStance,
+        is_done: impl Fn(&BattleEvent) -> bool,
+    ) -> Vec<BattleEvent> {
+        let mut events = battle.take_events();
+        for _ in 0..MAX_TURNS {
+            battle.take_turn(Action::Defend(stance), 0).unwrap();
+            events.extend(battle.take_events());
+            if events.iter().any(&is_done) {
+                return events;
+            }
+        }
+        panic!("the event never happened");
+    }
+
+    #[test]
+    fn guard_blocks_statuses() {
+        let venom = Move::new(
+            String::from("Venom"),
+            1,
+            1,
+            1,
+            ElementType::None,
+            Some(Status::new(String::from("Poison"), 2, false, 0, 3)),
+        );
+        let witch = brute()
+            .with_name(String::from("Witch"))
+            .with_moves(vec![venom])
+            .with_ai(AiPolicy::Caster);
+        let mut battle = Battle::new(
+            strong_player(0),
+            Some(vec![witch]),
+            SEED,
+            Content::built_in(),
+        );
+
+        let events = defend_until(&mut battle, DefendStance::Guard, |event| {
+            matches!(event, BattleEvent::StatusBlocked { .. })
+        });
+
+        assert!(events.contains(&BattleEvent::StatusBlocked {
+            status: String::from("Poison"),
+            target: String::from("Hero"),
+        }));
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, BattleEvent::StatusApplied { .. })));
+        assert!(battle.player().statuses().is_empty());
+    }
+
+    #[test]
+    fn parry_counters_attacks() {
+        let mut battle = Battle::new(
+            strong_player(0),
+            Some(vec![brute()]),
+            SEED,
+            Content::built_in(),
+        );
+
+        let events = defend_until(&mut battle, DefendStance::Parry, |event| {
+            matches!(event, BattleEvent::Countered { .. })
+        });
+
+        let counter = events
+            .iter()
+            .find(|event| matches!(event, BattleEvent::Countered { .. }));
+        assert!(matches!(
+            counter,
+            Some(BattleEvent::Countered { attacker, target, amount })
+                if attacker == "Hero" && target == "Brute" && *amount > 0
+        ));
+        assert!(battle.enemies()[0].health() < battle.enemies()[0].max_health());
+    }
+
+    #[test]
+    fn brace_restores_mana() {
+        let mut player = strong_player(0);
+        player.use_mana(player.max_mana());
+        let brace_mana = player.stats().calc_brace_mana();
+        let mut battle = Battle::new(player, Some(vec![weak_enemy()]), SEED, Content::built_in());
+        battle.take_events();
+
+        battle
+            .take_turn(Action::Defend(DefendStance::Brace), 0)
+            .unwrap();
+
+        assert!(battle.take_events().contains(&BattleEvent::ManaRestored {
+            target: String::from("Hero"),
+            amount: brace_mana,
+        }));
+    }
+
+    /// Defends once against an enemy as fast as the player, returning if the enemy got to act.
+    fn enemy_acts_after_defending(free_defend: bool) -> bool {
+        let mut battle = Battle::new(
+            strong_player(0),
+            Some(vec![brute()]),
+            SEED,
+            Content::built_in(),
+        )
+        .with_free_defend(free_defend);
+        battle.take_events();
+
+        battle
+            .take_turn(Action::Defend(DefendStance::Guard), 0)
+            .unwrap();
+
+        battle.take_events().iter().any(|event| match event {
+            BattleEvent::DamageDealt { attacker, .. }
+            | BattleEvent::AttackMissed { attacker, .. } => attacker == "Brute",
+            _ => false,
+        })
+    }
+
+    #[test]
+    fn defending_uses_the_turn() {
+        assert!(enemy_acts_after_defending(false));
+    }
+
+    #[test]
+    fn free_defend_keeps_the_turn() {
+        assert!(!enemy_acts_after_defending(true));
+    }
+
+    /// A custom AI that only ever defends.
+    struct Turtle;
+
+    impl EnemyAi for Turtle {
+        fn choose_action(
+            &self,
+            _enemy: &Enemy,
+            _target: &dyn Entity,
+            _rng: &mut GameRng,
+        ) -> Action {
+            Action::Defend(DefendStance::default())
+        }
+    }
+
+    /// Plays one turn against a sturdy enemy with a custom AI, returning the events.
+    fn play_custom_ai(content: Content) -> Vec<BattleEvent> {
+        let enemy = Enemy::new(String::from("Shell"), Stats::new(50, 0, 10, 1, 0, 0), 1).with_ai(
+            AiPolicy::Custom {
+                name: String::from("Turtle"),
+            },
+        );
+        let mut battle = Battle::new(Player::default(), Some(vec![enemy]), SEED, content);
+
+        for _ in 0..3 {
+            battle.take_turn(Action::Attack, 0).unwrap();
+        }
+        battle.take_events()
+    }
+
+    #[test]
+    fn custom_ai_is_plugged_in_by_name() {
+        let events = play_custom_ai(Content::built_in().with_ai("Turtle", Turtle));
+
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, BattleEvent::Defending { name, .. } if name == "Shell")));
+    }
+
+    #[test]
+    fn custom_ai_that_is_not_plugged_in_attacks() {
+        let events = play_custom_ai(Content::built_in());
+
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, BattleEvent::Defending { .. })));
+    }
+}
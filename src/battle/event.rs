@@ -0,0 +1,126 @@
+// file for the events emitted by a battle
+use serde::{Deserialize, Serialize};
+
+use crate::entity_components::{
+    affinity::Affinity,
+    moves::ElementType,
+    moves::MoveType,
+    stats::{DefendStance, ModifierSource, StatType},
+};
+
+/// Something that happened during a battle.
+///
+/// Entities push these as they act, and front ends decide how to show them
+/// (such as rendering them as text in the battle log). They are written as JSON
+/// with their variant name in `kind`, such as `{"kind": "Healed", "target": "Louie", "amount": 5}`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum BattleEvent {
+    /// Two entities' actions clashed under `RuleSet::Triangle`, and one won.
+    Clash {
+        winner: String,
+        winning_move: MoveType,
+        loser: String,
+        losing_move: MoveType,
+    },
+    /// An entity cast a magic move.
+    MagicCast { caster: String, move_name: String },
+    /// An entity dealt damage to another entity.
+    DamageDealt {
+        attacker: String,
+        target: String,
+        amount: u32,
+    },
+    /// An entity's attack missed another entity.
+    AttackMissed { attacker: String, target: String },
+    /// An entity's attack was a critical hit.
+    CriticalHit { attacker: String, target: String },
+    /// A move hit an entity that is not `Affinity::Normal` to its element.
+    ElementalHit {
+        target: String,
+        element: ElementType,
+        affinity: Affinity,
+    },
+    /// An entity was healed.
+    Healed { target: String, amount: u32 },
+    /// An entity took mana from another entity.
+    ManaDrained {
+        caster: String,
+        target: String,
+        amount: u32,
+    },
+    /// An entity got a shield, which soaks up damage before its health.
+    Shielded { target: String, amount: u32 },
+    /// An entity got mana back.
+    ManaRestored { target: String, amount: u32 },
+    /// An entity got back its mana for the end of its turn.
+    ManaRegenerated { target: String, amount: u32 },
+    /// The player built up focus by landing a hit.
+    FocusGained { target: String, amount: u32 },
+    /// An entity used an item.
+    ItemUsed { user: String, item: String },
+    /// An enemy dropped an item for the player.
+    ItemDropped { source: String, item: String },
+    /// A status was removed from an entity before it ran out of turns.
+    StatusCured { status: String, target: String },
+    /// A stat of an entity was raised or lowered, such as by a spell.
+    StatModified {
+        target: String,
+        stat: StatType,
+        is_raised: bool,
+    },
+    /// A modifier on an entity's stats ran out.
+    ModifierExpired {
+        source: ModifierSource,
+        target: String,
+    },
+    /// An entity started defending.
+    Defending {
+        name: String,
+        stance: DefendStance,
+        num_turns: u32,
+    },
+    /// A parrying entity countered an attack.
+    Countered {
+        attacker: String,
+        target: String,
+        amount: u32,
+    },
+    /// A guarding entity blocked a status.
+    StatusBlocked { status: String, target: String },
+    /// A status that was already on an entity got more intense.
+    StatusStacked {
+        status: String,
+        target: String,
+        stacks: u32,
+    },
+    /// A status on one entity reacted to something, hurting another entity.
+    StatusTriggered {
+        status: String,
+        owner: String,
+        target: String,
+        amount: u32,
+    },
+    /// An entity could not act on its turn because of a status.
+    TurnSkipped { name: String, status: String },
+    /// A status was applied to an entity.
+    StatusApplied { status: String, target: String },
+    /// A status damaged or healed the entity it was applied to.
+    StatusTicked {
+        status: String,
+        source: Option<String>, // whoever applied the status, if anyone did
+        target: String,
+        amount: u32,
+        is_healing: bool,
+    },
+    /// A status ran out of turns and was removed from an entity.
+    StatusExpired { status: String, target: String },
+    /// A new enemy appeared.
+    EnemySpawned { name: String, level: u32 },
+    /// An entity died.
+    EntityDied { name: String },
+    /// An enemy dropped xp for the player.
+    XpGained { source: String, amount: u32 },
+    /// An entity reached a new level.
+    LevelUp { name: String, level: u32 },
+}
@@ -0,0 +1,207 @@
+// file for deciding who acts next in a battle, based on speed
+use serde::{Deserialize, Serialize};
+
+/// How much initiative a combatant needs to take a turn.
+pub const TURN_THRESHOLD: u32 = 100;
+
+/// Someone taking part in a battle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Combatant {
+    Player,
+    Enemy(usize), // by index into the living enemies
+}
+
+/// Tracks the initiative of every combatant, ATB style.
+///
+/// Time passes in ticks, and each tick every combatant gains initiative equal
+/// to their speed. Once a combatant reaches `TURN_THRESHOLD` they take a turn,
+/// so faster combatants act first and more often.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Timeline {
+    player: u32,       // the initiative of the player
+    enemies: Vec<u32>, // the initiative of each enemy, in the same order as the enemies
+}
+
+impl Timeline {
+    /// Creates a timeline where nobody has any initiative yet.
+    ///
+    /// # Params
+    /// - `num_enemies` - The number of enemies in the fight.
+    pub fn new(num_enemies: usize) -> Self {
+        Timeline {
+            player: 0,
+            enemies: vec![0; num_enemies],
+        }
+    }
+
+    /// Gets the number of enemies this timeline is tracking.
+    pub fn num_enemies(&self) -> usize {
+        self.enemies.len()
+    }
+
+    /// Stops tracking an enemy, such as when it dies.
+    ///
+    /// # Params
+    /// - `enemy_idx` - The index of the enemy.
+    pub fn remove_enemy(&mut self, enemy_idx: usize) {
+        self.enemies.remove(enemy_idx);
+    }
+
+    /// Passes time until someone can act, and takes their turn off of the timeline.
+    ///
+    /// # Params
+    /// - `player_speed` - The speed of the player.
+    /// - `enemy_speeds` - The speed of each enemy, in the same order as the enemies.
+    ///
+    /// # Returns
+    /// - The `Combatant` whose turn it is.
+    pub fn next(&mut self, player_speed: u32, enemy_speeds: &[u32]) -> Combatant {
+        // everyone is always moving forward, even when they are very slow
+        let speed_of = |combatant: Combatant| match combatant {
+            Combatant::Player => player_speed.max(1),
+            Combatant::Enemy(i) => enemy_speeds[i].max(1),
+        };
+        let combatants: Vec<Combatant> = std::iter::once(Combatant::Player)
+            .chain((0..self.enemies.len()).map(Combatant::Enemy))
+            .collect();
+
+        // pass just enough ticks for the closest combatant to reach the threshold
+        let ticks = combatants
+            .iter()
+            .map(|c| {
+                TURN_THRESHOLD
+                    .saturating_sub(self.get(*c))
+                    .div_ceil(speed_of(*c))
+            })
+            .min()
+            .unwrap_or(0);
+        for c in &combatants {
+            *self.get_mut(*c) += ticks * speed_of(*c);
+        }
+
+        // the most initiative goes first, with the player winning ties
+        let mut next = Combatant::Player;
+        for c in combatants {
+            if self.get(c) > self.get(next) {
+                next = c;
+            }
+        }
+
+        *self.get_mut(next) -= TURN_THRESHOLD;
+
+        next
+    }
+
+    /// Takes a turn off of a combatant early, such as when it acts out of turn.
+    ///
+    /// # Params
+    /// - `combatant` - The combatant that acted.
+    pub fn spend_turn(&mut self, combatant: Combatant) {
+        let initiative = self.get_mut(combatant);
+        *initiative = initiative.saturating_sub(TURN_THRESHOLD);
+    }
+
+    /// Gets the upcoming turns, without passing any time.
+    ///
+    /// # Params
+    /// - `player_speed` - The speed of the player.
+    /// - `enemy_speeds` - The speed of each enemy, in the same order as the enemies.
+    /// - `count` - How many turns to look ahead.
+    ///
+    /// # Returns
+    /// - The `Combatant` of each upcoming turn, soonest first.
+    pub fn preview(&self, player_speed: u32, enemy_speeds: &[u32], count: usize) -> Vec<Combatant> {
+        let mut future = self.clone();
+
+        (0..count)
+            .map(|_| future.next(player_speed, enemy_speeds))
+            .collect()
+    }
+
+    fn get(&self, combatant: Combatant) -> u32 {
+        match combatant {
+            Combatant::Player => self.player,
+            Combatant::Enemy(i) => self.enemies[i],
+        }
+    }
+
+    fn get_mut(&mut self, combatant: Combatant) -> &mut u32 {
+        match combatant {
+            Combatant::Player => &mut self.player,
+            Combatant::Enemy(i) => &mut self.enemies[i],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Combatant::{Enemy, Player};
+
+    #[test]
+    fn faster_combatant_acts_more_often() {
+        let timeline = Timeline::new(1);
+
+        assert_eq!(
+            timeline.preview(10, &[5], 6),
+            [Player, Player, Enemy(0), Player, Player, Enemy(0)]
+        );
+    }
+
+    #[test]
+    fn faster_enemy_acts_first() {
+        let timeline = Timeline::new(1);
+
+        // they catch up at the same time, and the player wins that tie
+        assert_eq!(timeline.preview(5, &[10], 3), [Enemy(0), Player, Enemy(0)]);
+    }
+
+    #[test]
+    fn player_wins_ties() {
+        let timeline = Timeline::new(1);
+
+        assert_eq!(
+            timeline.preview(10, &[10], 4),
+            [Player, Enemy(0), Player, Enemy(0)]
+        );
+    }
+
+    #[test]
+    fn earlier_enemy_wins_ties_between_enemies() {
+        let timeline = Timeline::new(2);
+
+        assert_eq!(
+            timeline.preview(5, &[10, 10], 5),
+            [Enemy(0), Enemy(1), Player, Enemy(0), Enemy(1)]
+        );
+    }
+
+    #[test]
+    fn slow_combatant_still_acts() {
+        let timeline = Timeline::new(1);
+
+        assert!(timeline.preview(100, &[0], 101).contains(&Enemy(0)));
+    }
+
+    #[test]
+    fn preview_does_not_pass_time() {
+        let mut timeline = Timeline::new(1);
+        let preview = timeline.preview(10, &[7], 5);
+
+        let turns: Vec<Combatant> = (0..5).map(|_| timeline.next(10, &[7])).collect();
+        assert_eq!(turns, preview);
+    }
+
+    #[test]
+    fn removed_enemy_keeps_the_others_in_line() {
+        let mut timeline = Timeline::new(2);
+        assert_eq!(timeline.next(10, &[10, 10]), Player);
+        assert_eq!(timeline.next(10, &[10, 10]), Enemy(0));
+
+        // the second enemy is still owed its turn, now under the first enemy's index
+        timeline.remove_enemy(0);
+        assert_eq!(timeline.num_enemies(), 1);
+        assert_eq!(timeline.next(10, &[10]), Enemy(0));
+        assert_eq!(timeline.next(10, &[10]), Player);
+    }
+}
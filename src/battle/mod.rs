@@ -0,0 +1,6 @@
+//file to define the modules within this folder
+pub mod engine;
+pub mod event;
+pub mod initiative;
+pub mod outcome;
+pub mod rules;
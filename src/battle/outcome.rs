@@ -0,0 +1,72 @@
+// file for what happens when an entity tries to take an action
+use std::{error::Error, fmt};
+
+use crate::entity_components::moves::MoveTarget;
+use crate::entity_components::stats::DefendStance;
+
+/// What an action did, when it was done.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ActionOutcome {
+    /// A physical attack, with the damage it dealt, or `None` if it missed.
+    Attacked { damage: Option<u32> },
+    /// A magic move was cast.
+    Cast { move_name: String },
+    /// The entity started defending.
+    Defended { stance: DefendStance },
+    /// An item was used.
+    UsedItem { item: String },
+    /// The action lost a clash under `RuleSet::Triangle`, so it never happened,
+    /// but it still used up the turn.
+    Beaten,
+}
+
+/// Why an action could not be done. The turn is not used up when this happens.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ActionError {
+    /// It is not the player's turn, such as while they are leveling up or after they died.
+    AlreadyActed,
+    /// There is no enemy at the index the action was aimed at.
+    InvalidTarget { target: usize },
+    /// There is no move at the index that was given, or it hasn't been learned yet.
+    UnknownMove { move_idx: usize },
+    /// The move costs more mana than the entity has.
+    NotEnoughMana { need: u32, have: u32 },
+    /// The special move costs more focus than the entity has.
+    NotEnoughFocus { need: u32, have: u32 },
+    /// The entity can't cast magic because of a status.
+    Silenced,
+    /// The move's effect can't be used on who it is aimed at, such as a heal aimed at an enemy.
+    InvalidMoveTarget { target: MoveTarget },
+    /// There is no item at the index that was given.
+    UnknownItem { stack_idx: usize },
+    /// The item cures a status that the user doesn't have, so it would be wasted.
+    NothingToCure { status: String },
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionError::AlreadyActed => write!(f, "it isn't your turn"),
+            ActionError::InvalidTarget { target } => {
+                write!(f, "there is no enemy {} to aim at", target + 1)
+            }
+            ActionError::UnknownMove { .. } => write!(f, "you haven't learned that move"),
+            ActionError::NotEnoughMana { need, have } => {
+                write!(f, "it needs {} mana, but you only have {}", need, have)
+            }
+            ActionError::NotEnoughFocus { need, have } => {
+                write!(f, "it needs {} focus, but you only have {}", need, have)
+            }
+            ActionError::Silenced => write!(f, "you are silenced"),
+            ActionError::InvalidMoveTarget { target } => {
+                write!(f, "that move can't be aimed at {:?}", target)
+            }
+            ActionError::UnknownItem { .. } => write!(f, "you don't have that item"),
+            ActionError::NothingToCure { status } => {
+                write!(f, "you don't have {} to cure", status)
+            }
+        }
+    }
+}
+
+impl Error for ActionError {}
@@ -0,0 +1,100 @@
+// file for the optional rule sets a battle can be played with
+use serde::{Deserialize, Serialize};
+
+use crate::entity_components::moves::MoveType;
+
+/// The rules a battle is played with, picked by the game mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RuleSet {
+    /// Every action resolves on its own, in turn order.
+    #[default]
+    Classic,
+    /// When the player acts, the enemy they aim at commits its action at the same time,
+    /// and the two actions clash. Defending beats attacking, attacking beats magic,
+    /// and magic beats defending.
+    Triangle,
+}
+
+impl RuleSet {
+    /// Gets the name of a rule set, as it is given on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RuleSet::Classic => "classic",
+            RuleSet::Triangle => "triangle",
+        }
+    }
+
+    /// Gets a rule set from its name.
+    ///
+    /// # Params
+    /// - `name` - The name of the rule set, such as `"triangle"`.
+    ///
+    /// # Returns
+    /// - The `RuleSet`, or `None` if there is no rule set with that name.
+    pub fn from_name(name: &str) -> Option<RuleSet> {
+        [RuleSet::Classic, RuleSet::Triangle]
+            .into_iter()
+            .find(|rules| rules.name() == name)
+    }
+}
+
+/// Checks if one kind of move beats another when they clash under `RuleSet::Triangle`.
+///
+/// # Params
+/// - `move_type` - The kind of move that might win.
+/// - `other` - The kind of move it clashes with.
+///
+/// # Returns
+/// - `true` if `move_type` wins the clash, `false` if it loses or neither wins.
+pub fn beats(move_type: MoveType, other: MoveType) -> bool {
+    matches!(
+        (move_type, other),
+        (MoveType::DefendMove, MoveType::AttackMove) // the attack is blocked
+            | (MoveType::AttackMove, MoveType::MagicMove) // the cast is interrupted
+            | (MoveType::MagicMove, MoveType::DefendMove) // the guard is pierced
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use MoveType::{AttackMove, DefendMove, MagicMove};
+
+    const ALL_MOVES: [MoveType; 3] = [AttackMove, MagicMove, DefendMove];
+
+    #[test]
+    fn each_move_beats_one_other() {
+        assert!(beats(DefendMove, AttackMove));
+        assert!(beats(AttackMove, MagicMove));
+        assert!(beats(MagicMove, DefendMove));
+    }
+
+    #[test]
+    fn no_move_beats_what_beats_it() {
+        for move_type in ALL_MOVES {
+            for other in ALL_MOVES {
+                assert!(
+                    !(beats(move_type, other) && beats(other, move_type)),
+                    "{:?} and {:?} both win",
+                    move_type,
+                    other
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn same_moves_tie() {
+        for move_type in ALL_MOVES {
+            assert!(!beats(move_type, move_type));
+        }
+    }
+
+    #[test]
+    fn rule_set_names_round_trip() {
+        for rules in [RuleSet::Classic, RuleSet::Triangle] {
+            assert_eq!(RuleSet::from_name(rules.name()), Some(rules));
+        }
+        assert_eq!(RuleSet::from_name("chess"), None);
+    }
+}
@@ -0,0 +1,509 @@
+//! Batch balance simulator for Starstruck.
+//!
+//! Plays many seeded battles with player builds (the stats they pick when leveling up)
+//! and reports how they fare, so the numbers in the game data can be tuned.
+
+//create modules
+mod policy;
+mod report;
+
+use std::{error::Error, io, path::PathBuf, process};
+
+use starstruck::battle::engine::{Battle, BattleState};
+use starstruck::battle::event::BattleEvent;
+use starstruck::battle::rules::RuleSet;
+use starstruck::content::Content;
+use starstruck::entity_components::{
+    enemy::Enemy,
+    entity::Entity,
+    player::{LevelUpType, Player},
+};
+
+use crate::policy::Policy;
+use crate::report::{write_table, FightRecord, MatchupSummary};
+
+const DEFAULT_BATTLES: u32 = 1000;
+const DEFAULT_MAX_LEVEL: u32 = 10;
+const MAX_FIGHT_TURNS: u32 = 200; // a fight that goes on longer than this is counted as a loss
+const MAX_RUN_TURNS: u32 = 5000; // a progression run that goes on longer than this is stopped
+
+/// The stats a player build picks when leveling up, in order. The choices repeat
+/// once they run out, so a build of `[Strength, Magic]` alternates between the two.
+struct Build {
+    choices: Vec<LevelUpType>,
+}
+
+impl Build {
+    /// Gets the name of the build, such as `strength/magic`.
+    fn name(&self) -> String {
+        let names: Vec<&str> = self.choices.iter().map(|choice| choice.name()).collect();
+        names.join("/")
+    }
+
+    /// Gets the stat the build picks when leveling up.
+    ///
+    /// # Params
+    /// - `level` - The level the player is leaving.
+    fn choice(&self, level: u32) -> LevelUpType {
+        self.choices[(level as usize - 1) % self.choices.len()]
+    }
+
+    /// Creates a player at a level, having leveled up with this build.
+    ///
+    /// # Params
+    /// - `level` - The level of the player.
+    /// - `content` - The content of the game, for the starting items.
+    fn create_player(&self, level: u32, content: &Content) -> Player {
+        let mut player = Player::default().with_inventory(content.starting_inventory());
+        while player.level() < level {
+            player.level_up(self.choice(player.level()));
+        }
+        player
+    }
+}
+
+/// The options the simulator was started with from the command line.
+struct Options {
+    battles: u32,              // how many battles to play for each build and enemy
+    seed: u64,                 // the seed of the first battle, and each battle after uses the next
+    level: Option<u32>,        // the level of the player, or `None` to match each enemy's level
+    max_level: u32,            // the level that progression runs stop at
+    policy: Policy,            // how the player picks their actions
+    builds: Vec<Build>,        // the builds to simulate
+    rules: RuleSet,            // the rules the battles are played with
+    data_dir: Option<PathBuf>, // where the content is loaded from, or `None` for the default
+    progression: bool,         // if runs of random encounters are played instead of single fights
+    is_csv: bool,              // if the report is written as CSV
+}
+
+impl Options {
+    /// Parses the command line arguments.
+    ///
+    /// # Returns
+    /// - The parsed `Options`, or a message describing the bad argument.
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            battles: DEFAULT_BATTLES,
+            seed: 0,
+            level: None,
+            max_level: DEFAULT_MAX_LEVEL,
+            policy: Policy::Smart,
+            builds: Vec::new(),
+            rules: RuleSet::default(),
+            data_dir: None,
+            progression: false,
+            is_csv: false,
+        };
+        let mut args = args.skip(1); // skip the program name
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--battles" => {
+                    let value = args.next().ok_or("--battles needs a value")?;
+                    options.battles = value
+                        .parse::<u32>()
+                        .map_err(|_| format!("invalid number of battles: {}", value))?;
+                }
+                "--seed" => {
+                    let value = args.next().ok_or("--seed needs a value")?;
+                    options.seed = value
+                        .parse::<u64>()
+                        .map_err(|_| format!("invalid seed: {}", value))?;
+                }
+                "--level" => {
+                    let value = args.next().ok_or("--level needs a value")?;
+                    let level = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|&level| level >= 1)
+                        .ok_or_else(|| format!("invalid level: {}", value))?;
+                    options.level = Some(level);
+                }
+                "--max-level" => {
+                    let value = args.next().ok_or("--max-level needs a value")?;
+                    options.max_level = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|&level| level >= 2)
+                        .ok_or_else(|| format!("invalid max level: {}", value))?;
+                }
+                "--policy" => {
+                    let value = args.next().ok_or("--policy needs a policy")?;
+                    options.policy = Policy::from_name(&value)
+                        .ok_or_else(|| format!("unknown policy: {}", value))?;
+                }
+                "--build" => {
+                    let value = args.next().ok_or("--build needs a list of stats")?;
+                    let choices = value
+                        .split(',')
+                        .map(|name| {
+                            LevelUpType::from_name(name.trim())
+                                .ok_or_else(|| format!("unknown stat in build: {}", name))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    options.builds.push(Build { choices });
+                }
+                "--rules" => {
+                    let value = args.next().ok_or("--rules needs a rule set")?;
+                    options.rules = RuleSet::from_name(&value)
+                        .ok_or_else(|| format!("unknown rule set: {}", value))?;
+                }
+                "--data" => {
+                    let value = args.next().ok_or("--data needs a directory")?;
+                    options.data_dir = Some(PathBuf::from(value));
+                }
+                "--progression" => options.progression = true,
+                "--csv" => options.is_csv = true,
+                _ => return Err(format!("unknown argument: {}", arg)),
+            }
+        }
+
+        // one build for each stat, and one that spreads its levels evenly
+        if options.builds.is_empty() {
+            options.builds = LevelUpType::ALL
+                .into_iter()
+                .map(|choice| Build {
+                    choices: vec![choice],
+                })
+                .chain(std::iter::once(Build {
+                    choices: LevelUpType::ALL.to_vec(),
+                }))
+                .collect();
+        }
+
+        Ok(options)
+    }
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let options = Options::parse(std::env::args())?;
+    let content = match &options.data_dir {
+        Some(dir) => Content::load(dir)?,
+        None => Content::load_default()?,
+    };
+
+    let mut stdout = io::stdout().lock();
+    if options.progression {
+        report_progression(&options, &content, &mut stdout)?;
+    } else {
+        report_matchups(&options, &content, &mut stdout)?;
+    }
+
+    Ok(())
+}
+
+/// Plays single fights between each build and each enemy, and writes how they went.
+///
+/// # Params
+/// - `options` - The options of the simulation.
+/// - `content` - The content of the game.
+/// - `output` - Where the report is written.
+fn report_matchups(
+    options: &Options,
+    content: &Content,
+    output: &mut impl io::Write,
+) -> io::Result<()> {
+    let mut rows = Vec::new();
+
+    for build in &options.builds {
+        for enemy in content.enemies() {
+            let level = options.level.unwrap_or(enemy.level().max(1));
+            let records: Vec<FightRecord> = (0..options.battles)
+                .map(|i| {
+                    let player = build.create_player(level, content);
+                    let seed = options.seed.wrapping_add(i as u64);
+                    play_fight(player, enemy, seed, content, options)
+                })
+                .collect();
+            let summary = MatchupSummary::new(&records);
+
+            rows.push(vec![
+                build.name(),
+                enemy.name(),
+                level.to_string(),
+                summary.fights.to_string(),
+                format!("{:.1}", summary.win_rate * 100.0),
+                format!("{:.1}", summary.turns.mean),
+                format!("{:.1}", summary.damage_dealt.mean),
+                summary.damage_dealt.p10.to_string(),
+                summary.damage_dealt.p50.to_string(),
+                summary.damage_dealt.p90.to_string(),
+                format!("{:.1}", summary.damage_taken.mean),
+                summary.damage_taken.p10.to_string(),
+                summary.damage_taken.p50.to_string(),
+                summary.damage_taken.p90.to_string(),
+                format!("{:.1}", summary.mana_spent.mean),
+                summary.mana_spent.p90.to_string(),
+                format!("{:.1}", summary.focus_spent.mean),
+            ]);
+        }
+    }
+
+    write_table(
+        output,
+        &[
+            "build",
+            "enemy",
+            "level",
+            "fights",
+            "win_pct",
+            "avg_turns",
+            "dealt_avg",
+            "dealt_p10",
+            "dealt_p50",
+            "dealt_p90",
+            "taken_avg",
+            "taken_p10",
+            "taken_p50",
+            "taken_p90",
+            "mana_avg",
+            "mana_p90",
+            "focus_avg",
+        ],
+        &rows,
+        options.is_csv,
+    )
+}
+
+/// Plays one fight between the player and a single enemy, until one of them dies.
+///
+/// # Params
+/// - `player` - The player, built for the fight.
+/// - `enemy` - The enemy to fight.
+/// - `seed` - The seed of the fight.
+/// - `content` - The content of the game.
+/// - `options` - The options of the simulation.
+///
+/// # Returns
+/// - What happened in the fight.
+fn play_fight(
+    player: Player,
+    enemy: &Enemy,
+    seed: u64,
+    content: &Content,
+    options: &Options,
+) -> FightRecord {
+    let player_name = player.name();
+    let mut battle = Battle::new(player, Some(vec![enemy.clone()]), seed, content.clone())
+        .with_rules(options.rules);
+    let mut record = FightRecord {
+        won: false,
+        turns: 0,
+        damage_dealt: 0,
+        damage_taken: 0,
+        mana_spent: 0,
+        focus_spent: 0,
+    };
+    let (mut mana_before, mut focus_before) = (0, 0);
+
+    loop {
+        // the mana and focus that changed hands without being spent on a move
+        let (mut mana_gained, mut mana_lost, mut focus_gained) = (0, 0, 0);
+        for event in battle.take_events() {
+            match &event {
+                // the xp is dropped once the enemy is defeated
+                BattleEvent::XpGained { .. } => record.won = true,
+                BattleEvent::ManaRestored { target, amount }
+                | BattleEvent::ManaRegenerated { target, amount }
+                    if *target == player_name =>
+                {
+                    mana_gained += amount
+                }
+                BattleEvent::ManaDrained {
+                    caster,
+                    target,
+                    amount,
+                } => {
+                    if *caster == player_name {
+                        mana_gained += amount;
+                    } else if *target == player_name {
+                        mana_lost += amount;
+                    }
+                }
+                BattleEvent::FocusGained { target, amount } if *target == player_name => {
+                    focus_gained += amount
+                }
+                _ => {}
+            }
+            if let Some((target, amount)) = damage_of(&event) {
+                if target == player_name {
+                    record.damage_taken += amount;
+                } else {
+                    record.damage_dealt += amount;
+                }
+            }
+        }
+
+        // only count what the last turn really spent, as a spell beaten in a clash costs nothing
+        if record.turns > 0 {
+            let player = battle.player();
+            record.mana_spent +=
+                (mana_before + mana_gained).saturating_sub(player.mana_bar().current() + mana_lost);
+            record.focus_spent += (focus_before + focus_gained).saturating_sub(player.focus());
+        }
+
+        // the next encounter has already appeared, so stop once the enemy is defeated
+        if record.won || battle.state() != BattleState::Fighting || record.turns >= MAX_FIGHT_TURNS
+        {
+            return record;
+        }
+
+        mana_before = battle.player().mana_bar().current();
+        focus_before = battle.player().focus();
+        if !take_turn(&mut battle, options.policy) {
+            return record; // the policy is stuck, which counts as a loss
+        }
+        record.turns += 1;
+    }
+}
+
+/// Plays runs of random encounters with each build from level 1, and writes how
+/// quickly each build reaches each level.
+///
+/// # Params
+/// - `options` - The options of the simulation.
+/// - `content` - The content of the game.
+/// - `output` - Where the report is written.
+fn report_progression(
+    options: &Options,
+    content: &Content,
+    output: &mut impl io::Write,
+) -> io::Result<()> {
+    let mut rows = Vec::new();
+
+    for build in &options.builds {
+        // for each level, the turns and kills it took each run that reached it
+        let num_levels = options.max_level as usize + 1;
+        let mut turns_to_level: Vec<Vec<u32>> = vec![Vec::new(); num_levels];
+        let mut kills_to_level: Vec<Vec<u32>> = vec![Vec::new(); num_levels];
+        let mut xp_to_level: Vec<Vec<u32>> = vec![Vec::new(); num_levels];
+
+        for i in 0..options.battles {
+            let player = build.create_player(1, content);
+            let seed = options.seed.wrapping_add(i as u64);
+            let mut battle =
+                Battle::new(player, None, seed, content.clone()).with_rules(options.rules);
+            let (mut turns, mut kills, mut xp) = (0, 0, 0);
+
+            while battle.player().level() < options.max_level && turns < MAX_RUN_TURNS {
+                for event in battle.take_events() {
+                    match event {
+                        BattleEvent::XpGained { amount, .. } => {
+                            kills += 1;
+                            xp += amount;
+                        }
+                        BattleEvent::LevelUp { level, .. } => {
+                            turns_to_level[level as usize].push(turns);
+                            kills_to_level[level as usize].push(kills);
+                            xp_to_level[level as usize].push(xp);
+                        }
+                        _ => {}
+                    }
+                }
+
+                match battle.state() {
+                    BattleState::Fighting => {
+                        if !take_turn(&mut battle, options.policy) {
+                            break;
+                        }
+                        turns += 1;
+                    }
+                    BattleState::LevelingUp => {
+                        let level = battle.player().level();
+                        battle.level_up(build.choice(level));
+                    }
+                    BattleState::PlayerDied => break,
+                }
+            }
+
+            // the last level up happens after the last turn
+            for event in battle.take_events() {
+                if let BattleEvent::LevelUp { level, .. } = event {
+                    turns_to_level[level as usize].push(turns);
+                    kills_to_level[level as usize].push(kills);
+                    xp_to_level[level as usize].push(xp);
+                }
+            }
+        }
+
+        for level in 2..=options.max_level as usize {
+            let runs = turns_to_level[level].len();
+            // a level that no run reached has nothing to average
+            let average = |values: &[u32]| {
+                if values.is_empty() {
+                    String::new()
+                } else {
+                    let sum: f64 = values.iter().map(|&value| value as f64).sum();
+                    format!("{:.1}", sum / values.len() as f64)
+                }
+            };
+
+            rows.push(vec![
+                build.name(),
+                level.to_string(),
+                format!("{:.1}", runs as f64 / options.battles.max(1) as f64 * 100.0),
+                average(&turns_to_level[level]),
+                average(&kills_to_level[level]),
+                average(&xp_to_level[level]),
+            ]);
+        }
+    }
+
+    write_table(
+        output,
+        &[
+            "build",
+            "level",
+            "reached_pct",
+            "avg_turns",
+            "avg_kills",
+            "avg_xp",
+        ],
+        &rows,
+        options.is_csv,
+    )
+}
+
+/// Makes the player take their turn with the action the policy picks.
+///
+/// # Params
+/// - `battle` - The battle, which must be waiting on the player to fight.
+/// - `policy` - How the player picks their action.
+///
+/// # Returns
+/// - `true` if the turn was taken, `false` if the policy couldn't find an action that worked.
+fn take_turn(battle: &mut Battle, policy: Policy) -> bool {
+    policy
+        .choose(battle)
+        .is_some_and(|action| action.apply(battle).is_ok())
+}
+
+/// Gets who took damage in an event, and how much.
+///
+/// # Params
+/// - `event` - The event that happened.
+///
+/// # Returns
+/// - The name of the entity that was damaged and the amount, or `None` if the event
+///   didn't damage anyone.
+fn damage_of(event: &BattleEvent) -> Option<(&str, u32)> {
+    match event {
+        BattleEvent::DamageDealt { target, amount, .. }
+        | BattleEvent::Countered { target, amount, .. }
+        | BattleEvent::StatusTriggered { target, amount, .. } => Some((target, *amount)),
+        BattleEvent::StatusTicked {
+            target,
+            amount,
+            is_healing: false,
+            ..
+        } => Some((target, *amount)),
+        _ => None,
+    }
+}
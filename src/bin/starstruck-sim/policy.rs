@@ -0,0 +1,220 @@
+// file for the policies that make the player's decisions in a simulation
+use starstruck::battle::engine::Battle;
+use starstruck::battle::rules::RuleSet;
+use starstruck::entity_components::{
+    affinity::Affinity,
+    enemy::Enemy,
+    entity::Entity,
+    item::ItemEffect,
+    moves::{Move, MoveEffect},
+    stats::ModifierSource,
+};
+use starstruck::protocol::{legal_actions, ActionMessage};
+
+const LOW_HEALTH: f64 = 0.35; // the fraction of max health that the smart policy heals below
+
+/// How the player picks their actions.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    Attack, // always attacks
+    Magic,  // casts the move that should hit hardest, and attacks otherwise
+    Smart,  // heals when low on health, and plays like `Magic` otherwise
+}
+
+impl Policy {
+    /// Every policy.
+    pub const ALL: [Policy; 3] = [Policy::Attack, Policy::Magic, Policy::Smart];
+
+    /// Gets the name of a policy, as it is given on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Policy::Attack => "attack",
+            Policy::Magic => "magic",
+            Policy::Smart => "smart",
+        }
+    }
+
+    /// Gets a policy from its name.
+    ///
+    /// # Params
+    /// - `name` - The name of the policy, such as `"smart"`.
+    ///
+    /// # Returns
+    /// - The `Policy`, or `None` if there is no policy with that name.
+    pub fn from_name(name: &str) -> Option<Policy> {
+        Policy::ALL.into_iter().find(|policy| policy.name() == name)
+    }
+
+    /// Picks the player's next action in a fight, from the legal actions of the battle.
+    ///
+    /// Under `RuleSet::Triangle`, the enemy a spell is aimed at can interrupt it by
+    /// attacking, so spells are only cast when that enemy can't act or has its guard up.
+    ///
+    /// # Params
+    /// - `battle` - The battle, which must be waiting on the player to fight.
+    ///
+    /// # Returns
+    /// - The action to take, or `None` if there are none.
+    pub fn choose(&self, battle: &Battle) -> Option<ActionMessage> {
+        let legal = legal_actions(battle);
+        let find_move = |move_name: &str| -> Option<&Move> {
+            battle
+                .available_moves()
+                .iter()
+                .find(|the_move| the_move.name() == move_name)
+        };
+
+        let is_spell_safe =
+            |target: usize| battle.rules() != RuleSet::Triangle || !can_interrupt(battle, target);
+
+        // heal with a move or an item when low on health
+        if *self == Policy::Smart && battle.player().health_bar().fraction() < LOW_HEALTH {
+            let heal = legal.iter().find(|action| match action {
+                ActionMessage::Magic { move_name, target } => {
+                    is_spell_safe(*target)
+                        && find_move(move_name)
+                            .is_some_and(|the_move| the_move.effect() == MoveEffect::HealSelf)
+                }
+                ActionMessage::Item { item, .. } => {
+                    battle.player().inventory().stacks().iter().any(|stack| {
+                        stack.item().name() == *item
+                            && matches!(stack.item().effect(), ItemEffect::Heal { .. })
+                    })
+                }
+                _ => false,
+            });
+            if let Some(heal) = heal {
+                return Some(heal.clone());
+            }
+        }
+
+        // aim at the enemy closest to dying
+        let weakest = battle
+            .enemies()
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, enemy)| enemy.health())
+            .map(|(idx, _)| idx)
+            .unwrap_or(0);
+
+        // the damaging move that should hit it hardest
+        if *self != Policy::Attack && is_spell_safe(weakest) {
+            let enemy = &battle.enemies()[weakest];
+            let strongest_spell = legal
+                .iter()
+                .filter_map(|action| match action {
+                    ActionMessage::Magic { move_name, target } if *target == weakest => {
+                        find_move(move_name)
+                            .and_then(|the_move| expected_damage(battle, enemy, the_move))
+                            .map(|expected| (expected, action))
+                    }
+                    _ => None,
+                })
+                .max_by_key(|(expected, _)| *expected)
+                .map(|(_, action)| action);
+            if let Some(spell) = strongest_spell {
+                return Some(spell.clone());
+            }
+        }
+
+        legal
+            .iter()
+            .find(|action| matches!(action, ActionMessage::Attack { target } if *target == weakest))
+            .or_else(|| legal.first())
+            .cloned()
+    }
+}
+
+/// Checks if an enemy might interrupt a spell aimed at it under `RuleSet::Triangle`.
+/// A stunned enemy can't commit to an action, and an enemy with its guard up is
+/// likely to keep it up, which magic pierces.
+///
+/// # Params
+/// - `battle` - The battle the enemy is in.
+/// - `target` - The index of the enemy.
+fn can_interrupt(battle: &Battle, target: usize) -> bool {
+    match battle.enemies().get(target) {
+        Some(enemy) => {
+            let is_defending = enemy
+                .stats()
+                .modifiers()
+                .iter()
+                .any(|active| *active.source() == ModifierSource::Defending);
+            enemy.stunned_by().is_none() && !is_defending
+        }
+        None => false,
+    }
+}
+
+/// Gets how hard a move should hit an enemy, ignoring the random roll.
+///
+/// # Params
+/// - `battle` - The battle, for the player casting the move.
+/// - `enemy` - The enemy the move is aimed at.
+/// - `the_move` - The move.
+///
+/// # Returns
+/// - The expected damage, or `None` if the move doesn't deal damage to the enemy.
+fn expected_damage(battle: &Battle, enemy: &Enemy, the_move: &Move) -> Option<u32> {
+    let is_damaging = matches!(
+        the_move.effect(),
+        MoveEffect::Damage | MoveEffect::DrainHealth
+    );
+    let affinity = enemy.affinity(the_move.element());
+    if !is_damaging || affinity == Affinity::Absorb {
+        return None;
+    }
+
+    let expected = affinity.scale(the_move.base_amount() + battle.player().magic_strength());
+    (expected > 0).then_some(expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starstruck::content::Content;
+    use starstruck::entity_components::player::Player;
+
+    /// Starts a fight with the built in Spider, which is weak to fire.
+    fn fight_spider(rules: RuleSet) -> Battle {
+        let content = Content::built_in();
+        let spider = content
+            .enemies()
+            .iter()
+            .find(|enemy| enemy.name() == "Spider")
+            .unwrap()
+            .clone();
+
+        Battle::new(Player::default(), Some(vec![spider]), 1, content).with_rules(rules)
+    }
+
+    #[test]
+    fn magic_casts_what_the_target_is_weak_to() {
+        let battle = fight_spider(RuleSet::Classic);
+
+        assert!(matches!(
+            Policy::Magic.choose(&battle),
+            Some(ActionMessage::Magic { move_name, .. }) if move_name == "FireOne"
+        ));
+    }
+
+    #[test]
+    fn magic_attacks_a_target_that_could_interrupt_it() {
+        let battle = fight_spider(RuleSet::Triangle);
+
+        assert!(matches!(
+            Policy::Magic.choose(&battle),
+            Some(ActionMessage::Attack { target: 0 })
+        ));
+    }
+
+    #[test]
+    fn attack_always_attacks() {
+        let battle = fight_spider(RuleSet::Classic);
+
+        assert!(matches!(
+            Policy::Attack.choose(&battle),
+            Some(ActionMessage::Attack { target: 0 })
+        ));
+    }
+}
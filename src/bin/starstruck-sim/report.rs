@@ -0,0 +1,239 @@
+// file for summing up simulated battles and writing them out
+use std::io::{self, Write};
+
+/// What happened in one simulated fight against a single enemy.
+pub struct FightRecord {
+    pub won: bool,         // if the enemy died before the player did
+    pub turns: u32,        // how many actions the player took
+    pub damage_dealt: u32, // every bit of damage done to the enemy
+    pub damage_taken: u32, // every bit of damage done to the player
+    pub mana_spent: u32,   // the mana the player spent on moves
+    pub focus_spent: u32,  // the focus the player spent on special moves
+}
+
+/// A summary of many values, such as the damage dealt in each fight.
+pub struct Distribution {
+    pub mean: f64,
+    pub p10: u32,
+    pub p50: u32,
+    pub p90: u32,
+}
+
+impl Distribution {
+    /// Sums up a list of values.
+    ///
+    /// # Params
+    /// - `values` - The values, in any order.
+    pub fn new(mut values: Vec<u32>) -> Self {
+        if values.is_empty() {
+            return Self {
+                mean: 0.0,
+                p10: 0,
+                p50: 0,
+                p90: 0,
+            };
+        }
+
+        values.sort_unstable();
+        let percentile = |fraction: f64| {
+            let idx = (fraction * (values.len() - 1) as f64).round() as usize;
+            values[idx]
+        };
+
+        Self {
+            mean: values.iter().map(|&value| value as f64).sum::<f64>() / values.len() as f64,
+            p10: percentile(0.1),
+            p50: percentile(0.5),
+            p90: percentile(0.9),
+        }
+    }
+}
+
+/// A summary of every fight between one build and one enemy.
+pub struct MatchupSummary {
+    pub fights: usize,
+    pub win_rate: f64,
+    pub turns: Distribution,
+    pub damage_dealt: Distribution,
+    pub damage_taken: Distribution,
+    pub mana_spent: Distribution,
+    pub focus_spent: Distribution,
+}
+
+impl MatchupSummary {
+    /// Sums up the fights between one build and one enemy.
+    ///
+    /// # Params
+    /// - `records` - What happened in each fight.
+    pub fn new(records: &[FightRecord]) -> Self {
+        let collect = |field: fn(&FightRecord) -> u32| records.iter().map(field).collect();
+        let wins = records.iter().filter(|record| record.won).count();
+
+        Self {
+            fights: records.len(),
+            win_rate: wins as f64 / records.len().max(1) as f64,
+            turns: Distribution::new(collect(|record| record.turns)),
+            damage_dealt: Distribution::new(collect(|record| record.damage_dealt)),
+            damage_taken: Distribution::new(collect(|record| record.damage_taken)),
+            mana_spent: Distribution::new(collect(|record| record.mana_spent)),
+            focus_spent: Distribution::new(collect(|record| record.focus_spent)),
+        }
+    }
+}
+
+/// Writes rows of values, either as an aligned table or as CSV.
+///
+/// # Params
+/// - `output` - Where the table is written.
+/// - `headers` - The name of each column.
+/// - `rows` - The values of each row, with one for each column.
+/// - `is_csv` - `true` to write CSV, `false` to write an aligned table.
+pub fn write_table(
+    output: &mut impl Write,
+    headers: &[&str],
+    rows: &[Vec<String>],
+    is_csv: bool,
+) -> io::Result<()> {
+    if is_csv {
+        writeln!(output, "{}", headers.join(","))?;
+        for row in rows {
+            writeln!(output, "{}", row.join(","))?;
+        }
+        return Ok(());
+    }
+
+    // make each column as wide as its widest value
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+    for row in rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.len());
+        }
+    }
+
+    // names go on the left, numbers on the right, and blank values fit either
+    let is_number: Vec<bool> = (0..headers.len())
+        .map(|column| {
+            let mut values = rows
+                .iter()
+                .filter_map(|row| row.get(column))
+                .filter(|value| !value.is_empty())
+                .peekable();
+            values.peek().is_some() && values.all(|value| value.parse::<f64>().is_ok())
+        })
+        .collect();
+
+    let header_row: Vec<String> = headers.iter().map(|header| header.to_string()).collect();
+    for row in std::iter::once(&header_row).chain(rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths.iter().zip(&is_number))
+            .map(|(value, (width, is_number))| {
+                if *is_number {
+                    format!("{:>width$}", value, width = width)
+                } else {
+                    format!("{:<width$}", value, width = width)
+                }
+            })
+            .collect();
+        writeln!(output, "{}", cells.join("  ").trim_end())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes a table into a string.
+    fn table(headers: &[&str], rows: &[Vec<String>], is_csv: bool) -> String {
+        let mut output = Vec::new();
+        write_table(&mut output, headers, rows, is_csv).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    fn row(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn distribution_of_nothing_is_zero() {
+        let distribution = Distribution::new(Vec::new());
+
+        assert_eq!(distribution.mean, 0.0);
+        assert_eq!(
+            (distribution.p10, distribution.p50, distribution.p90),
+            (0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn distribution_of_one_value_is_that_value() {
+        let distribution = Distribution::new(vec![7]);
+
+        assert_eq!(distribution.mean, 7.0);
+        assert_eq!(
+            (distribution.p10, distribution.p50, distribution.p90),
+            (7, 7, 7)
+        );
+    }
+
+    #[test]
+    fn distribution_picks_percentiles_in_any_order() {
+        // 1 to 11, shuffled, so each percentile lands on a value
+        let distribution = Distribution::new(vec![6, 11, 2, 9, 1, 4, 10, 3, 8, 5, 7]);
+
+        assert_eq!(distribution.mean, 6.0);
+        assert_eq!(
+            (distribution.p10, distribution.p50, distribution.p90),
+            (2, 6, 10)
+        );
+    }
+
+    #[test]
+    fn summary_counts_wins() {
+        let record = |won: bool, turns: u32| FightRecord {
+            won,
+            turns,
+            damage_dealt: 0,
+            damage_taken: 0,
+            mana_spent: 0,
+            focus_spent: 0,
+        };
+        let summary = MatchupSummary::new(&[record(true, 2), record(false, 4)]);
+
+        assert_eq!(summary.fights, 2);
+        assert_eq!(summary.win_rate, 0.5);
+        assert_eq!(summary.turns.mean, 3.0);
+    }
+
+    #[test]
+    fn summary_of_no_fights_has_no_wins() {
+        let summary = MatchupSummary::new(&[]);
+
+        assert_eq!(summary.fights, 0);
+        assert_eq!(summary.win_rate, 0.0);
+    }
+
+    #[test]
+    fn csv_is_written_as_is() {
+        let rows = [row(&["magic", "12.5", ""]), row(&["strength", "3.0", "4"])];
+
+        assert_eq!(
+            table(&["build", "avg", "p90"], &rows, true),
+            "build,avg,p90\nmagic,12.5,\nstrength,3.0,4\n"
+        );
+    }
+
+    #[test]
+    fn table_aligns_names_left_and_numbers_right() {
+        let rows = [row(&["magic", "12.5", ""]), row(&["strength", "3.0", "4"])];
+
+        assert_eq!(
+            table(&["build", "avg", "p90"], &rows, false),
+            "build      avg  p90\n\
+             magic     12.5\n\
+             strength   3.0    4\n"
+        );
+    }
+}
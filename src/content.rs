@@ -0,0 +1,925 @@
+// file for loading the game content (statuses, moves, items, gear and enemies) from data files
+use std::{collections::HashSet, error::Error, fmt, fs, io, path::Path, path::PathBuf};
+
+use serde::Deserialize;
+
+use crate::battle::engine::MAX_ENEMY_LEVELS_ABOVE_PLAYER;
+use crate::entity_components::{
+    affinity::Affinities,
+    ai::{AiPolicy, AiRegistry, EnemyAi},
+    enemy::Enemy,
+    equipment::Gear,
+    item::{Inventory, Item, ItemEffect, Loot, LootDrop},
+    moves::{ElementType, Move, MoveEffect, MoveTarget},
+    player::DEFAULT_PLAYER_LEVEL,
+    stats::{StatModifier, StatType, Stats},
+    status::{Stacking, Status, StatusTrigger},
+};
+
+/// The directory that content is loaded from when no other directory is given.
+pub const DEFAULT_DATA_DIR: &str = "data";
+
+const STATUSES_FILE: &str = "statuses.json";
+const MOVES_FILE: &str = "moves.json";
+const ITEMS_FILE: &str = "items.json";
+const GEAR_FILE: &str = "gear.json";
+const ENEMIES_FILE: &str = "enemies.json";
+
+// the content that ships with the game, for when there is no data directory
+const BUILT_IN_STATUSES: &str = include_str!("../data/statuses.json");
+const BUILT_IN_MOVES: &str = include_str!("../data/moves.json");
+const BUILT_IN_ITEMS: &str = include_str!("../data/items.json");
+const BUILT_IN_GEAR: &str = include_str!("../data/gear.json");
+const BUILT_IN_ENEMIES: &str = include_str!("../data/enemies.json");
+
+/// A status as it is written in a data file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StatusDef {
+    name: String,
+    base_amount: u32,
+    is_healing: bool,
+    num_turns: u32,
+    #[serde(default)]
+    modifiers: Vec<StatModifier>, // changes to the stats of the entity while it has the status
+    #[serde(default)]
+    stacking: Stacking, // what happens when the status is applied again
+    #[serde(default)]
+    skips_turn: bool,
+    #[serde(default)]
+    blocks_magic: bool,
+    #[serde(default)]
+    triggers: Vec<StatusTrigger>,
+}
+
+/// A move as it is written in a data file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MoveDef {
+    name: String,
+    base_amount: u32,
+    mana_cost: u32,
+    level_requirement: u32,
+    element: ElementType,
+    #[serde(default)]
+    status: Option<String>, // the name of the status this move can apply
+    #[serde(default)]
+    modifiers: Vec<StatModifier>, // buffs or debuffs for the target
+    #[serde(default)]
+    modifier_turns: u32, // how many of the target's turns the modifiers last
+    #[serde(default)]
+    effect: MoveEffect,
+    #[serde(default)]
+    target: MoveTarget,
+    #[serde(default)]
+    focus_cost: u32, // the focus needed on top of the mana, for the player's special moves
+}
+
+/// An item as it is written in a data file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ItemDef {
+    name: String,
+    effect: ItemEffect,
+    #[serde(default)]
+    starting_count: u32, // how many of this item a new player carries
+}
+
+/// An entry in an enemy's loot table, as it is written in a data file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DropDef {
+    item: String, // the name of the item or gear
+    chance: f64,
+}
+
+/// An enemy as it is written in a data file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EnemyDef {
+    name: String,
+    level: u32,
+    stats: Stats,
+    #[serde(default)]
+    affinities: Affinities,
+    #[serde(default)]
+    moves: Vec<String>, // the names of the magic moves this enemy can cast
+    #[serde(default)]
+    ai: AiPolicy,
+    #[serde(default = "default_max_group_size")]
+    max_group_size: u32, // the most of this enemy that can appear in one encounter
+    #[serde(default)]
+    drops: Vec<DropDef>,
+    #[serde(default)]
+    statuses: Vec<String>, // the names of the statuses this enemy starts with
+}
+
+fn default_max_group_size() -> u32 {
+    1
+}
+
+/// All of the statuses, moves, items, gear and enemies in the game.
+#[derive(Clone)]
+pub struct Content {
+    statuses: Vec<Status>,
+    moves: Vec<Move>, // ordered by level requirement
+    items: Vec<Item>,
+    starting_inventory: Inventory, // the items a new player carries
+    gear: Vec<Gear>,
+    enemies: Vec<Enemy>,
+    ai_registry: AiRegistry, // the enemy AI policies plugged in from code
+}
+
+/// An error from loading the game content.
+#[derive(Debug)]
+pub enum ContentError {
+    /// A data file could not be read.
+    Io { path: PathBuf, err: io::Error },
+    /// A data file is not valid.
+    Parse {
+        file: String,
+        err: serde_json::Error,
+    },
+    /// Two entries of the same kind have the same name.
+    DuplicateName { kind: &'static str, name: String },
+    /// A move refers to a status that does not exist.
+    UnknownStatus { move_name: String, status: String },
+    /// An enemy starts with a status that does not exist.
+    EnemyUnknownStatus { enemy_name: String, status: String },
+    /// An enemy starts with a status that changes its max health, so it would
+    /// appear without full health.
+    EnemyStatusMaxHealth { enemy_name: String, status: String },
+    /// An item cures a status that does not exist.
+    ItemUnknownStatus { item_name: String, status: String },
+    /// An enemy drops an item or gear that does not exist.
+    UnknownItem {
+        enemy_name: String,
+        item_name: String,
+    },
+    /// An enemy has a drop chance that is not between 0 and 1.
+    InvalidDropChance {
+        enemy_name: String,
+        item_name: String,
+        chance: f64,
+    },
+    /// An enemy refers to a move that does not exist.
+    UnknownMove {
+        enemy_name: String,
+        move_name: String,
+    },
+    /// An enemy knows a special move, which needs focus that only the player has.
+    EnemySpecialMove {
+        enemy_name: String,
+        move_name: String,
+    },
+    /// An enemy can never appear, as it has a group size of zero.
+    EmptyGroup { enemy_name: String },
+    /// A move has modifiers that would run out straight away.
+    NoModifierTurns { move_name: String },
+    /// A move is aimed at someone its effect can't be used on.
+    InvalidMoveTarget {
+        move_name: String,
+        effect: MoveEffect,
+        target: MoveTarget,
+    },
+    /// A move has a lower level requirement than the move before it.
+    MovesNotSorted {
+        move_name: String,
+        level_requirement: u32,
+        previous_requirement: u32,
+    },
+    /// There are no enemies that a new player can fight.
+    NoStartingEnemies,
+}
+
+impl fmt::Display for ContentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContentError::Io { path, err } => {
+                write!(f, "could not read {}: {}", path.display(), err)
+            }
+            ContentError::Parse { file, err } => write!(f, "{} is not valid: {}", file, err),
+            ContentError::DuplicateName { kind, name } => {
+                write!(f, "there is more than one {} named \"{}\"", kind, name)
+            }
+            ContentError::UnknownStatus { move_name, status } => write!(
+                f,
+                "move \"{}\" applies status \"{}\", which does not exist",
+                move_name, status
+            ),
+            ContentError::EnemyUnknownStatus { enemy_name, status } => write!(
+                f,
+                "enemy \"{}\" starts with status \"{}\", which does not exist",
+                enemy_name, status
+            ),
+            ContentError::EnemyStatusMaxHealth { enemy_name, status } => write!(
+                f,
+                "enemy \"{}\" starts with status \"{}\", which can't change max health",
+                enemy_name, status
+            ),
+            ContentError::ItemUnknownStatus { item_name, status } => write!(
+                f,
+                "item \"{}\" cures status \"{}\", which does not exist",
+                item_name, status
+            ),
+            ContentError::UnknownItem {
+                enemy_name,
+                item_name,
+            } => write!(
+                f,
+                "enemy \"{}\" drops \"{}\", which is not an item or gear",
+                enemy_name, item_name
+            ),
+            ContentError::InvalidDropChance {
+                enemy_name,
+                item_name,
+                chance,
+            } => write!(
+                f,
+                "enemy \"{}\" drops \"{}\" with a chance of {}, which is not between 0 and 1",
+                enemy_name, item_name, chance
+            ),
+            ContentError::UnknownMove {
+                enemy_name,
+                move_name,
+            } => write!(
+                f,
+                "enemy \"{}\" casts move \"{}\", which does not exist",
+                enemy_name, move_name
+            ),
+            ContentError::EnemySpecialMove {
+                enemy_name,
+                move_name,
+            } => write!(
+                f,
+                "enemy \"{}\" knows move \"{}\", which costs focus that enemies don't have",
+                enemy_name, move_name
+            ),
+            ContentError::EmptyGroup { enemy_name } => write!(
+                f,
+                "enemy \"{}\" has a max_group_size of 0, so it can never appear",
+                enemy_name
+            ),
+            ContentError::NoModifierTurns { move_name } => write!(
+                f,
+                "move \"{}\" has modifiers, but a modifier_turns of 0",
+                move_name
+            ),
+            ContentError::InvalidMoveTarget {
+                move_name,
+                effect,
+                target,
+            } => write!(
+                f,
+                "move \"{}\" has effect {:?}, which can't be aimed at {:?}",
+                move_name, effect, target
+            ),
+            ContentError::MovesNotSorted {
+                move_name,
+                level_requirement,
+                previous_requirement,
+            } => write!(
+                f,
+                "move \"{}\" has level requirement {}, but the move before it needs level {} \
+                 (moves must be ordered by level requirement)",
+                move_name, level_requirement, previous_requirement
+            ),
+            ContentError::NoStartingEnemies => write!(
+                f,
+                "there are no enemies that a level {} player can fight",
+                DEFAULT_PLAYER_LEVEL
+            ),
+        }
+    }
+}
+
+impl Error for ContentError {}
+
+impl Content {
+    /// Loads the content from the data files in a directory.
+    ///
+    /// # Params
+    /// - `dir` - The directory containing the data files.
+    ///
+    /// # Returns
+    /// - The loaded `Content`, or the first problem found with the data files.
+    pub fn load(dir: &Path) -> Result<Self, ContentError> {
+        let read = |file: &str| {
+            let path = dir.join(file);
+            fs::read_to_string(&path).map_err(|err| ContentError::Io { path, err })
+        };
+
+        Content::from_json(
+            &read(STATUSES_FILE)?,
+            &read(MOVES_FILE)?,
+            &read(ITEMS_FILE)?,
+            &read(GEAR_FILE)?,
+            &read(ENEMIES_FILE)?,
+        )
+    }
+
+    /// Loads the content from `DEFAULT_DATA_DIR` if it exists, otherwise uses
+    /// the content that ships with the game.
+    pub fn load_default() -> Result<Self, ContentError> {
+        let dir = Path::new(DEFAULT_DATA_DIR);
+
+        if dir.is_dir() {
+            Content::load(dir)
+        } else {
+            Ok(Content::built_in())
+        }
+    }
+
+    /// Gets the content that ships with the game.
+    pub fn built_in() -> Self {
+        Content::from_json(
+            BUILT_IN_STATUSES,
+            BUILT_IN_MOVES,
+            BUILT_IN_ITEMS,
+            BUILT_IN_GEAR,
+            BUILT_IN_ENEMIES,
+        )
+        .expect("the built in content should be valid")
+    }
+
+    /// Creates the content from the text of each data file.
+    ///
+    /// # Params
+    /// - `statuses` - The text of the statuses file.
+    /// - `moves` - The text of the moves file.
+    /// - `items` - The text of the items file.
+    /// - `gear` - The text of the gear file.
+    /// - `enemies` - The text of the enemies file.
+    pub fn from_json(
+        statuses: &str,
+        moves: &str,
+        items: &str,
+        gear: &str,
+        enemies: &str,
+    ) -> Result<Self, ContentError> {
+        let status_defs: Vec<StatusDef> = parse(STATUSES_FILE, statuses)?;
+        let move_defs: Vec<MoveDef> = parse(MOVES_FILE, moves)?;
+        let item_defs: Vec<ItemDef> = parse(ITEMS_FILE, items)?;
+        let gear: Vec<Gear> = parse(GEAR_FILE, gear)?;
+        let enemy_defs: Vec<EnemyDef> = parse(ENEMIES_FILE, enemies)?;
+
+        check_unique("status", status_defs.iter().map(|def| &def.name))?;
+        check_unique("move", move_defs.iter().map(|def| &def.name))?;
+        // loot tables find items and gear by name, so they share names
+        let loot_names: Vec<String> = item_defs
+            .iter()
+            .map(|def| def.name.clone())
+            .chain(gear.iter().map(|gear| gear.name()))
+            .collect();
+        check_unique("item or gear", loot_names.iter())?;
+        check_unique("enemy", enemy_defs.iter().map(|def| &def.name))?;
+
+        let statuses: Vec<Status> = status_defs
+            .into_iter()
+            .map(|def| {
+                Status::new(def.name, def.base_amount, def.is_healing, 0, def.num_turns)
+                    .with_modifiers(def.modifiers)
+                    .with_stacking(def.stacking)
+                    .with_restrictions(def.skips_turn, def.blocks_magic)
+                    .with_triggers(def.triggers)
+            })
+            .collect();
+
+        let mut moves = Vec::with_capacity(move_defs.len());
+        let mut previous_requirement = 0;
+        for def in move_defs {
+            // `Move::get_move_list` relies on this order
+            if def.level_requirement < previous_requirement {
+                return Err(ContentError::MovesNotSorted {
+                    move_name: def.name,
+                    level_requirement: def.level_requirement,
+                    previous_requirement,
+                });
+            }
+            previous_requirement = def.level_requirement;
+
+            let applied_status = match def.status {
+                Some(status_name) => match Status::get_status_from(&status_name, &statuses) {
+                    Some(status) => Some(status),
+                    None => {
+                        return Err(ContentError::UnknownStatus {
+                            move_name: def.name,
+                            status: status_name,
+                        })
+                    }
+                },
+                None => None,
+            };
+
+            if !def.modifiers.is_empty() && def.modifier_turns == 0 {
+                return Err(ContentError::NoModifierTurns {
+                    move_name: def.name,
+                });
+            }
+
+            if !def.effect.can_target(def.target) {
+                return Err(ContentError::InvalidMoveTarget {
+                    move_name: def.name,
+                    effect: def.effect,
+                    target: def.target,
+                });
+            }
+
+            moves.push(
+                Move::new(
+                    def.name,
+                    def.base_amount,
+                    def.mana_cost,
+                    def.level_requirement,
+                    def.element,
+                    applied_status,
+                )
+                .with_modifiers(def.modifiers, def.modifier_turns)
+                .with_effect(def.effect, def.target)
+                .with_focus_cost(def.focus_cost),
+            );
+        }
+
+        let mut items = Vec::with_capacity(item_defs.len());
+        let mut starting_inventory = Inventory::default();
+        for def in item_defs {
+            if let ItemEffect::Cure { status } = &def.effect {
+                if Status::get_status_from(status, &statuses).is_none() {
+                    return Err(ContentError::ItemUnknownStatus {
+                        item_name: def.name,
+                        status: status.clone(),
+                    });
+                }
+            }
+
+            let item = Item::new(def.name, def.effect);
+            starting_inventory.add(item.clone(), def.starting_count);
+            items.push(item);
+        }
+
+        // a new player needs something to fight
+        if !enemy_defs
+            .iter()
+            .any(|def| def.level <= DEFAULT_PLAYER_LEVEL + MAX_ENEMY_LEVELS_ABOVE_PLAYER)
+        {
+            return Err(ContentError::NoStartingEnemies);
+        }
+
+        let mut enemies = Vec::with_capacity(enemy_defs.len());
+        for def in enemy_defs {
+            if def.max_group_size == 0 {
+                return Err(ContentError::EmptyGroup {
+                    enemy_name: def.name,
+                });
+            }
+
+            let mut enemy_moves = Vec::with_capacity(def.moves.len());
+            for move_name in def.moves {
+                match moves.iter().find(|the_move| the_move.name() == move_name) {
+                    Some(the_move) if the_move.focus_cost() > 0 => {
+                        return Err(ContentError::EnemySpecialMove {
+                            enemy_name: def.name,
+                            move_name,
+                        })
+                    }
+                    // an enemy's opponent is the player, who would get the buff
+                    Some(the_move)
+                        if the_move.effect() == MoveEffect::Buff
+                            && the_move.target() != MoveTarget::User =>
+                    {
+                        return Err(ContentError::InvalidMoveTarget {
+                            move_name,
+                            effect: the_move.effect(),
+                            target: the_move.target(),
+                        })
+                    }
+                    Some(the_move) => enemy_moves.push(the_move.clone()),
+                    None => {
+                        return Err(ContentError::UnknownMove {
+                            enemy_name: def.name,
+                            move_name,
+                        })
+                    }
+                }
+            }
+
+            let mut drops = Vec::with_capacity(def.drops.len());
+            for drop in def.drops {
+                if !(0.0..=1.0).contains(&drop.chance) {
+                    return Err(ContentError::InvalidDropChance {
+                        enemy_name: def.name,
+                        item_name: drop.item,
+                        chance: drop.chance,
+                    });
+                }
+
+                let loot = match items.iter().find(|item| item.name() == drop.item) {
+                    Some(item) => Some(Loot::Item(item.clone())),
+                    None => gear
+                        .iter()
+                        .find(|gear| gear.name() == drop.item)
+                        .map(|gear| Loot::Gear(gear.clone())),
+                };
+
+                match loot {
+                    Some(loot) => drops.push(LootDrop::new(loot, drop.chance)),
+                    None => {
+                        return Err(ContentError::UnknownItem {
+                            enemy_name: def.name,
+                            item_name: drop.item,
+                        })
+                    }
+                }
+            }
+
+            let mut enemy_statuses = Vec::with_capacity(def.statuses.len());
+            for status_name in def.statuses {
+                match Status::get_status_from(&status_name, &statuses) {
+                    // the enemy would appear hurt, or lose health when the status ran out
+                    Some(status)
+                        if status
+                            .modifiers()
+                            .iter()
+                            .any(|modifier| modifier.stat() == StatType::MaxHealth) =>
+                    {
+                        return Err(ContentError::EnemyStatusMaxHealth {
+                            enemy_name: def.name,
+                            status: status_name,
+                        })
+                    }
+                    Some(status) => enemy_statuses.push(status),
+                    None => {
+                        return Err(ContentError::EnemyUnknownStatus {
+                            enemy_name: def.name,
+                            status: status_name,
+                        })
+                    }
+                }
+            }
+
+            enemies.push(
+                Enemy::new(def.name, def.stats, def.level)
+                    .with_affinities(def.affinities)
+                    .with_moves(enemy_moves)
+                    .with_ai(def.ai)
+                    .with_max_group_size(def.max_group_size)
+                    .with_drops(drops)
+                    .with_statuses(enemy_statuses),
+            );
+        }
+
+        Ok(Content {
+            statuses,
+            moves,
+            items,
+            starting_inventory,
+            gear,
+            enemies,
+            ai_registry: AiRegistry::default(),
+        })
+    }
+
+    /// Builder function for plugging in an enemy AI policy, which enemies pick in
+    /// their data with `AiPolicy::Custom`.
+    ///
+    /// # Params
+    /// - `name` - The name the enemies pick the policy by.
+    /// - `ai` - The policy.
+    ///
+    /// # Returns
+    /// - The `Content` object.
+    pub fn with_ai(mut self, name: &str, ai: impl EnemyAi + Send + Sync + 'static) -> Self {
+        self.ai_registry.register(name, ai);
+        self
+    }
+
+    /// Gets every status in the game.
+    pub fn statuses(&self) -> &[Status] {
+        &self.statuses
+    }
+
+    /// Gets every move in the game, ordered by level requirement.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// Gets every item in the game.
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// Gets the items that a new player carries.
+    pub fn starting_inventory(&self) -> Inventory {
+        self.starting_inventory.clone()
+    }
+
+    /// Gets every piece of gear in the game.
+    pub fn gear(&self) -> &[Gear] {
+        &self.gear
+    }
+
+    /// Gets every enemy in the game.
+    pub fn enemies(&self) -> &[Enemy] {
+        &self.enemies
+    }
+
+    /// Gets the enemy AI policies that were plugged in.
+    pub fn ai_registry(&self) -> &AiRegistry {
+        &self.ai_registry
+    }
+}
+
+/// Parses the text of a data file.
+///
+/// # Params
+/// - `file` - The name of the file, for errors.
+/// - `text` - The text of the file.
+fn parse<T: for<'de> Deserialize<'de>>(file: &str, text: &str) -> Result<T, ContentError> {
+    serde_json::from_str(text).map_err(|err| ContentError::Parse {
+        file: String::from(file),
+        err,
+    })
+}
+
+/// Checks that no two names are the same.
+///
+/// # Params
+/// - `kind` - What is being named, for errors.
+/// - `names` - The names to check.
+fn check_unique<'a>(
+    kind: &'static str,
+    names: impl Iterator<Item = &'a String>,
+) -> Result<(), ContentError> {
+    let mut seen = HashSet::new();
+
+    for name in names {
+        if !seen.insert(name) {
+            return Err(ContentError::DuplicateName {
+                kind,
+                name: name.clone(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the smallest content that is valid, which each test breaks in one place
+    const STATUSES: &str =
+        r#"[{"name": "Burn", "base_amount": 10, "is_healing": false, "num_turns": 5}]"#;
+    const MOVES: &str = r#"[{"name": "FireOne", "base_amount": 12, "mana_cost": 2,
+        "level_requirement": 1, "element": "Fire", "status": "Burn"}]"#;
+    const ITEMS: &str = r#"[{"name": "Potion", "effect": {"kind": "Heal", "amount": 25}}]"#;
+    const GEAR: &str = r#"[{"name": "Bone Sword", "slot": "Weapon", "modifiers": []}]"#;
+    const ENEMIES: &str = r#"[{"name": "Slime", "level": 1, "stats": {"health": 3, "mana": 0,
+        "speed": 2, "strength": 2, "magic_strength": 0, "defense": 0}}]"#;
+
+    /// Gets an enemy with one extra field, written as `"key": value`.
+    fn enemy_with(extra: &str) -> String {
+        format!(
+            r#"[{{"name": "Slime", "level": 1, "stats": {{"health": 3, "mana": 0,
+            "speed": 2, "strength": 2, "magic_strength": 0, "defense": 0}}, {}}}]"#,
+            extra
+        )
+    }
+
+    #[test]
+    fn built_in_content_is_valid() {
+        let content = Content::from_json(
+            BUILT_IN_STATUSES,
+            BUILT_IN_MOVES,
+            BUILT_IN_ITEMS,
+            BUILT_IN_GEAR,
+            BUILT_IN_ENEMIES,
+        );
+
+        assert!(content.is_ok(), "{}", content.err().unwrap());
+    }
+
+    #[test]
+    fn smallest_content_is_valid() {
+        assert!(Content::from_json(STATUSES, MOVES, ITEMS, GEAR, ENEMIES).is_ok());
+    }
+
+    #[test]
+    fn invalid_json_is_a_parse_error() {
+        let result = Content::from_json(STATUSES, "[{", ITEMS, GEAR, ENEMIES);
+
+        assert!(matches!(result, Err(ContentError::Parse { file, .. }) if file == MOVES_FILE));
+    }
+
+    #[test]
+    fn duplicate_names_are_rejected() {
+        let moves = MOVES.replace("}]", "}, ") + &MOVES[1..];
+        let result = Content::from_json(STATUSES, &moves, ITEMS, GEAR, ENEMIES);
+
+        assert!(matches!(
+            result,
+            Err(ContentError::DuplicateName { kind: "move", name }) if name == "FireOne"
+        ));
+    }
+
+    #[test]
+    fn items_and_gear_share_names() {
+        let gear = GEAR.replace("Bone Sword", "Potion");
+        let result = Content::from_json(STATUSES, MOVES, ITEMS, &gear, ENEMIES);
+
+        assert!(matches!(
+            result,
+            Err(ContentError::DuplicateName {
+                kind: "item or gear",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn move_with_unknown_status_is_rejected() {
+        let moves = MOVES.replace("\"Burn\"", "\"Freeze\"");
+        let result = Content::from_json(STATUSES, &moves, ITEMS, GEAR, ENEMIES);
+
+        assert!(matches!(
+            result,
+            Err(ContentError::UnknownStatus { status, .. }) if status == "Freeze"
+        ));
+    }
+
+    #[test]
+    fn enemy_with_unknown_status_is_rejected() {
+        let enemies = enemy_with(r#""statuses": ["Freeze"]"#);
+        let result = Content::from_json(STATUSES, MOVES, ITEMS, GEAR, &enemies);
+
+        assert!(matches!(
+            result,
+            Err(ContentError::EnemyUnknownStatus { status, .. }) if status == "Freeze"
+        ));
+    }
+
+    #[test]
+    fn enemy_starting_with_max_health_status_is_rejected() {
+        let statuses = r#"[{"name": "Burn", "base_amount": 10, "is_healing": false, "num_turns": 5},
+            {"name": "Vigor", "base_amount": 0, "is_healing": false, "num_turns": 3,
+            "modifiers": [{"stat": "MaxHealth", "kind": "Percent", "amount": 50}]}]"#;
+        let enemies = enemy_with(r#""statuses": ["Vigor"]"#);
+        let result = Content::from_json(statuses, MOVES, ITEMS, GEAR, &enemies);
+
+        assert!(matches!(
+            result,
+            Err(ContentError::EnemyStatusMaxHealth { status, .. }) if status == "Vigor"
+        ));
+    }
+
+    #[test]
+    fn item_curing_unknown_status_is_rejected() {
+        let items = r#"[{"name": "Ice Pack", "effect": {"kind": "Cure", "status": "Freeze"}}]"#;
+        let result = Content::from_json(STATUSES, MOVES, items, GEAR, ENEMIES);
+
+        assert!(matches!(
+            result,
+            Err(ContentError::ItemUnknownStatus { status, .. }) if status == "Freeze"
+        ));
+    }
+
+    #[test]
+    fn enemy_dropping_unknown_item_is_rejected() {
+        let enemies = enemy_with(r#""drops": [{"item": "Elixir", "chance": 0.5}]"#);
+        let result = Content::from_json(STATUSES, MOVES, ITEMS, GEAR, &enemies);
+
+        assert!(matches!(
+            result,
+            Err(ContentError::UnknownItem { item_name, .. }) if item_name == "Elixir"
+        ));
+    }
+
+    #[test]
+    fn enemy_can_drop_gear() {
+        let enemies = enemy_with(r#""drops": [{"item": "Bone Sword", "chance": 1.0}]"#);
+
+        assert!(Content::from_json(STATUSES, MOVES, ITEMS, GEAR, &enemies).is_ok());
+    }
+
+    #[test]
+    fn drop_chance_outside_of_0_and_1_is_rejected() {
+        for chance in ["-0.1", "1.5"] {
+            let enemies = enemy_with(&format!(
+                r#""drops": [{{"item": "Potion", "chance": {}}}]"#,
+                chance
+            ));
+            let result = Content::from_json(STATUSES, MOVES, ITEMS, GEAR, &enemies);
+
+            assert!(
+                matches!(result, Err(ContentError::InvalidDropChance { .. })),
+                "chance {} was allowed",
+                chance
+            );
+        }
+    }
+
+    #[test]
+    fn enemy_buffing_the_player_is_rejected() {
+        let moves = r#"[{"name": "Haste", "base_amount": 0, "mana_cost": 3,
+            "level_requirement": 1, "element": "Wind", "effect": "Buff", "target": "SingleEnemy"}]"#;
+        let enemies = enemy_with(r#""moves": ["Haste"]"#);
+
+        // the player can still cast it at an enemy
+        assert!(Content::from_json(STATUSES, moves, ITEMS, GEAR, ENEMIES).is_ok());
+        assert!(matches!(
+            Content::from_json(STATUSES, moves, ITEMS, GEAR, &enemies),
+            Err(ContentError::InvalidMoveTarget {
+                effect: MoveEffect::Buff,
+                target: MoveTarget::SingleEnemy,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn enemy_with_unknown_move_is_rejected() {
+        let enemies = enemy_with(r#""moves": ["IceOne"]"#);
+        let result = Content::from_json(STATUSES, MOVES, ITEMS, GEAR, &enemies);
+
+        assert!(matches!(
+            result,
+            Err(ContentError::UnknownMove { move_name, .. }) if move_name == "IceOne"
+        ));
+    }
+
+    #[test]
+    fn enemy_with_special_move_is_rejected() {
+        let moves = MOVES.replace("\"status\": \"Burn\"", "\"focus_cost\": 100");
+        let enemies = enemy_with(r#""moves": ["FireOne"]"#);
+        let result = Content::from_json(STATUSES, &moves, ITEMS, GEAR, &enemies);
+
+        assert!(matches!(result, Err(ContentError::EnemySpecialMove { .. })));
+    }
+
+    #[test]
+    fn enemy_can_pick_a_custom_ai() {
+        let enemies = enemy_with(r#""ai": {"policy": "Custom", "name": "Turtle"}"#);
+
+        assert!(Content::from_json(STATUSES, MOVES, ITEMS, GEAR, &enemies).is_ok());
+    }
+
+    #[test]
+    fn enemy_with_no_group_size_is_rejected() {
+        let enemies = enemy_with(r#""max_group_size": 0"#);
+        let result = Content::from_json(STATUSES, MOVES, ITEMS, GEAR, &enemies);
+
+        assert!(matches!(result, Err(ContentError::EmptyGroup { .. })));
+    }
+
+    #[test]
+    fn move_with_modifiers_but_no_turns_is_rejected() {
+        let moves = MOVES.replace(
+            "\"status\": \"Burn\"",
+            r#""modifiers": [{"stat": "Speed", "kind": "Flat", "amount": -2}]"#,
+        );
+        let result = Content::from_json(STATUSES, &moves, ITEMS, GEAR, ENEMIES);
+
+        assert!(matches!(result, Err(ContentError::NoModifierTurns { .. })));
+    }
+
+    #[test]
+    fn hostile_move_aimed_at_user_is_rejected() {
+        let moves = MOVES.replace("\"status\": \"Burn\"", "\"target\": \"User\"");
+        let result = Content::from_json(STATUSES, &moves, ITEMS, GEAR, ENEMIES);
+
+        assert!(matches!(
+            result,
+            Err(ContentError::InvalidMoveTarget {
+                target: MoveTarget::User,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn moves_out_of_level_order_are_rejected() {
+        let moves = r#"[
+            {"name": "Venom", "base_amount": 8, "mana_cost": 3, "level_requirement": 2, "element": "None"},
+            {"name": "FireOne", "base_amount": 12, "mana_cost": 2, "level_requirement": 1, "element": "Fire"}
+        ]"#;
+        let result = Content::from_json(STATUSES, moves, ITEMS, GEAR, ENEMIES);
+
+        assert!(matches!(
+            result,
+            Err(ContentError::MovesNotSorted {
+                level_requirement: 1,
+                previous_requirement: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn no_starting_enemies_is_rejected() {
+        let enemies = ENEMIES.replace("\"level\": 1", "\"level\": 10");
+        let result = Content::from_json(STATUSES, MOVES, ITEMS, GEAR, &enemies);
+
+        assert!(matches!(result, Err(ContentError::NoStartingEnemies)));
+    }
+}
@@ -0,0 +1,182 @@
+// file for how entities react to the elements of moves
+use serde::{Deserialize, Serialize};
+
+use super::moves::ElementType;
+
+/// How an entity reacts to moves of an element.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Affinity {
+    Weak, // takes extra damage
+    #[default]
+    Normal, // takes normal damage
+    Resist, // takes less damage
+    Immune, // takes no damage
+    Absorb, // is healed instead of damaged
+}
+
+impl Affinity {
+    /// Gets how much damage is multiplied by for this affinity.
+    ///
+    /// # Returns
+    /// - The damage multiplier. For `Absorb`, this is how much of the damage is healed instead.
+    pub fn multiplier(&self) -> f64 {
+        match self {
+            Affinity::Weak => 2.0,
+            Affinity::Normal => 1.0,
+            Affinity::Resist => 0.5,
+            Affinity::Immune => 0.0,
+            Affinity::Absorb => 1.0,
+        }
+    }
+
+    /// Scales an amount of damage by this affinity.
+    ///
+    /// # Params
+    /// - `amount` - The amount of damage before the affinity.
+    ///
+    /// # Returns
+    /// - The scaled amount of damage (or healing, for `Absorb`).
+    pub fn scale(&self, amount: u32) -> u32 {
+        (amount as f64 * self.multiplier()) as u32
+    }
+
+    /// Gets how well this affinity protects against an element, from `Weak` (the least)
+    /// to `Absorb` (the most).
+    fn protection(&self) -> u32 {
+        match self {
+            Affinity::Weak => 0,
+            Affinity::Normal => 1,
+            Affinity::Resist => 2,
+            Affinity::Immune => 3,
+            Affinity::Absorb => 4,
+        }
+    }
+
+    /// Combines two affinities to the same element. A listed affinity beats `Normal`,
+    /// and the more protective of two listed affinities wins.
+    fn combine(self, other: Affinity) -> Affinity {
+        match (self, other) {
+            (Affinity::Normal, affinity) | (affinity, Affinity::Normal) => affinity,
+            _ if self.protection() >= other.protection() => self,
+            _ => other,
+        }
+    }
+}
+
+/// The affinity of an entity to each element.
+/// Any element that is not listed is `Affinity::Normal`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "PascalCase")]
+pub struct Affinities {
+    fire: Affinity,
+    wind: Affinity,
+    earth: Affinity,
+    water: Affinity,
+}
+
+impl Affinities {
+    /// The elements an entity can have an affinity to.
+    pub const ELEMENTS: [ElementType; 4] = [
+        ElementType::Fire,
+        ElementType::Wind,
+        ElementType::Earth,
+        ElementType::Water,
+    ];
+
+    /// Gets the affinity to an element.
+    ///
+    /// # Params
+    /// - `element` - The element of the move.
+    ///
+    /// # Returns
+    /// - The `Affinity` to `element`.
+    pub fn get(&self, element: ElementType) -> Affinity {
+        match element {
+            ElementType::Fire => self.fire,
+            ElementType::Wind => self.wind,
+            ElementType::Earth => self.earth,
+            ElementType::Water => self.water,
+            ElementType::None => Affinity::Normal,
+        }
+    }
+
+    /// Gets the elements that don't have an `Affinity::Normal`, and the affinity to each.
+    pub fn listed(&self) -> Vec<(ElementType, Affinity)> {
+        Affinities::ELEMENTS
+            .into_iter()
+            .map(|element| (element, self.get(element)))
+            .filter(|(_, affinity)| *affinity != Affinity::Normal)
+            .collect()
+    }
+
+    /// Combines these affinities with another set, such as when wearing more than
+    /// one piece of gear.
+    ///
+    /// # Params
+    /// - `other` - The affinities to combine with.
+    ///
+    /// # Returns
+    /// - For each element, the listed affinity of the two, or the more protective
+    ///   one if both are listed.
+    pub fn combine(&self, other: &Affinities) -> Affinities {
+        Affinities {
+            fire: self.fire.combine(other.fire),
+            wind: self.wind.combine(other.wind),
+            earth: self.earth.combine(other.earth),
+            water: self.water.combine(other.water),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn affinities(json: &str) -> Affinities {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn unlisted_elements_are_normal() {
+        let affinities = affinities(r#"{"Fire": "Weak"}"#);
+
+        assert_eq!(affinities.get(ElementType::Fire), Affinity::Weak);
+        assert_eq!(affinities.get(ElementType::Water), Affinity::Normal);
+        assert_eq!(affinities.get(ElementType::None), Affinity::Normal);
+        assert_eq!(affinities.listed(), [(ElementType::Fire, Affinity::Weak)]);
+    }
+
+    #[test]
+    fn listed_affinity_beats_normal() {
+        let weak = affinities(r#"{"Fire": "Weak"}"#);
+
+        let combined = Affinities::default().combine(&weak);
+        assert_eq!(combined.get(ElementType::Fire), Affinity::Weak);
+        assert_eq!(weak.combine(&Affinities::default()), combined);
+    }
+
+    #[test]
+    fn more_protective_affinity_wins() {
+        let first = affinities(r#"{"Fire": "Weak", "Wind": "Absorb"}"#);
+        let second = affinities(r#"{"Fire": "Resist", "Wind": "Immune", "Earth": "Resist"}"#);
+
+        let combined = first.combine(&second);
+        assert_eq!(combined, second.combine(&first));
+        assert_eq!(
+            combined.listed(),
+            [
+                (ElementType::Fire, Affinity::Resist),
+                (ElementType::Wind, Affinity::Absorb),
+                (ElementType::Earth, Affinity::Resist),
+            ]
+        );
+    }
+
+    #[test]
+    fn affinities_scale_damage() {
+        assert_eq!(Affinity::Weak.scale(10), 20);
+        assert_eq!(Affinity::Normal.scale(10), 10);
+        assert_eq!(Affinity::Resist.scale(10), 5);
+        assert_eq!(Affinity::Immune.scale(10), 0);
+    }
+}
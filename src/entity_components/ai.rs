@@ -0,0 +1,341 @@
+// file for how enemies decide what to do on their turn
+use std::{collections::HashMap, sync::Arc};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::affinity::Affinity;
+use super::enemy::Enemy;
+use super::entity::Entity;
+use super::moves::MoveEffect;
+use super::stats::DefendStance;
+use crate::battle::engine::Action;
+use crate::rng::GameRng;
+
+/// Trait for deciding what an enemy does on its turn.
+///
+/// The built in policies are listed in `AiPolicy`, which is what each enemy
+/// is given in its data file. Other policies can be plugged in by name with
+/// `Content::with_ai`, and picked in the data file with `AiPolicy::Custom`.
+pub trait EnemyAi {
+    /// Chooses the action for an enemy's turn.
+    ///
+    /// # Params
+    /// - `enemy` - The enemy that is taking its turn.
+    /// - `target` - The entity the enemy is fighting.
+    /// - `rng` - The random number generator of the game.
+    ///
+    /// # Returns
+    /// - The `Action` the enemy takes. `Action::Magic` indexes into the enemy's own moves.
+    fn choose_action(&self, enemy: &Enemy, target: &dyn Entity, rng: &mut GameRng) -> Action;
+}
+
+/// The built in enemy AI policies, which are chosen for each enemy in its data file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(tag = "policy", deny_unknown_fields)]
+pub enum AiPolicy {
+    #[default]
+    Aggressive,
+    Defensive(Defensive),
+    Caster,
+    RandomWeighted(RandomWeighted),
+    Custom {
+        name: String, // the name the policy was plugged in with
+    },
+}
+
+impl EnemyAi for AiPolicy {
+    fn choose_action(&self, enemy: &Enemy, target: &dyn Entity, rng: &mut GameRng) -> Action {
+        match self {
+            AiPolicy::Aggressive => Aggressive.choose_action(enemy, target, rng),
+            AiPolicy::Defensive(policy) => policy.choose_action(enemy, target, rng),
+            AiPolicy::Caster => Caster.choose_action(enemy, target, rng),
+            AiPolicy::RandomWeighted(policy) => policy.choose_action(enemy, target, rng),
+            // custom policies are found in an `AiRegistry`, so without one the enemy attacks
+            AiPolicy::Custom { .. } => Aggressive.choose_action(enemy, target, rng),
+        }
+    }
+}
+
+/// The enemy AI policies that were plugged in from outside of the game data, by name.
+#[derive(Clone, Default)]
+pub struct AiRegistry {
+    policies: HashMap<String, Arc<dyn EnemyAi + Send + Sync>>,
+}
+
+impl AiRegistry {
+    /// Adds a policy, replacing any policy with the same name.
+    ///
+    /// # Params
+    /// - `name` - The name enemies pick the policy by, with `AiPolicy::Custom`.
+    /// - `ai` - The policy.
+    pub fn register(&mut self, name: &str, ai: impl EnemyAi + Send + Sync + 'static) {
+        self.policies.insert(String::from(name), Arc::new(ai));
+    }
+
+    /// Gets the policy that makes the decisions for an `AiPolicy`.
+    ///
+    /// # Params
+    /// - `policy` - The policy an enemy was given.
+    ///
+    /// # Returns
+    /// - The plugged in policy for `AiPolicy::Custom`, or the `AiPolicy` itself otherwise.
+    ///   A custom policy that was never plugged in is `Aggressive`.
+    pub fn policy_for<'a>(&'a self, policy: &'a AiPolicy) -> &'a dyn EnemyAi {
+        match policy {
+            AiPolicy::Custom { name } => match self.policies.get(name) {
+                Some(ai) => ai.as_ref(),
+                None => &Aggressive,
+            },
+            policy => policy,
+        }
+    }
+}
+
+/// Always attacks.
+pub struct Aggressive;
+
+impl EnemyAi for Aggressive {
+    fn choose_action(&self, _enemy: &Enemy, _target: &dyn Entity, _rng: &mut GameRng) -> Action {
+        Action::Attack
+    }
+}
+
+/// Attacks, but often defends once its health gets low.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Defensive {
+    health_threshold: f64, // the fraction of max health to start defending below
+    defend_chance: f64,    // the chance of defending when below the threshold
+}
+
+impl EnemyAi for Defensive {
+    fn choose_action(&self, enemy: &Enemy, _target: &dyn Entity, rng: &mut GameRng) -> Action {
+        let health_fraction = enemy.health() as f64 / enemy.max_health().max(1) as f64;
+
+        if health_fraction < self.health_threshold
+            && rng.gen_bool(self.defend_chance.clamp(0.0, 1.0))
+        {
+            Action::Defend(DefendStance::default())
+        } else {
+            Action::Attack
+        }
+    }
+}
+
+/// The fraction of max health a `Caster` starts healing itself below.
+const CASTER_HEAL_THRESHOLD: f64 = 0.3;
+
+/// Casts the strongest move it can afford against the target, and attacks when out of mana.
+/// Heals itself instead when its health gets low, if it knows how.
+pub struct Caster;
+
+impl EnemyAi for Caster {
+    fn choose_action(&self, enemy: &Enemy, target: &dyn Entity, _rng: &mut GameRng) -> Action {
+        let mut best: Option<(usize, u32)> = None;
+        if enemy.is_silenced() {
+            return Action::Attack;
+        }
+
+        let health_fraction = enemy.health() as f64 / enemy.max_health().max(1) as f64;
+        if health_fraction < CASTER_HEAL_THRESHOLD {
+            let heal = enemy.moves().iter().position(|the_move| {
+                the_move.effect() == MoveEffect::HealSelf && the_move.cost() <= enemy.mana()
+            });
+            if let Some(move_idx) = heal {
+                return Action::Magic(move_idx);
+            }
+        }
+
+        for (i, the_move) in enemy.moves().iter().enumerate() {
+            let affinity = target.affinity(the_move.element());
+            // only moves that deal damage are worth comparing
+            let is_damaging = matches!(
+                the_move.effect(),
+                MoveEffect::Damage | MoveEffect::DrainHealth
+            );
+            if !is_damaging || the_move.cost() > enemy.mana() || affinity == Affinity::Absorb {
+                continue;
+            }
+
+            // how hard the move should hit, ignoring the random roll
+            let expected = affinity.scale(the_move.base_amount() + enemy.magic_strength());
+            if expected > 0 && best.is_none_or(|(_, best_amount)| expected > best_amount) {
+                best = Some((i, expected));
+            }
+        }
+
+        match best {
+            Some((move_idx, _)) => Action::Magic(move_idx),
+            None => Action::Attack,
+        }
+    }
+}
+
+/// Picks a random action, with each kind of action weighted.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RandomWeighted {
+    attack: u32,
+    magic: u32,
+    defend: u32,
+}
+
+impl EnemyAi for RandomWeighted {
+    fn choose_action(&self, enemy: &Enemy, _target: &dyn Entity, rng: &mut GameRng) -> Action {
+        // only the moves the enemy can afford can be picked
+        let affordable: Vec<usize> = (0..enemy.moves().len())
+            .filter(|i| enemy.moves()[*i].cost() <= enemy.mana())
+            .collect();
+        let magic_weight = if affordable.is_empty() || enemy.is_silenced() {
+            0
+        } else {
+            self.magic
+        };
+
+        // the weights come from the data files, so huge ones are capped rather than overflowing
+        let attack_and_magic = self.attack.saturating_add(magic_weight);
+        let total = attack_and_magic.saturating_add(self.defend);
+        if total == 0 {
+            return Action::Attack;
+        }
+
+        let roll = rng.gen_range(0..total);
+        if roll < self.attack {
+            Action::Attack
+        } else if roll < attack_and_magic {
+            Action::Magic(affordable[rng.gen_range(0..affordable.len())])
+        } else {
+            Action::Defend(DefendStance::default())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity_components::{
+        affinity::Affinities,
+        moves::{ElementType, Move, MoveTarget},
+        stats::Stats,
+        status::Status,
+    };
+    use crate::rng::create_rng;
+
+    const SEED: u64 = 3;
+    const ROLLS: usize = 100;
+
+    fn spell(name: &str, base_amount: u32, mana_cost: u32, element: ElementType) -> Move {
+        Move::new(String::from(name), base_amount, mana_cost, 1, element, None)
+    }
+
+    /// Creates an enemy with 10 mana that knows `moves`.
+    fn enemy(moves: Vec<Move>) -> Enemy {
+        Enemy::new(String::from("Witch"), Stats::new(10, 4, 1, 1, 0, 0), 1).with_moves(moves)
+    }
+
+    fn target() -> Enemy {
+        enemy(Vec::new())
+    }
+
+    /// Rolls a policy many times, returning every action it chose.
+    fn roll(policy: &dyn EnemyAi, enemy: &Enemy) -> Vec<Action> {
+        let mut rng = create_rng(SEED);
+        (0..ROLLS)
+            .map(|_| policy.choose_action(enemy, &target(), &mut rng))
+            .collect()
+    }
+
+    #[test]
+    fn caster_picks_the_hardest_hitting_move_it_can_afford() {
+        let caster = enemy(vec![
+            spell("Spark", 3, 1, ElementType::Fire),
+            spell("Gust", 8, 2, ElementType::Wind),
+            spell("Meteor", 50, 20, ElementType::Earth),
+            spell("Flood", 30, 1, ElementType::Water),
+            spell("Wall", 100, 1, ElementType::None)
+                .with_effect(MoveEffect::Shield, MoveTarget::User),
+        ]);
+        let absorbs_water: Affinities = serde_json::from_str(r#"{"Water": "Absorb"}"#).unwrap();
+        let target = target().with_affinities(absorbs_water);
+
+        let action = Caster.choose_action(&caster, &target, &mut create_rng(SEED));
+
+        // Meteor costs too much, Flood would heal the target and Wall doesn't hurt
+        assert_eq!(action, Action::Magic(1));
+    }
+
+    #[test]
+    fn caster_heals_when_its_health_is_low() {
+        let mend = spell("Mend", 5, 1, ElementType::None)
+            .with_effect(MoveEffect::HealSelf, MoveTarget::User);
+        let mut caster = enemy(vec![spell("Spark", 3, 1, ElementType::Fire), mend]);
+        let mut rng = create_rng(SEED);
+
+        assert_eq!(
+            Caster.choose_action(&caster, &target(), &mut rng),
+            Action::Magic(0)
+        );
+
+        caster.take_damage(caster.max_health() * 3 / 4);
+        assert!(caster.health_bar().fraction() < CASTER_HEAL_THRESHOLD);
+        assert_eq!(
+            Caster.choose_action(&caster, &target(), &mut rng),
+            Action::Magic(1)
+        );
+    }
+
+    #[test]
+    fn random_weighted_only_casts_when_it_can() {
+        let policy = RandomWeighted {
+            attack: 0,
+            magic: 1,
+            defend: 1,
+        };
+        let spark = spell("Spark", 3, 1, ElementType::Fire);
+        let is_magic = |action: &Action| matches!(action, Action::Magic(_));
+
+        let caster = enemy(vec![spark.clone()]);
+        assert!(roll(&policy, &caster).iter().any(is_magic));
+
+        let mut silenced = enemy(vec![spark.clone()]);
+        let silence =
+            Status::new(String::from("Silence"), 0, false, 0, 3).with_restrictions(false, true);
+        silenced.apply_status(&silence, &mut Vec::new());
+        assert!(!roll(&policy, &silenced).iter().any(is_magic));
+
+        let mut drained = enemy(vec![spark]);
+        drained.use_mana(drained.max_mana());
+        assert!(!roll(&policy, &drained).iter().any(is_magic));
+    }
+
+    #[test]
+    fn random_weighted_survives_huge_weights() {
+        let policy = RandomWeighted {
+            attack: u32::MAX,
+            magic: u32::MAX,
+            defend: u32::MAX,
+        };
+        let caster = enemy(vec![spell("Spark", 3, 1, ElementType::Fire)]);
+
+        assert!(roll(&policy, &caster)
+            .iter()
+            .all(|action| *action == Action::Attack));
+    }
+
+    #[test]
+    fn custom_policy_that_is_not_plugged_in_attacks() {
+        let policy = AiPolicy::Custom {
+            name: String::from("Missing"),
+        };
+        let caster = enemy(vec![spell("Spark", 3, 1, ElementType::Fire)]);
+        let registry = AiRegistry::default();
+
+        assert!(roll(registry.policy_for(&policy), &caster)
+            .iter()
+            .all(|action| *action == Action::Attack));
+        assert!(roll(&policy, &caster)
+            .iter()
+            .all(|action| *action == Action::Attack));
+    }
+}
@@ -0,0 +1,102 @@
+// file for the state that every entity in a fight has
+use serde::{Deserialize, Serialize};
+
+use super::affinity::Affinities;
+use super::resource::Resource;
+use super::stats::Stats;
+use super::status::Status;
+
+/// The state shared by the `Player` and enemies. Each of them holds one, and the
+/// `Entity` trait builds its default behaviour on top of it, so a mechanic only
+/// has to be written once to work for everyone in a fight.
+///
+/// It is flattened into the entity holding it when saved, so its fields sit
+/// alongside the entity's own.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CombatantCore {
+    name: String,
+    health: Resource,
+    mana: Resource,
+    stats: Stats,
+    level: u32,
+    statuses: Vec<Status>,
+    #[serde(default)]
+    affinities: Affinities, // how this entity reacts to each element
+}
+
+impl CombatantCore {
+    /// Creates a new `CombatantCore`, with full health and mana and no statuses.
+    ///
+    /// # Params
+    /// - `name` - The name of the entity.
+    /// - `stats` - The `Stats` of the entity.
+    /// - `level` - The level of the entity.
+    pub fn new(name: String, stats: Stats, level: u32) -> Self {
+        Self {
+            name,
+            health: Resource::new(stats.calculate_max_health()),
+            mana: Resource::new(stats.calculate_max_mana()),
+            stats,
+            level,
+            statuses: Vec::new(), // start with no statuses
+            affinities: Affinities::default(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn health(&self) -> &Resource {
+        &self.health
+    }
+
+    pub fn health_mut(&mut self) -> &mut Resource {
+        &mut self.health
+    }
+
+    pub fn mana(&self) -> &Resource {
+        &self.mana
+    }
+
+    pub fn mana_mut(&mut self) -> &mut Resource {
+        &mut self.mana
+    }
+
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    pub fn stats_mut(&mut self) -> &mut Stats {
+        &mut self.stats
+    }
+
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    /// Raises the level of the entity by one.
+    pub fn level_up(&mut self) {
+        self.level += 1;
+    }
+
+    pub fn statuses(&self) -> &[Status] {
+        &self.statuses
+    }
+
+    pub fn statuses_mut(&mut self) -> &mut Vec<Status> {
+        &mut self.statuses
+    }
+
+    pub fn affinities(&self) -> &Affinities {
+        &self.affinities
+    }
+
+    pub fn set_affinities(&mut self, affinities: Affinities) {
+        self.affinities = affinities;
+    }
+}
@@ -1,27 +1,39 @@
-use std::collections::VecDeque;
-
-use crate::entity_components::{entity::Entity, moves::MoveType, stats::Stats};
-use ratatui::text;
-
+use crate::battle::engine::Action;
+use crate::battle::event::BattleEvent;
+use crate::battle::outcome::{ActionError, ActionOutcome};
+use crate::entity_components::{entity::Entity, moves::Move, stats::Stats};
+use crate::rng::GameRng;
+
+use serde::{Deserialize, Serialize};
+
+use super::affinity::Affinities;
+use super::ai::{AiPolicy, AiRegistry};
+use super::combatant::CombatantCore;
+use super::item::{Loot, LootDrop};
 use super::status::Status;
 
 ///Struct to represent an enemy.
 ///Implements the Entity trait.
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Enemy {
-    name: String,
-    health: u32,
-    max_health: u32,
-    mana: u32,
-    max_mana: u32,
-    stats: Stats,
-    level: u32,
-    has_gone: bool,
-    statuses: Vec<Status>,
+    #[serde(flatten)]
+    core: CombatantCore, // the name, health, mana, stats, level and statuses
+    #[serde(default)]
+    moves: Vec<Move>, // the magic moves this enemy can cast
+    #[serde(default)]
+    ai: AiPolicy, // how this enemy chooses what to do on its turn
+    #[serde(default = "default_max_group_size")]
+    max_group_size: u32, // the most of this enemy that can appear in one encounter
+    #[serde(default)]
+    drops: Vec<LootDrop>, // the loot table of this enemy, for when it dies
 }
 
 const BASE_XP: u32 = 20; // the base xp dropped by an enemy
 
+fn default_max_group_size() -> u32 {
+    1
+}
+
 impl Enemy {
     /// Create a new `Enemy`
     ///
@@ -29,232 +41,228 @@ impl Enemy {
     /// - `name` - The name of the `Enemy`.
     /// - `stats` - The `Stats` of the `Enemy`.
     /// - `level` - The level of the `Enemy`
-    /// - `has_gone` - If this `Enemy` has gone this turn.
-    pub fn new(name: String, stats: Stats, level: u32, has_gone: bool) -> Self {
-        let starting_health = stats.calculate_max_health();
-        let starting_mana = stats.calculate_max_mana();
+    pub fn new(name: String, stats: Stats, level: u32) -> Self {
         Self {
-            name,
-            health: starting_health,
-            max_health: starting_health,
-            mana: starting_mana,
-            max_mana: starting_mana,
-            stats,
-            level,
-            has_gone,
-            statuses: Vec::new(), // start with no statuses
+            core: CombatantCore::new(name, stats, level),
+            moves: Vec::new(),
+            ai: AiPolicy::default(),
+            max_group_size: default_max_group_size(),
+            drops: Vec::new(),
         }
     }
 
-    /// Calculate the xp dropped by this `Enemy`.
+    /// Builder function for setting the name of an `Enemy`.
     ///
     /// # Params
-    /// - `player_level` - The level of the player.
+    /// - `name` - The name of the `Enemy`.
     ///
     /// # Returns
-    /// - The xp dropped by this `Enemy`.
-    pub fn drop_xp(&self, player_level: u32, text_vec: &mut VecDeque<String>) -> u32 {
-        let mut amount = BASE_XP; // start with a base xp
-
-        let num_levels_above_player = self.level as i64 - player_level as i64;
-        for _i in 0..num_levels_above_player {
-            amount *= 2; // just crazy xp as enemies get way higher leveled than you
-        }
-
-        text_vec.push_back(format!("{} dropped {} xp!", self.name, amount));
-
-        amount
+    /// - The `Enemy` object.
+    pub fn with_name(mut self, name: String) -> Self {
+        self.core.set_name(name);
+        self
     }
 
-    /// Display the text for attacking another `Entity`.
+    /// Builder function for setting the affinities of an `Enemy`.
     ///
     /// # Params
-    /// - `victim_entity_name` - The name of the `Entity` that is receiving the attack.
-    /// - `damage_dealt` - The amount of damage dealt to this `Entity`.
-    fn display_attack_text(
-        &self,
-        victim_entity_name: String,
-        damage_dealt: u32,
-        text_vec: &mut VecDeque<String>,
-    ) {
-        text_vec.push_back(format!(
-            "{} did {} damage to {}",
-            self.name,
-            damage_dealt.to_string(),
-            victim_entity_name
-        ));
-    }
-
-    /// Create the enemy list for the game
+    /// - `affinities` - How the `Enemy` reacts to each element.
     ///
     /// # Returns
-    /// - The full enemy list for the game
-    pub fn create_enemy_list() -> Vec<Enemy> {
-        vec![
-            Enemy::new("Spider".to_string(), Stats::new(5, 0, 4, 2, 1, 0), 1, false),
-            Enemy::new(
-                "Skeleton".to_string(),
-                Stats::new(3, 0, 3, 5, 4, 0),
-                1,
-                false,
-            ),
-            Enemy::new(
-                "Dragon".to_string(),
-                Stats::new(20, 100, 10, 10, 10, 10),
-                8,
-                false,
-            ),
-        ]
+    /// - The `Enemy` object.
+    pub fn with_affinities(mut self, affinities: Affinities) -> Self {
+        self.core.set_affinities(affinities);
+        self
     }
-}
-
-//entity implementation for enemy
-impl Entity for Enemy {
-    fn take_damage(&mut self, amount: u32) -> u32 {
-        let damage_taken = self.stats.calc_damage_taken(amount);
-
-        if damage_taken > self.health {
-            self.health = 0;
-        } else {
-            self.health -= damage_taken;
-        }
 
-        damage_taken
-    }
-
-    fn heal(&mut self, amount: u32) {
-        self.health += amount;
-    }
-
-    fn use_mana(&mut self, amount: u32) {
-        if amount > self.mana {
-            self.mana = 0;
-        } else {
-            self.mana -= amount;
-        }
-    }
-
-    fn speed(&self) -> u32 {
-        self.stats.get_speed()
+    /// Builder function for setting the magic moves of an `Enemy`.
+    ///
+    /// # Params
+    /// - `moves` - The magic moves the `Enemy` can cast.
+    ///
+    /// # Returns
+    /// - The `Enemy` object.
+    pub fn with_moves(mut self, moves: Vec<Move>) -> Self {
+        self.moves = moves;
+        self
     }
 
-    fn is_dead(&self) -> bool {
-        self.health == 0
+    /// Builder function for setting the AI policy of an `Enemy`.
+    ///
+    /// # Params
+    /// - `ai` - How the `Enemy` chooses what to do on its turn.
+    ///
+    /// # Returns
+    /// - The `Enemy` object.
+    pub fn with_ai(mut self, ai: AiPolicy) -> Self {
+        self.ai = ai;
+        self
     }
 
-    fn gone_this_turn(&self) -> bool {
-        self.has_gone
+    /// Builder function for setting how many of an `Enemy` can appear in one encounter.
+    ///
+    /// # Params
+    /// - `max_group_size` - The most of this `Enemy` that can appear together.
+    ///
+    /// # Returns
+    /// - The `Enemy` object.
+    pub fn with_max_group_size(mut self, max_group_size: u32) -> Self {
+        self.max_group_size = max_group_size;
+        self
     }
 
-    // The Enemy makes a choice as to what type of move it wants to do this turn
-    // FUTURE: implement AI for this
-    fn get_turn_type(&mut self) -> Option<MoveType> {
-        Some(MoveType::AttackMove)
+    /// Builder function for setting the loot an `Enemy` can drop.
+    ///
+    /// # Params
+    /// - `drops` - The items and gear the `Enemy` can drop, each with its own chance.
+    ///
+    /// # Returns
+    /// - The `Enemy` object.
+    pub fn with_drops(mut self, drops: Vec<LootDrop>) -> Self {
+        self.drops = drops;
+        self
     }
 
-    fn get_random_attack_dmg(&self) -> u32 {
-        self.stats.generate_random_attack_dmg()
+    /// Builder function for setting the statuses an `Enemy` starts with.
+    ///
+    /// # Params
+    /// - `statuses` - The statuses to apply to the `Enemy`.
+    ///
+    /// # Returns
+    /// - The `Enemy` object.
+    pub fn with_statuses(mut self, statuses: Vec<Status>) -> Self {
+        // nobody sees the enemy get these, so there is nothing to report
+        let mut events = Vec::new();
+        for status in &statuses {
+            self.apply_status(status, &mut events);
+        }
+        self
     }
 
-    fn name(&self) -> String {
-        self.name.clone()
+    /// Gets the most of this `Enemy` that can appear in one encounter.
+    pub fn max_group_size(&self) -> u32 {
+        self.max_group_size
     }
 
-    fn level(&self) -> u32 {
-        self.level
-    }
+    /// Calculate the xp dropped by this `Enemy`.
+    ///
+    /// # Params
+    /// - `player_level` - The level of the player.
+    /// - `events` - The events of the battle, to push the xp that was dropped.
+    ///
+    /// # Returns
+    /// - The xp dropped by this `Enemy`.
+    pub fn drop_xp(&self, player_level: u32, events: &mut Vec<BattleEvent>) -> u32 {
+        let mut amount = BASE_XP; // start with a base xp
 
-    fn magic_strength(&self) -> u32 {
-        self.stats.get_magic_strength()
-    }
+        let num_levels_above_player = self.level() as i64 - player_level as i64;
+        for _i in 0..num_levels_above_player {
+            amount *= 2; // just crazy xp as enemies get way higher leveled than you
+        }
 
-    fn start_defending(&mut self) {
-        self.stats.start_defending();
-    }
+        events.push(BattleEvent::XpGained {
+            source: self.name(),
+            amount,
+        });
 
-    fn stop_defending(&mut self) {
-        self.stats.stop_defending()
+        amount
     }
 
-    fn tick_statuses(&mut self, text_vec: &mut VecDeque<String>) {
-        let mut indicies_to_remove: Vec<usize> = Vec::new();
-
-        for i in 0..self.statuses.len() {
-            // TODO: there is a bug with this going out of bounds
-            let amount = self.statuses[i].calculate_amount();
-
-            // mark this status for removal if it has no turns left
-            if self.statuses[i].tick() {
-                // push this index
-                indicies_to_remove.push(i);
-            }
-
-            if self.statuses[i].is_healing() {
-                text_vec.push_back(format!(
-                    "{} healed {} health from {}!",
-                    self.name,
-                    amount.to_string().as_str(),
-                    self.statuses[i].name()
-                ));
-                self.heal(amount);
-            } else {
-                text_vec.push_back(format!(
-                    "{} took {} damage from {}!",
-                    self.name,
-                    amount.to_string().as_str(),
-                    self.statuses[i].name()
-                ));
-                self.take_damage(amount);
-            }
-
-            let mut cur_num_removed = 0;
-            // remove all statuses that were marked for removal
-            for i in 0..indicies_to_remove.len() {
-                // since the indices of the elements will change due to the removal
-                let index = indicies_to_remove[i] - cur_num_removed;
-                self.statuses.remove(index);
-                cur_num_removed += 1; // we have removed another status
-            }
-        }
+    /// Roll for the loot dropped by this `Enemy`.
+    ///
+    /// # Params
+    /// - `rng` - The random number generator of the game.
+    /// - `events` - The events of the battle, to push the loot that was dropped.
+    ///
+    /// # Returns
+    /// - The items and gear dropped by this `Enemy`.
+    pub fn drop_loot(&self, rng: &mut GameRng, events: &mut Vec<BattleEvent>) -> Vec<Loot> {
+        self.drops
+            .iter()
+            .filter_map(|drop| drop.roll(self.core.name(), rng, events))
+            .collect()
     }
 
-    fn apply_status(&mut self, status: &Status, text_vec: &mut VecDeque<String>) {
-        text_vec.push_back(format!("{} applied to {}", status.name(), self.name()));
-        self.statuses.push(status.clone());
+    /// The `Enemy` casts one of its magic moves against another `Entity`.
+    ///
+    /// # Params
+    /// - `target` - The target of the attack.
+    /// - `move_idx` - The index of the move in this `Enemy`'s moves.
+    /// - `rng` - The random number generator of the game.
+    /// - `events` - The events of the battle, to push what the move did.
+    ///
+    /// # Returns
+    /// - The move that was cast, or why it couldn't be cast.
+    pub fn magic_move(
+        &mut self,
+        target: &mut dyn Entity,
+        move_idx: usize,
+        rng: &mut GameRng,
+        events: &mut Vec<BattleEvent>,
+    ) -> Result<ActionOutcome, ActionError> {
+        let the_move = self.check_magic(move_idx)?.clone();
+
+        let random_damage = the_move.generate_random_amount(self.magic_strength(), rng);
+        // use the mana from this move
+        self.use_mana(the_move.cost());
+
+        self.cast_move(&the_move, random_damage, vec![target], rng, events);
+
+        Ok(ActionOutcome::Cast {
+            move_name: the_move.name(),
+        })
     }
 
-    fn attack_move(&mut self, target: &mut dyn Entity, text_vec: &mut VecDeque<String>) -> bool {
-        if self.has_gone {
-            return true; // has gone, error
-        }
-        // attack the player with a random amount of damage
-        let random_damage = self.get_random_attack_dmg();
-
-        let damage_dealt = self.attack_entity(random_damage, target);
-        // display the text for an attack
-        self.display_attack_text(target.name(), damage_dealt, text_vec);
-
-        // the enemy has gone
-        self.has_gone = true;
-
-        // no error
-        false
+    /// Checks if the `Enemy` can cast one of its magic moves right now.
+    ///
+    /// # Params
+    /// - `move_idx` - The index of the move in this `Enemy`'s moves.
+    ///
+    /// # Returns
+    /// - The move, or why it can't be cast.
+    pub fn check_magic(&self, move_idx: usize) -> Result<&Move, ActionError> {
+        let the_move = match self.moves.get(move_idx) {
+            Some(the_move) => the_move,
+            None => return Err(ActionError::UnknownMove { move_idx }),
+        };
+        self.check_cast(the_move)?;
+
+        Ok(the_move)
     }
 
-    fn has_gone(&self) -> bool {
-        self.has_gone.clone()
+    /// Gets the magic moves this `Enemy` can cast.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
     }
 
-    fn health(&self) -> u32 {
-        self.health.clone()
+    /// The `Enemy` chooses what to do this turn, based on its AI policy.
+    ///
+    /// # Params
+    /// - `target` - The `Entity` this `Enemy` is fighting.
+    /// - `ai_registry` - The policies that were plugged in, for `AiPolicy::Custom`.
+    /// - `rng` - The random number generator of the game.
+    ///
+    /// # Returns
+    /// - The `Action` the `Enemy` chose.
+    pub fn get_turn_type(
+        &self,
+        target: &dyn Entity,
+        ai_registry: &AiRegistry,
+        rng: &mut GameRng,
+    ) -> Action {
+        ai_registry
+            .policy_for(&self.ai)
+            .choose_action(self, target, rng)
     }
+}
 
-    fn max_health(&self) -> u32 {
-        self.max_health.clone()
+//entity implementation for enemy
+impl Entity for Enemy {
+    fn core(&self) -> &CombatantCore {
+        &self.core
     }
 
-    fn allow_move(&mut self) {
-        // we can move again
-        self.has_gone = false;
+    fn core_mut(&mut self) -> &mut CombatantCore {
+        &mut self.core
     }
 }
@@ -1,12 +1,26 @@
-use std::collections::VecDeque;
-
 ///Represents the type of move that an entity is making
-use crate::entity_components::moves::MoveType;
+use crate::entity_components::moves::{ElementType, Move, MoveEffect, MoveTarget};
 
-use super::status::Status;
+use super::affinity::Affinity;
+use super::combatant::CombatantCore;
+use super::resource::Resource;
+use super::stats::{DefendStance, ModifierSource, StatModifier, Stats};
+use super::status::{Status, StatusAdded};
+use crate::battle::event::BattleEvent;
+use crate::battle::outcome::{ActionError, ActionOutcome};
+use crate::rng::GameRng;
 
 ///trait for entities
+///
+///Most of the behaviour is shared through the entity's `CombatantCore`, so
+///implementors only have to give their core and decide how they act.
 pub trait Entity {
+    /// Get the state this entity shares with every other entity in a fight.
+    fn core(&self) -> &CombatantCore;
+
+    /// Get the shared state of this entity to change it.
+    fn core_mut(&mut self) -> &mut CombatantCore;
+
     ///Entity takes damage
     ///
     /// # Params
@@ -14,68 +28,146 @@ pub trait Entity {
     ///
     /// # Returns
     /// - The amount of damage the entity actually took from the attack, such as when an entity takes less damage due to defense.
-    fn take_damage(&mut self, amount: u32) -> u32;
+    fn take_damage(&mut self, amount: u32) -> u32 {
+        let damage_taken = self.stats().calc_damage_taken(amount);
+        // the shield soaks up what it can first
+        self.core_mut().health_mut().lose(damage_taken)
+    }
 
     ///Entity heals
     ///
     /// # Params
     /// - `amount` - The amount to heal.
-    fn heal(&mut self, amount: u32);
+    ///
+    /// # Returns
+    /// - The amount the entity actually healed, which never takes it past its max health.
+    fn heal(&mut self, amount: u32) -> u32 {
+        self.core_mut().health_mut().restore(amount)
+    }
 
     ///Uses mana
     ///
     /// # Params
     /// - `amount` - The amount to use.
-    fn use_mana(&mut self, amount: u32);
+    fn use_mana(&mut self, amount: u32) {
+        self.core_mut().mana_mut().spend(amount);
+    }
+
+    /// Entity gets mana back, up to its max mana.
+    ///
+    /// # Params
+    /// - `amount` - The amount of mana to give back.
+    ///
+    /// # Returns
+    /// - The amount of mana the entity actually got back.
+    fn restore_mana(&mut self, amount: u32) -> u32 {
+        self.core_mut().mana_mut().restore(amount)
+    }
+
+    /// Get the health of the entity, along with its max and its shield.
+    fn health_bar(&self) -> &Resource {
+        self.core().health()
+    }
+
+    /// Get the mana of the entity, along with its max.
+    fn mana_bar(&self) -> &Resource {
+        self.core().mana()
+    }
+
+    /// Get the health the entity has left.
+    fn health(&self) -> u32 {
+        self.health_bar().current()
+    }
+
+    /// Get the most health the entity can have.
+    fn max_health(&self) -> u32 {
+        self.health_bar().max()
+    }
+
+    /// Get the mana the entity has left.
+    fn mana(&self) -> u32 {
+        self.mana_bar().current()
+    }
+
+    /// Get the most mana the entity can have.
+    fn max_mana(&self) -> u32 {
+        self.mana_bar().max()
+    }
+
+    /// Get the shield of the entity, which soaks up damage before its health.
+    fn shield(&self) -> u32 {
+        self.health_bar().shield()
+    }
+
+    /// Gives the entity more shield.
+    ///
+    /// # Params
+    /// - `amount` - The amount of shield to add.
+    fn add_shield(&mut self, amount: u32) {
+        self.core_mut().health_mut().add_shield(amount);
+    }
 
     ///Get the speed of the entity
     ///
     /// # Returns
     /// - The speed of this entity.
-    fn speed(&self) -> u32;
+    fn speed(&self) -> u32 {
+        self.stats().get_speed()
+    }
 
     /// Get the level of the entity.
     ///
     /// # Returns
     /// - The level of this entity.
-    fn level(&self) -> u32;
+    fn level(&self) -> u32 {
+        self.core().level()
+    }
 
     /// Get the name of the entity.
     ///
     /// # Returns
     /// - The name of this entity.
-    fn name(&self) -> String;
+    fn name(&self) -> String {
+        String::from(self.core().name())
+    }
 
     /// Get the magic strength of the entity.
     ///
     /// # Returns
     /// - The magic strength of this entity.
-    fn magic_strength(&self) -> u32;
+    fn magic_strength(&self) -> u32 {
+        self.stats().get_magic_strength()
+    }
 
-    /// Checks to see if this entity is dead.
+    /// Get the affinity of the entity to an element.
     ///
-    /// # Returns
-    /// - `true` if the entity is dead, `false` otherwise.
-    fn is_dead(&self) -> bool;
-
-    /// Checks to see if the entity has gone this turn.
-    /// For use when there are more than 2 entities in a fight.
+    /// # Params
+    /// - `element` - The element of the move hitting this entity.
     ///
     /// # Returns
-    /// - `true` if the `Entity` has gone this turn, `false` otherwise.
-    fn gone_this_turn(&self) -> bool;
+    /// - How this entity reacts to `element`.
+    fn affinity(&self, element: ElementType) -> Affinity {
+        self.core().affinities().get(element)
+    }
 
-    /// Makes this Entity do its turn and make a choice.
+    /// Checks to see if this entity is dead.
     ///
     /// # Returns
-    /// - The `MoveType` selected by the `Entity`.
-    fn get_turn_type(&mut self) -> Option<MoveType>;
+    /// - `true` if the entity is dead, `false` otherwise.
+    fn is_dead(&self) -> bool {
+        self.health_bar().is_empty()
+    }
 
     /// Generate random attack damage from the Entity's stats
     ///
+    /// # Params
+    /// - `rng` - The random number generator of the game.
+    ///
     /// # Returns
     /// - A random attack damage number.
-    fn get_random_attack_dmg(&self) -> u32;
+    fn get_random_attack_dmg(&self, rng: &mut GameRng) -> u32 {
+        self.stats().generate_random_attack_dmg(rng)
+    }
 
     /// Makes this `Entity` attack another `Entity`.
     ///
@@ -90,35 +182,961 @@ pub trait Entity {
         entity.take_damage(amount)
     }
 
-    /// Entity starts defending.
-    fn start_defending(&mut self);
+    /// Makes this `Entity` hit another `Entity` with a physical attack.
+    ///
+    /// The attack goes through the damage pipeline, where each stage can change it: the
+    /// attacker's accuracy against the target's evasion decides if it hits, then it rolls
+    /// for a critical hit, and then the target's defense lowers the damage it takes.
+    ///
+    /// # Params
+    /// - `target` - The `Entity` being attacked.
+    /// - `rng` - The random number generator of the game.
+    /// - `events` - The events of the battle, to push each stage of the attack.
+    ///
+    /// # Returns
+    /// - The damage dealt, or `None` if the attack missed.
+    fn strike(
+        &mut self,
+        target: &mut dyn Entity,
+        rng: &mut GameRng,
+        events: &mut Vec<BattleEvent>,
+    ) -> Option<u32> {
+        if !self.stats().roll_hit(target.stats(), rng) {
+            events.push(BattleEvent::AttackMissed {
+                attacker: self.name(),
+                target: target.name(),
+            });
+            return None;
+        }
+
+        let mut damage = self.get_random_attack_dmg(rng);
+        if self.stats().roll_crit(rng) {
+            damage = self.stats().calc_crit_damage(damage);
+            events.push(BattleEvent::CriticalHit {
+                attacker: self.name(),
+                target: target.name(),
+            });
+        }
+
+        // the target's defense is applied as it takes the damage
+        let damage_dealt = self.attack_entity(damage, target);
+        events.push(BattleEvent::DamageDealt {
+            attacker: self.name(),
+            target: target.name(),
+            amount: damage_dealt,
+        });
+        self.trigger_on_hit(target, damage_dealt, events);
+
+        // a parrying target can strike straight back, but a counter can't be countered
+        if !target.is_dead() && target.stats().roll_parry(rng) {
+            let counter_damage = target.get_random_attack_dmg(rng);
+            let amount = self.take_damage(counter_damage);
+            events.push(BattleEvent::Countered {
+                attacker: target.name(),
+                target: self.name(),
+                amount,
+            });
+        }
+
+        Some(damage_dealt)
+    }
+
+    /// Makes this `Entity` cast a magic move.
+    ///
+    /// A move aimed at the caster only affects the caster, while any other move
+    /// affects each of its targets. See `MoveEffect` for what each move does.
+    ///
+    /// # Params
+    /// - `the_move` - The `Move` being cast.
+    /// - `amount` - The amount of damage, healing or shield rolled for the move.
+    /// - `targets` - The opponents the move is aimed at (ignored when it is aimed at the caster).
+    /// - `rng` - The random number generator of the game.
+    /// - `events` - The events of the battle, to push what the move did.
+    fn cast_move(
+        &mut self,
+        the_move: &Move,
+        amount: u32,
+        targets: Vec<&mut dyn Entity>,
+        rng: &mut GameRng,
+        events: &mut Vec<BattleEvent>,
+    ) where
+        Self: Sized,
+    {
+        events.push(BattleEvent::MagicCast {
+            caster: self.name(),
+            move_name: the_move.name(),
+        });
+
+        if the_move.target() == MoveTarget::User {
+            let (caster, magic_strength) = (self.name(), self.magic_strength());
+            support_with_move(the_move, amount, &caster, magic_strength, self, events);
+        } else {
+            for target in targets {
+                self.hit_with_move(the_move, amount, target, rng, events);
+            }
+        }
+    }
+
+    /// Makes this `Entity` hit another `Entity` with a magic move.
+    ///
+    /// The target's affinity to the move's element changes how much a damaging
+    /// move hurts, and the move can apply its status as long as it hurt the target.
+    /// Moves that don't hurt are given to the target instead.
+    ///
+    /// # Params
+    /// - `the_move` - The `Move` being cast.
+    /// - `amount` - The amount of damage rolled for the move.
+    /// - `target` - The `Entity` being hit.
+    /// - `rng` - The random number generator of the game.
+    /// - `events` - The events of the battle, to push what the move did.
+    fn hit_with_move(
+        &mut self,
+        the_move: &Move,
+        amount: u32,
+        target: &mut dyn Entity,
+        rng: &mut GameRng,
+        events: &mut Vec<BattleEvent>,
+    ) {
+        let can_apply_status = match the_move.effect() {
+            MoveEffect::Damage | MoveEffect::DrainHealth => {
+                let (affinity, damage_dealt) =
+                    self.hit_with_element(amount, the_move.element(), target, events);
+
+                if the_move.effect() == MoveEffect::DrainHealth && damage_dealt > 0 {
+                    let healed = self.heal(damage_dealt);
+                    events.push(BattleEvent::Healed {
+                        target: self.name(),
+                        amount: healed,
+                    });
+                }
+
+                // the status can only be applied as long as the move hurt the target
+                !matches!(affinity, Affinity::Immune | Affinity::Absorb)
+            }
+
+            MoveEffect::DrainMana => {
+                let drained = amount.min(target.mana());
+                target.use_mana(drained);
+                self.restore_mana(drained);
+                events.push(BattleEvent::ManaDrained {
+                    caster: self.name(),
+                    target: target.name(),
+                    amount: drained,
+                });
+                true
+            }
+
+            MoveEffect::HealSelf | MoveEffect::Buff | MoveEffect::Cleanse | MoveEffect::Shield => {
+                let (caster, magic_strength) = (self.name(), self.magic_strength());
+                support_with_move(the_move, amount, &caster, magic_strength, target, events);
+                return;
+            }
+        };
+
+        // roll for random chance to apply status if it exists
+        if can_apply_status && the_move.roll_status_chance(rng) {
+            let status = the_move.get_status().unwrap();
+            if target.stats().stance() == Some(DefendStance::Guard) {
+                events.push(BattleEvent::StatusBlocked {
+                    status: status.name(),
+                    target: target.name(),
+                });
+            } else {
+                target.apply_status(&status.cast_by(self.name(), self.magic_strength()), events);
+            }
+        }
+
+        // the move's buffs or debuffs land whenever its status could
+        if can_apply_status && !the_move.modifiers().is_empty() {
+            target.push_modifiers(
+                ModifierSource::Move(the_move.name()),
+                the_move.modifiers(),
+                Some(the_move.modifier_turns()),
+                events,
+            );
+        }
+    }
+
+    /// Makes this `Entity` hit another `Entity` with elemental damage.
+    ///
+    /// # Params
+    /// - `amount` - The amount of damage before the target's affinity is applied.
+    /// - `element` - The element of the damage.
+    /// - `target` - The `Entity` being hit.
+    /// - `events` - The events of the battle, to push what the hit did.
+    ///
+    /// # Returns
+    /// - The affinity of the target to the element, and the damage dealt (0 if it absorbed the hit).
+    fn hit_with_element(
+        &self,
+        amount: u32,
+        element: ElementType,
+        target: &mut dyn Entity,
+        events: &mut Vec<BattleEvent>,
+    ) -> (Affinity, u32) {
+        // the target's affinity to the element changes how much the hit hurts
+        let affinity = target.affinity(element);
+        let scaled_amount = affinity.scale(amount);
+
+        let damage_dealt = if affinity == Affinity::Absorb {
+            let healed = target.heal(scaled_amount);
+            events.push(BattleEvent::Healed {
+                target: target.name(),
+                amount: healed,
+            });
+            0
+        } else {
+            let damage_dealt = self.attack_entity(scaled_amount, target);
+            // report the damage that was dealt
+            events.push(BattleEvent::DamageDealt {
+                attacker: self.name(),
+                target: target.name(),
+                amount: damage_dealt,
+            });
+            damage_dealt
+        };
+
+        if affinity != Affinity::Normal {
+            events.push(BattleEvent::ElementalHit {
+                target: target.name(),
+                element,
+                affinity,
+            });
+        }
+
+        (affinity, damage_dealt)
+    }
+
+    /// Get the stats of the entity, with their modifiers.
+    fn stats(&self) -> &Stats {
+        self.core().stats()
+    }
+
+    /// Get the stats of the entity to change them, such as to push a modifier.
+    fn stats_mut(&mut self) -> &mut Stats {
+        self.core_mut().stats_mut()
+    }
+
+    /// Recalculates the max health and mana after the stats change, making
+    /// sure the current health and mana are not above them.
+    fn update_max_resources(&mut self) {
+        let max_health = self.stats().calculate_max_health();
+        let max_mana = self.stats().calculate_max_mana();
+
+        self.core_mut().health_mut().set_max(max_health);
+        self.core_mut().mana_mut().set_max(max_mana);
+    }
+
+    /// Get the statuses applied to the entity.
+    fn statuses(&self) -> &[Status] {
+        self.core().statuses()
+    }
+
+    /// Checks if a status is making this entity skip its turn.
+    ///
+    /// # Returns
+    /// - The name of the status, or `None` if the entity can act.
+    fn stunned_by(&self) -> Option<String> {
+        self.statuses()
+            .iter()
+            .find(|status| status.skips_turn())
+            .map(|status| status.name())
+    }
+
+    /// Checks if a status is stopping this entity from casting magic.
+    fn is_silenced(&self) -> bool {
+        self.statuses().iter().any(|status| status.blocks_magic())
+    }
+
+    /// Get the focus the entity has built up for special moves. Only the player builds focus.
+    fn focus(&self) -> u32 {
+        0
+    }
+
+    /// Checks if the entity can cast a move right now.
+    ///
+    /// # Params
+    /// - `the_move` - The `Move` to cast.
+    ///
+    /// # Returns
+    /// - `Ok` if the move can be cast, or why it can't be.
+    fn check_cast(&self, the_move: &Move) -> Result<(), ActionError> {
+        if self.is_silenced() {
+            return Err(ActionError::Silenced);
+        }
+        // content is checked when it is loaded, but moves can also be built in code
+        if !the_move.effect().can_target(the_move.target()) {
+            return Err(ActionError::InvalidMoveTarget {
+                target: the_move.target(),
+            });
+        }
+        if self.mana() < the_move.cost() {
+            return Err(ActionError::NotEnoughMana {
+                need: the_move.cost(),
+                have: self.mana(),
+            });
+        }
+        if self.focus() < the_move.focus_cost() {
+            return Err(ActionError::NotEnoughFocus {
+                need: the_move.focus_cost(),
+                have: self.focus(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Triggers the statuses of another entity that react to this entity hitting it,
+    /// such as thorns.
+    ///
+    /// # Params
+    /// - `target` - The `Entity` that was hit.
+    /// - `damage_dealt` - The damage the hit did to the target.
+    /// - `events` - The events of the battle, to push what the statuses did.
+    fn trigger_on_hit(
+        &mut self,
+        target: &dyn Entity,
+        damage_dealt: u32,
+        events: &mut Vec<BattleEvent>,
+    ) {
+        for status in target.statuses() {
+            let thorns_damage = status.thorns_damage(damage_dealt);
+            if thorns_damage == 0 {
+                continue;
+            }
+
+            let amount = self.take_damage(thorns_damage);
+            events.push(BattleEvent::StatusTriggered {
+                status: status.name(),
+                owner: target.name(),
+                target: self.name(),
+                amount,
+            });
+        }
+    }
+
+    /// Entity starts defending in a stance, until the end of its next turn.
+    ///
+    /// # Params
+    /// - `stance` - How the entity defends.
+    fn start_defending(&mut self, stance: DefendStance) {
+        self.stats_mut().start_defending(stance);
+    }
+
+    /// Entity defends in a stance, which uses up its turn.
+    ///
+    /// # Params
+    /// - `stance` - How the entity defends.
+    /// - `events` - The events of the battle, to push that the entity is defending.
+    ///
+    /// # Returns
+    /// - The stance the entity is defending in.
+    fn defend_move(
+        &mut self,
+        stance: DefendStance,
+        events: &mut Vec<BattleEvent>,
+    ) -> Result<ActionOutcome, ActionError> {
+        self.start_defending(stance);
+
+        events.push(BattleEvent::Defending {
+            name: self.name(),
+            stance,
+            num_turns: 1,
+        });
+
+        Ok(ActionOutcome::Defended { stance })
+    }
+
+    /// Does what the entity's defending stance does at the end of its turn.
+    ///
+    /// # Params
+    /// - `events` - The events of the battle, to push what the stance did.
+    fn tick_stance(&mut self, events: &mut Vec<BattleEvent>) {
+        if self.stats().stance() == Some(DefendStance::Brace) {
+            let amount = self.restore_mana(self.stats().calc_brace_mana());
+            if amount > 0 {
+                events.push(BattleEvent::ManaRestored {
+                    target: self.name(),
+                    amount,
+                });
+            }
+        }
+    }
+
+    /// Regains the entity's mana for the end of its turn.
+    ///
+    /// # Params
+    /// - `events` - The events of the battle, to push the mana regained.
+    fn regen_mana(&mut self, events: &mut Vec<BattleEvent>) {
+        let amount = self.restore_mana(self.stats().calc_mana_regen());
+        if amount > 0 {
+            events.push(BattleEvent::ManaRegenerated {
+                target: self.name(),
+                amount,
+            });
+        }
+    }
+
+    /// Pushes modifiers onto the stats of this entity, replacing any that are already
+    /// there from the same source (so casting the same spell again refreshes it).
+    ///
+    /// # Params
+    /// - `source` - Where the modifiers came from.
+    /// - `modifiers` - The changes to the stats.
+    /// - `turns` - How many of this entity's turns the modifiers last, or `None`
+    ///   if they last until they are removed.
+    /// - `events` - The events of the battle, to push the changed stats.
+    fn push_modifiers(
+        &mut self,
+        source: ModifierSource,
+        modifiers: &[StatModifier],
+        turns: Option<u32>,
+        events: &mut Vec<BattleEvent>,
+    ) {
+        self.stats_mut().remove_modifiers(&source);
+
+        for modifier in modifiers {
+            self.stats_mut()
+                .push_modifier(source.clone(), modifier.clone(), turns);
+            events.push(BattleEvent::StatModified {
+                target: self.name(),
+                stat: modifier.stat(),
+                is_raised: modifier.amount() >= 0,
+            });
+        }
+
+        self.update_max_resources();
+    }
 
-    /// Entity stops defending.
-    fn stop_defending(&mut self);
+    /// Removes the modifiers from a source, such as when a status is cured.
+    ///
+    /// # Params
+    /// - `source` - The source of the modifiers to remove.
+    fn remove_modifiers(&mut self, source: &ModifierSource) {
+        self.stats_mut().remove_modifiers(source);
+        self.update_max_resources();
+    }
+
+    /// Removes a status from this entity, along with its modifiers.
+    ///
+    /// # Params
+    /// - `status` - The name of the status to remove.
+    ///
+    /// # Returns
+    /// - `true` if the entity had the status, `false` otherwise.
+    fn cure_status(&mut self, status: &str) -> bool {
+        let statuses = self.core_mut().statuses_mut();
+        let had_status = statuses.iter().any(|applied| applied.name() == status);
+        statuses.retain(|applied| applied.name() != status);
+
+        self.remove_modifiers(&ModifierSource::Status(String::from(status)));
+        had_status
+    }
+
+    /// Removes every harmful status from this entity, keeping the ones that help it.
+    ///
+    /// # Params
+    /// - `events` - The events of the battle, to push each status that was cured.
+    fn cleanse(&mut self, events: &mut Vec<BattleEvent>) {
+        let names: Vec<String> = self
+            .statuses()
+            .iter()
+            .filter(|status| status.is_harmful())
+            .map(|status| status.name())
+            .collect();
+        for status in names {
+            if self.cure_status(&status) {
+                events.push(BattleEvent::StatusCured {
+                    status,
+                    target: self.name(),
+                });
+            }
+        }
+    }
+
+    /// Counts down the modifiers on this entity at the end of its turn.
+    ///
+    /// # Params
+    /// - `events` - The events of the battle, to push the modifiers that ran out.
+    fn tick_modifiers(&mut self, events: &mut Vec<BattleEvent>) {
+        let expired = self.stats_mut().tick_modifiers();
+
+        if !expired.is_empty() {
+            self.update_max_resources();
+        }
+        for source in expired {
+            events.push(BattleEvent::ModifierExpired {
+                source,
+                target: self.name(),
+            });
+        }
+    }
 
     /// Ticks all statuses in vector
-    fn tick_statuses(&mut self, text_vec: &mut VecDeque<String>);
+    ///
+    /// # Params
+    /// - `rng` - The random number generator of the game.
+    /// - `events` - The events of the battle, to push what the statuses did.
+    fn tick_statuses(&mut self, rng: &mut GameRng, events: &mut Vec<BattleEvent>) {
+        let mut expired = Vec::new();
+
+        for i in 0..self.statuses().len() {
+            let status = &mut self.core_mut().statuses_mut()[i];
+            let amount = status.calculate_amount(rng);
+            let (name, source) = (status.name(), status.source());
+            let is_healing = status.is_healing();
+            let has_amount = status.has_amount();
+
+            // remember this status for removal if it has no turns left
+            if status.tick() {
+                expired.push(name.clone());
+            }
+
+            // some statuses only restrict the entity, without damaging or healing it
+            if !has_amount {
+                continue;
+            }
+
+            // apply the status effect and report what it did
+            let amount = if is_healing {
+                self.heal(amount)
+            } else {
+                self.take_damage(amount)
+            };
+            // healing at full health does nothing worth reporting
+            if is_healing && amount == 0 {
+                continue;
+            }
+            events.push(BattleEvent::StatusTicked {
+                status: name,
+                source,
+                target: self.name(),
+                amount,
+                is_healing,
+            });
+        }
+
+        // the statuses are only removed once the loop is done with them
+        for status in expired {
+            self.core_mut()
+                .statuses_mut()
+                .retain(|applied| applied.name() != status);
+            self.remove_modifiers(&ModifierSource::Status(status.clone()));
+            events.push(BattleEvent::StatusExpired {
+                status,
+                target: self.name(),
+            });
+        }
+    }
 
     /// Applies a status to this Entity.
     ///
     /// # Params
     /// - `status` The `Status` to apply to this `Entity`.
-    fn apply_status(&mut self, status: &Status, text_vec: &mut VecDeque<String>);
+    /// - `events` - The events of the battle, to push the applied status.
+    fn apply_status(&mut self, status: &Status, events: &mut Vec<BattleEvent>) {
+        match status.add_to(self.core_mut().statuses_mut()) {
+            StatusAdded::Applied => {
+                events.push(BattleEvent::StatusApplied {
+                    status: status.name(),
+                    target: self.name(),
+                });
+                self.push_modifiers(
+                    ModifierSource::Status(status.name()),
+                    status.modifiers(),
+                    None,
+                    events,
+                );
+            }
+            StatusAdded::Refreshed => events.push(BattleEvent::StatusApplied {
+                status: status.name(),
+                target: self.name(),
+            }),
+            StatusAdded::Stacked(stacks) => events.push(BattleEvent::StatusStacked {
+                status: status.name(),
+                target: self.name(),
+                stacks,
+            }),
+            // the entity already has the status, and it doesn't build up
+            StatusAdded::Ignored => {}
+        }
+    }
 
     /// Entity does a physical attack against another `Entity`.
     ///
     /// # Params
     /// - `target` - The target `Entity` receiving the attack.
+    /// - `rng` - The random number generator of the game.
+    /// - `events` - The events of the battle, to push the damage dealt.
     ///
     /// # Returns
-    /// - `true` if the move was done, `false` if the move was not done (in case the move was canceled).
-    fn attack_move(&mut self, target: &mut dyn Entity, text_vec: &mut VecDeque<String>) -> bool;
+    /// - The damage the attack dealt, or why it couldn't be done.
+    fn attack_move(
+        &mut self,
+        target: &mut dyn Entity,
+        rng: &mut GameRng,
+        events: &mut Vec<BattleEvent>,
+    ) -> Result<ActionOutcome, ActionError> {
+        // a miss still uses up the turn
+        let damage = self.strike(target, rng, events);
+
+        Ok(ActionOutcome::Attacked { damage })
+    }
+}
+
+/// Gives the effect of a move that helps (or hinders without hurting) to an entity.
+/// These moves always apply their status and modifiers.
+///
+/// # Params
+/// - `the_move` - The `Move` being cast.
+/// - `amount` - The amount of healing or shield rolled for the move.
+/// - `caster` - The name of the entity casting the move.
+/// - `magic_strength` - The magic strength of the caster, for the status.
+/// - `recipient` - The `Entity` the move is given to.
+/// - `events` - The events of the battle, to push what the move did.
+fn support_with_move(
+    the_move: &Move,
+    amount: u32,
+    caster: &str,
+    magic_strength: u32,
+    recipient: &mut dyn Entity,
+    events: &mut Vec<BattleEvent>,
+) {
+    match the_move.effect() {
+        MoveEffect::HealSelf => {
+            let healed = recipient.heal(amount);
+            events.push(BattleEvent::Healed {
+                target: recipient.name(),
+                amount: healed,
+            });
+        }
+        MoveEffect::Cleanse => recipient.cleanse(events),
+        MoveEffect::Shield => {
+            recipient.add_shield(amount);
+            events.push(BattleEvent::Shielded {
+                target: recipient.name(),
+                amount,
+            });
+        }
+        MoveEffect::Buff => {} // only the status and modifiers
+        // hostile moves are handled by `Entity::hit_with_move`
+        MoveEffect::Damage | MoveEffect::DrainHealth | MoveEffect::DrainMana => {}
+    }
 
-    /// Returns if the entity has gone this turn yet.
-    fn has_gone(&self) -> bool;
+    if let Some(status) = the_move.get_status() {
+        recipient.apply_status(
+            &status.cast_by(String::from(caster), magic_strength),
+            events,
+        );
+    }
+
+    if !the_move.modifiers().is_empty() {
+        recipient.push_modifiers(
+            ModifierSource::Move(the_move.name()),
+            the_move.modifiers(),
+            Some(the_move.modifier_turns()),
+            events,
+        );
+    }
+}
 
-    fn health(&self) -> u32;
-    fn max_health(&self) -> u32;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity_components::enemy::Enemy;
+    use crate::entity_components::player::Player;
+    use crate::entity_components::stats::{ModifierKind, StatType};
+    use crate::rng::create_rng;
 
-    fn allow_move(&mut self);
+    fn regen() -> Status {
+        Status::new(String::from("Regen"), 6, true, 0, 4)
+    }
+
+    /// Ticks the statuses of an entity once, returning the events.
+    fn tick(entity: &mut dyn Entity) -> Vec<BattleEvent> {
+        let mut events = Vec::new();
+        entity.tick_statuses(&mut create_rng(1), &mut events);
+        events
+    }
+
+    #[test]
+    fn regen_at_full_health_is_not_reported() {
+        let mut player = Player::default();
+        player.apply_status(&regen(), &mut Vec::new());
+
+        let events = tick(&mut player);
+
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, BattleEvent::StatusTicked { .. })));
+        assert_eq!(player.statuses()[0].turns_left(), 3);
+    }
+
+    #[test]
+    fn regen_when_hurt_is_reported() {
+        let mut player = Player::default();
+        player.take_damage(20);
+        player.apply_status(&regen(), &mut Vec::new());
+
+        let events = tick(&mut player);
+
+        assert!(events.iter().any(|event| matches!(
+            event,
+            BattleEvent::StatusTicked {
+                is_healing: true,
+                amount,
+                ..
+            } if *amount > 0
+        )));
+    }
+
+    /// Creates a move with an effect, aimed at a target.
+    fn spell(effect: MoveEffect, target: MoveTarget) -> Move {
+        Move::default().with_effect(effect, target)
+    }
+
+    fn dummy() -> Enemy {
+        Enemy::new(String::from("Dummy"), Stats::new(10, 10, 1, 0, 0, 0), 1)
+    }
+
+    #[test]
+    fn drain_heals_the_caster_by_the_damage_dealt() {
+        let mut player = Player::default();
+        player.take_damage(30);
+        let health = player.health();
+        let mut enemy = dummy();
+
+        player.hit_with_move(
+            &spell(MoveEffect::DrainHealth, MoveTarget::SingleEnemy),
+            10,
+            &mut enemy,
+            &mut create_rng(1),
+            &mut Vec::new(),
+        );
+
+        assert_eq!(enemy.health(), enemy.max_health() - 10);
+        assert_eq!(player.health(), health + 10);
+    }
+
+    #[test]
+    fn heal_stops_at_max_health() {
+        let mut player = Player::default();
+        player.take_damage(5);
+        let missing = player.max_health() - player.health();
+        let mut events = Vec::new();
+
+        let heal = spell(MoveEffect::HealSelf, MoveTarget::User);
+        support_with_move(&heal, 1000, "Louie", 0, &mut player, &mut events);
+
+        assert!(player.health_bar().is_full());
+        assert_eq!(
+            events,
+            [BattleEvent::Healed {
+                target: String::from("Louie"),
+                amount: missing,
+            }]
+        );
+    }
+
+    #[test]
+    fn shield_goes_on_top_of_health() {
+        let mut player = Player::default();
+
+        let shield = spell(MoveEffect::Shield, MoveTarget::User);
+        support_with_move(&shield, 12, "Louie", 0, &mut player, &mut Vec::new());
+
+        assert_eq!(player.health_bar().shield(), 12);
+        assert!(player.health_bar().is_full());
+
+        player.take_damage(5);
+        assert_eq!(player.shield(), 7);
+        assert!(player.health_bar().is_full());
+    }
+
+    #[test]
+    fn cleanse_keeps_helpful_statuses() {
+        let weaken = Status::new(String::from("Weaken"), 0, false, 0, 3).with_modifiers(vec![
+            StatModifier::new(StatType::Strength, ModifierKind::Percent, -25),
+        ]);
+        let poison = Status::new(String::from("Poison"), 4, false, 0, 4);
+        let mut player = Player::default();
+        for status in [weaken, poison, regen()] {
+            player.apply_status(&status, &mut Vec::new());
+        }
+        let mut events = Vec::new();
+
+        let cleanse = spell(MoveEffect::Cleanse, MoveTarget::User);
+        support_with_move(&cleanse, 0, "Louie", 0, &mut player, &mut events);
+
+        let left: Vec<String> = player
+            .statuses()
+            .iter()
+            .map(|status| status.name())
+            .collect();
+        assert_eq!(left, ["Regen"]);
+        assert_eq!(
+            events
+                .iter()
+                .filter(|event| matches!(event, BattleEvent::StatusCured { .. }))
+                .count(),
+            2
+        );
+        assert!(player.stats().modifiers().is_empty());
+    }
+
+    /// Creates an attacker whose plain hits always do 1 damage, with extra modifiers on its stats.
+    fn striker(modifiers: &[(StatType, i32)]) -> Player {
+        let mut striker = Player::new(
+            String::from("Striker"),
+            Stats::new(10, 10, 10, 1, 0, 0),
+            1,
+            0,
+        );
+        for (stat, amount) in modifiers {
+            striker.stats_mut().push_modifier(
+                ModifierSource::Move(String::from("Training")),
+                StatModifier::new(*stat, ModifierKind::Flat, *amount),
+                None,
+            );
+        }
+        striker
+    }
+
+    /// Creates a target with plenty of health and some defense.
+    fn wall(defense: u32) -> Enemy {
+        Enemy::new(String::from("Wall"), Stats::new(40, 0, 1, 0, 0, defense), 1)
+    }
+
+    #[test]
+    fn evasive_target_is_rarely_hit() {
+        let mut striker = striker(&[]);
+        let mut target = wall(0);
+        target.stats_mut().push_modifier(
+            ModifierSource::Move(String::from("Blur")),
+            StatModifier::new(StatType::Evasion, ModifierKind::Flat, 95),
+            None,
+        );
+        let mut rng = create_rng(1);
+        let mut events = Vec::new();
+
+        assert_eq!(striker.strike(&mut target, &mut rng, &mut events), None);
+        assert_eq!(
+            events,
+            [BattleEvent::AttackMissed {
+                attacker: String::from("Striker"),
+                target: String::from("Wall"),
+            }]
+        );
+
+        // even 100 evasion leaves the smallest chance to be hit
+        let hits = (0..100)
+            .filter_map(|_| striker.strike(&mut target, &mut rng, &mut Vec::new()))
+            .count();
+        assert!(hits < 25, "{} hits got through", hits);
+    }
+
+    #[test]
+    fn sure_crit_deals_crit_damage() {
+        // 100 accuracy and crit chance, and crits doing 100 times the damage
+        let mut striker = striker(&[
+            (StatType::Accuracy, 100),
+            (StatType::CritChance, 100),
+            (StatType::CritDamage, 9850),
+        ]);
+        let mut target = wall(0);
+        let mut events = Vec::new();
+
+        let damage = striker.strike(&mut target, &mut create_rng(1), &mut events);
+
+        assert_eq!(damage, Some(striker.stats().calc_crit_damage(1)));
+        assert_eq!(damage, Some(100));
+        assert_eq!(target.health(), target.max_health() - 100);
+        assert!(events.contains(&BattleEvent::CriticalHit {
+            attacker: String::from("Striker"),
+            target: String::from("Wall"),
+        }));
+    }
+
+    #[test]
+    fn defense_is_applied_after_the_crit() {
+        let mut striker = striker(&[
+            (StatType::Accuracy, 100),
+            (StatType::CritChance, 100),
+            (StatType::CritDamage, 9850),
+        ]);
+        let mut target = wall(50);
+
+        let damage = striker.strike(&mut target, &mut create_rng(1), &mut Vec::new());
+
+        // halving the 1 damage first would have left nothing for the crit to multiply
+        assert_eq!(damage, Some(50));
+    }
+
+    /// Casts a curse that always burns from a caster with some magic strength,
+    /// returning the first tick of the burn.
+    fn burn_tick(caster_name: &str, magic_strength: u32) -> BattleEvent {
+        let burn = Status::new(String::from("Burn"), 4, false, 0, 3);
+        let curse =
+            spell(MoveEffect::Buff, MoveTarget::SingleEnemy).with_applied_status(Some(burn));
+        let mut caster = Player::new(
+            String::from(caster_name),
+            Stats::new(10, 10, 10, 1, magic_strength, 0),
+            1,
+            0,
+        );
+        let mut enemy = dummy();
+
+        caster.cast_move(
+            &curse,
+            0,
+            vec![&mut enemy],
+            &mut create_rng(1),
+            &mut Vec::new(),
+        );
+
+        tick(&mut enemy).remove(0)
+    }
+
+    #[test]
+    fn status_scales_with_its_caster() {
+        let weak_tick = burn_tick("Apprentice", 0);
+        let strong_tick = burn_tick("Archmage", 40);
+
+        let amount = |event: &BattleEvent| match event {
+            BattleEvent::StatusTicked { amount, .. } => *amount,
+            _ => panic!("the burn didn't tick: {:?}", event),
+        };
+        assert!(amount(&strong_tick) > amount(&weak_tick));
+        assert!(matches!(
+            weak_tick,
+            BattleEvent::StatusTicked { source: Some(ref source), .. } if source == "Apprentice"
+        ));
+        assert!(matches!(
+            strong_tick,
+            BattleEvent::StatusTicked { source: Some(ref source), .. } if source == "Archmage"
+        ));
+    }
+
+    #[test]
+    fn move_aimed_at_the_wrong_target_cant_be_cast() {
+        let enemy = dummy().with_moves(vec![spell(MoveEffect::HealSelf, MoveTarget::SingleEnemy)]);
+        let player = Player::default();
+
+        assert_eq!(
+            enemy.check_magic(0).err(),
+            Some(ActionError::InvalidMoveTarget {
+                target: MoveTarget::SingleEnemy,
+            })
+        );
+        assert_eq!(
+            player.check_cast(&spell(MoveEffect::Damage, MoveTarget::User)),
+            Err(ActionError::InvalidMoveTarget {
+                target: MoveTarget::User,
+            })
+        );
+        assert_eq!(
+            player.check_cast(&spell(MoveEffect::Shield, MoveTarget::User)),
+            Ok(())
+        );
+    }
 }
@@ -0,0 +1,230 @@
+// file for the gear an entity can wear and the slots it goes into
+use serde::{Deserialize, Serialize};
+
+use crate::entity_components::{affinity::Affinities, stats::StatModifier};
+
+/// Where a piece of gear is worn. Only one piece of gear can be in each slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EquipSlot {
+    Weapon,
+    Armor,
+    Accessory,
+}
+
+impl EquipSlot {
+    /// Every slot, in the order they are shown.
+    pub const ALL: [EquipSlot; 3] = [EquipSlot::Weapon, EquipSlot::Armor, EquipSlot::Accessory];
+}
+
+/// A piece of gear, such as a sword, which changes the stats and affinities of whoever wears it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Gear {
+    name: String,
+    slot: EquipSlot,
+    modifiers: Vec<StatModifier>,
+    #[serde(default)]
+    affinities: Affinities, // how the wearer reacts to each element
+}
+
+impl Gear {
+    /// Create a new `Gear`.
+    ///
+    /// # Params
+    /// - `name` - The name of the `Gear`.
+    /// - `slot` - The slot the `Gear` is worn in.
+    /// - `modifiers` - How the `Gear` changes the stats of whoever wears it.
+    pub fn new(name: String, slot: EquipSlot, modifiers: Vec<StatModifier>) -> Self {
+        Self {
+            name,
+            slot,
+            modifiers,
+            affinities: Affinities::default(),
+        }
+    }
+
+    /// Builder function for setting how the gear changes the affinities of whoever wears it.
+    ///
+    /// # Params
+    /// - `affinities` - The affinities the gear gives to each element.
+    ///
+    /// # Returns
+    /// - The `Gear` object.
+    pub fn with_affinities(mut self, affinities: Affinities) -> Self {
+        self.affinities = affinities;
+        self
+    }
+
+    /// Get the name of this gear.
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Get the slot this gear is worn in.
+    pub fn slot(&self) -> EquipSlot {
+        self.slot
+    }
+
+    /// Get how this gear changes the stats of whoever wears it.
+    pub fn modifiers(&self) -> &[StatModifier] {
+        &self.modifiers
+    }
+
+    /// Get how this gear changes the affinities of whoever wears it.
+    pub fn affinities(&self) -> &Affinities {
+        &self.affinities
+    }
+}
+
+/// The gear an entity is wearing, along with the gear it is carrying.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Equipment {
+    weapon: Option<Gear>,
+    armor: Option<Gear>,
+    accessory: Option<Gear>,
+    bag: Vec<Gear>, // the gear that is carried but not worn
+}
+
+impl Equipment {
+    /// Gets the gear worn in a slot.
+    ///
+    /// # Params
+    /// - `slot` - The slot to look in.
+    pub fn equipped(&self, slot: EquipSlot) -> Option<&Gear> {
+        match slot {
+            EquipSlot::Weapon => self.weapon.as_ref(),
+            EquipSlot::Armor => self.armor.as_ref(),
+            EquipSlot::Accessory => self.accessory.as_ref(),
+        }
+    }
+
+    /// Gets the gear that is carried but not worn.
+    pub fn bag(&self) -> &[Gear] {
+        &self.bag
+    }
+
+    /// Adds gear to the bag.
+    ///
+    /// # Params
+    /// - `gear` - The gear to carry.
+    pub fn add(&mut self, gear: Gear) {
+        self.bag.push(gear);
+    }
+
+    /// Wears gear from the bag, putting whatever was in its slot back into the bag.
+    ///
+    /// # Params
+    /// - `bag_idx` - The index of the gear in the bag.
+    ///
+    /// # Returns
+    /// - `true` if the gear was equipped, `false` if there is no gear at `bag_idx`.
+    pub fn equip(&mut self, bag_idx: usize) -> bool {
+        if bag_idx >= self.bag.len() {
+            return false;
+        }
+
+        let gear = self.bag.remove(bag_idx);
+        if let Some(old_gear) = self.slot_mut(gear.slot).replace(gear) {
+            self.bag.push(old_gear);
+        }
+
+        true
+    }
+
+    /// Takes off the gear in a slot, putting it into the bag.
+    ///
+    /// # Params
+    /// - `slot` - The slot to empty.
+    ///
+    /// # Returns
+    /// - `true` if gear was taken off, `false` if the slot was already empty.
+    pub fn unequip(&mut self, slot: EquipSlot) -> bool {
+        match self.slot_mut(slot).take() {
+            Some(gear) => {
+                self.bag.push(gear);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn slot_mut(&mut self, slot: EquipSlot) -> &mut Option<Gear> {
+        match slot {
+            EquipSlot::Weapon => &mut self.weapon,
+            EquipSlot::Armor => &mut self.armor,
+            EquipSlot::Accessory => &mut self.accessory,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity_components::{
+        entity::Entity,
+        player::Player,
+        stats::{ModifierKind, StatType},
+    };
+
+    fn sword(name: &str, strength: i32) -> Gear {
+        Gear::new(
+            String::from(name),
+            EquipSlot::Weapon,
+            vec![StatModifier::new(
+                StatType::Strength,
+                ModifierKind::Flat,
+                strength,
+            )],
+        )
+    }
+
+    #[test]
+    fn new_gear_replaces_the_old_gear_in_its_slot() {
+        let mut player = Player::default();
+        let strength = player.stats().get_strength();
+        player.add_gear(sword("Bone Sword", 3));
+        player.add_gear(sword("Iron Sword", 5));
+
+        assert!(player.equip(0));
+        assert_eq!(player.stats().get_strength(), strength + 3);
+
+        // the iron sword is now first in the bag, with the bone sword going back behind it
+        assert!(player.equip(0));
+        assert_eq!(player.stats().get_strength(), strength + 5);
+        assert_eq!(player.stats().modifiers().len(), 1);
+
+        let equipment = player.equipment();
+        assert_eq!(
+            equipment
+                .equipped(EquipSlot::Weapon)
+                .map(Gear::name)
+                .as_deref(),
+            Some("Iron Sword")
+        );
+        assert_eq!(equipment.bag(), [sword("Bone Sword", 3)]);
+    }
+
+    #[test]
+    fn unequipping_restores_the_base_stats() {
+        let mut player = Player::default();
+        let max_health = player.max_health();
+        player.add_gear(Gear::new(
+            String::from("Plate"),
+            EquipSlot::Armor,
+            vec![
+                StatModifier::new(StatType::Defense, ModifierKind::Flat, 10),
+                StatModifier::new(StatType::MaxHealth, ModifierKind::Percent, 20),
+            ],
+        ));
+
+        player.equip(0);
+        assert_eq!(player.stats().get_defense(), 10);
+        assert!(player.max_health() > max_health);
+
+        assert!(player.unequip(EquipSlot::Armor));
+        assert_eq!(player.stats().get_defense(), 0);
+        assert_eq!(player.max_health(), max_health);
+        assert!(player.stats().modifiers().is_empty());
+        assert!(!player.unequip(EquipSlot::Armor));
+    }
+}
@@ -0,0 +1,268 @@
+// file for items and the inventory that holds them
+use rand::Rng;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::battle::event::BattleEvent;
+use crate::entity_components::equipment::Gear;
+use crate::entity_components::moves::ElementType;
+use crate::rng::GameRng;
+
+/// What an item does when it is used.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", deny_unknown_fields)]
+pub enum ItemEffect {
+    Heal { amount: u32 },                       // heals the user
+    RestoreMana { amount: u32 },                // gives mana back to the user
+    Cure { status: String },                    // removes a status from the user
+    Bomb { amount: u32, element: ElementType }, // deals elemental damage to the target
+}
+
+impl ItemEffect {
+    /// Checks if this effect is aimed at an enemy, rather than the user.
+    pub fn needs_target(&self) -> bool {
+        matches!(self, ItemEffect::Bomb { .. })
+    }
+}
+
+/// A consumable item, such as a potion.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Item {
+    name: String,
+    effect: ItemEffect,
+}
+
+impl Item {
+    /// Create a new `Item`.
+    ///
+    /// # Params
+    /// - `name` - The name of the `Item`.
+    /// - `effect` - What the `Item` does when it is used.
+    pub fn new(name: String, effect: ItemEffect) -> Self {
+        Self { name, effect }
+    }
+
+    /// Get the name of this item.
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Get what this item does when it is used.
+    pub fn effect(&self) -> &ItemEffect {
+        &self.effect
+    }
+}
+
+/// A number of the same item.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ItemStack {
+    item: Item,
+    count: u32,
+}
+
+impl ItemStack {
+    /// Get the item in this stack.
+    pub fn item(&self) -> &Item {
+        &self.item
+    }
+
+    /// Get how many of the item are in this stack.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+/// The items carried by an entity, stacked by name.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Inventory {
+    #[serde(deserialize_with = "non_empty_stacks")]
+    stacks: Vec<ItemStack>,
+}
+
+/// Loads the stacks of an inventory, dropping any that are empty, so a hand-edited
+/// save can't hold a stack that nothing can be taken from.
+fn non_empty_stacks<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<ItemStack>, D::Error> {
+    let mut stacks = Vec::<ItemStack>::deserialize(deserializer)?;
+    stacks.retain(|stack| stack.count > 0);
+    Ok(stacks)
+}
+
+impl Inventory {
+    /// Adds items to this inventory.
+    ///
+    /// # Params
+    /// - `item` - The item to add.
+    /// - `count` - How many of the item to add.
+    pub fn add(&mut self, item: Item, count: u32) {
+        if count == 0 {
+            return;
+        }
+
+        match self
+            .stacks
+            .iter_mut()
+            .find(|stack| stack.item.name == item.name)
+        {
+            Some(stack) => stack.count = stack.count.saturating_add(count),
+            None => self.stacks.push(ItemStack { item, count }),
+        }
+    }
+
+    /// Takes one item out of a stack, removing the stack once it is empty.
+    ///
+    /// # Params
+    /// - `stack_idx` - The index of the stack.
+    ///
+    /// # Returns
+    /// - The item taken, or `None` if there is no stack at `stack_idx` or it is empty.
+    pub fn take(&mut self, stack_idx: usize) -> Option<Item> {
+        let stack = self.stacks.get_mut(stack_idx)?;
+        stack.count = stack.count.checked_sub(1)?;
+
+        if stack.count == 0 {
+            Some(self.stacks.remove(stack_idx).item)
+        } else {
+            Some(stack.item.clone())
+        }
+    }
+
+    /// Gets the stacks of items in this inventory.
+    pub fn stacks(&self) -> &[ItemStack] {
+        &self.stacks
+    }
+
+    /// Checks if there are no items in this inventory.
+    pub fn is_empty(&self) -> bool {
+        self.stacks.is_empty()
+    }
+}
+
+/// Something an enemy can drop when it dies.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)] // told apart by their fields
+pub enum Loot {
+    Item(Item),
+    Gear(Gear),
+}
+
+impl Loot {
+    /// Get the name of the dropped item or gear.
+    pub fn name(&self) -> String {
+        match self {
+            Loot::Item(item) => item.name(),
+            Loot::Gear(gear) => gear.name(),
+        }
+    }
+}
+
+/// A chance for an enemy to drop loot when it dies. An enemy's list of these is its loot table.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LootDrop {
+    #[serde(alias = "item")]
+    loot: Loot,
+    chance: f64, // between 0 and 1
+}
+
+impl LootDrop {
+    /// Create a new `LootDrop`.
+    ///
+    /// # Params
+    /// - `loot` - The item or gear that can be dropped.
+    /// - `chance` - The chance of dropping the loot, between 0 and 1.
+    pub fn new(loot: Loot, chance: f64) -> Self {
+        Self { loot, chance }
+    }
+
+    /// Rolls to see if the loot is dropped.
+    ///
+    /// # Params
+    /// - `source` - The name of the entity dropping the loot, for the event.
+    /// - `rng` - The random number generator of the game.
+    /// - `events` - The events of the battle, to push the dropped loot.
+    ///
+    /// # Returns
+    /// - The loot if it was dropped, `None` otherwise.
+    pub fn roll(
+        &self,
+        source: &str,
+        rng: &mut GameRng,
+        events: &mut Vec<BattleEvent>,
+    ) -> Option<Loot> {
+        if !rng.gen_bool(self.chance.clamp(0.0, 1.0)) {
+            return None;
+        }
+
+        events.push(BattleEvent::ItemDropped {
+            source: String::from(source),
+            item: self.loot.name(),
+        });
+
+        Some(self.loot.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn potion() -> Item {
+        Item::new(String::from("Potion"), ItemEffect::Heal { amount: 20 })
+    }
+
+    fn ether() -> Item {
+        Item::new(
+            String::from("Ether"),
+            ItemEffect::RestoreMana { amount: 10 },
+        )
+    }
+
+    #[test]
+    fn items_with_the_same_name_stack() {
+        let mut inventory = Inventory::default();
+        inventory.add(potion(), 2);
+        inventory.add(ether(), 1);
+        inventory.add(potion(), 3);
+        inventory.add(ether(), 0);
+
+        let counts: Vec<(String, u32)> = inventory
+            .stacks()
+            .iter()
+            .map(|stack| (stack.item().name(), stack.count()))
+            .collect();
+        assert_eq!(
+            counts,
+            [(String::from("Potion"), 5), (String::from("Ether"), 1)]
+        );
+    }
+
+    #[test]
+    fn taking_the_last_item_removes_the_stack() {
+        let mut inventory = Inventory::default();
+        inventory.add(potion(), 2);
+        inventory.add(ether(), 1);
+
+        assert_eq!(inventory.take(0), Some(potion()));
+        assert_eq!(inventory.stacks()[0].count(), 1);
+
+        assert_eq!(inventory.take(1), Some(ether()));
+        assert_eq!(inventory.stacks().len(), 1);
+
+        assert_eq!(inventory.take(0), Some(potion()));
+        assert!(inventory.is_empty());
+        assert_eq!(inventory.take(0), None);
+    }
+
+    #[test]
+    fn empty_stacks_are_dropped_when_loaded() {
+        let json = r#"{"stacks": [
+            {"item": {"name": "Potion", "effect": {"kind": "Heal", "amount": 20}}, "count": 0},
+            {"item": {"name": "Ether", "effect": {"kind": "RestoreMana", "amount": 10}}, "count": 1}
+        ]}"#;
+        let mut inventory: Inventory = serde_json::from_str(json).unwrap();
+
+        assert_eq!(inventory.stacks().len(), 1);
+        assert_eq!(inventory.take(0), Some(ether()));
+        assert!(inventory.is_empty());
+    }
+}
@@ -1,7 +1,13 @@
 //file to define the modules within this folder
+pub mod affinity;
+pub mod ai;
+pub mod combatant;
 pub mod enemy;
 pub mod entity;
+pub mod equipment;
+pub mod item;
 pub mod moves;
 pub mod player;
+pub mod resource;
 pub mod stats;
 pub mod status;
@@ -1,8 +1,11 @@
-use rand::random;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
+use super::stats::StatModifier;
 use super::status::Status;
+use crate::rng::GameRng;
 
-#[derive(Clone)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ElementType {
     Fire,
     Wind,
@@ -11,15 +14,63 @@ pub enum ElementType {
     None,
 }
 
+/// What a move does when it is cast.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MoveEffect {
+    #[default]
+    Damage, // damages the target
+    HealSelf,    // heals the caster
+    Buff,        // always applies its status and modifiers, without dealing damage
+    Cleanse,     // removes every harmful status from the target
+    DrainHealth, // damages the target, healing the caster by the damage dealt
+    DrainMana,   // takes mana from the target and gives it to the caster
+    Shield,      // gives the target a shield, which soaks up damage before health
+}
+
+impl MoveEffect {
+    /// Checks if this effect is meant for the caster's opponents, rather than allies.
+    pub fn is_hostile(&self) -> bool {
+        matches!(
+            self,
+            MoveEffect::Damage | MoveEffect::DrainHealth | MoveEffect::DrainMana
+        )
+    }
+
+    /// Checks if this effect can be aimed at a target. Hostile effects can't hit the
+    /// caster, and healing the caster can't hit anyone else.
+    ///
+    /// # Params
+    /// - `target` - Who the move is aimed at.
+    pub fn can_target(&self, target: MoveTarget) -> bool {
+        let aimed_at_user = target == MoveTarget::User;
+        match self {
+            MoveEffect::HealSelf => aimed_at_user,
+            effect => !(effect.is_hostile() && aimed_at_user),
+        }
+    }
+}
+
+/// Who a move is aimed at. Enemies are the opponents of the caster, so an
+/// enemy's moves aimed at its enemies hit the player.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MoveTarget {
+    User, // the caster
+    #[default]
+    SingleEnemy, // one opponent, chosen by the caster
+    AllEnemies, // every opponent
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MoveType {
     AttackMove,
     MagicMove,
     DefendMove,
+    ItemMove,
 }
 
 /// Struct for representing a move in the game.
-/// This could be an attacking or healing move.
-#[derive(Clone)]
+/// This could be an attacking or healing move, see `MoveEffect`.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Move {
     name: String, // specify the lifetime of this variable (still don't know why)
     base_amount: u32,
@@ -27,6 +78,16 @@ pub struct Move {
     level_requirement: u32,
     element: ElementType,
     applied_status: Option<Status>,
+    #[serde(default)]
+    modifiers: Vec<StatModifier>, // buffs or debuffs for the target of the move
+    #[serde(default)]
+    modifier_turns: u32, // how many of the target's turns the modifiers last
+    #[serde(default)]
+    effect: MoveEffect,
+    #[serde(default)]
+    target: MoveTarget,
+    #[serde(default)]
+    focus_cost: u32, // the focus needed on top of the mana, for the player's special moves
 }
 
 impl Move {
@@ -45,9 +106,16 @@ impl Move {
             level_requirement,
             element,
             applied_status,
+            modifiers: Vec::new(),
+            modifier_turns: 0,
+            effect: MoveEffect::Damage,
+            target: MoveTarget::SingleEnemy,
+            focus_cost: 0,
         }
     }
+}
 
+impl Default for Move {
     fn default() -> Self {
         Self {
             name: String::from("DEFAULT_MOVE"),
@@ -56,9 +124,16 @@ impl Move {
             level_requirement: 1,
             element: ElementType::None,
             applied_status: None,
+            modifiers: Vec::new(),
+            modifier_turns: 0,
+            effect: MoveEffect::Damage,
+            target: MoveTarget::SingleEnemy,
+            focus_cost: 0,
         }
     }
+}
 
+impl Move {
     /// Builder function for easily builing moves.
     /// Sets the name of the Move object and returns it.
     ///
@@ -67,7 +142,7 @@ impl Move {
     ///
     /// # Returns
     /// - The `Move` object.
-    fn with_name(mut self, name: String) -> Self {
+    pub fn with_name(mut self, name: String) -> Self {
         self.name = name;
         self
     }
@@ -80,7 +155,7 @@ impl Move {
     ///
     /// # Returns
     /// - The `Move` object.
-    fn with_base_amount(mut self, base_amount: u32) -> Self {
+    pub fn with_base_amount(mut self, base_amount: u32) -> Self {
         self.base_amount = base_amount;
         self
     }
@@ -93,7 +168,7 @@ impl Move {
     ///
     /// # Returns
     /// - The `Move` object.
-    fn with_mana_cost(mut self, mana_cost: u32) -> Self {
+    pub fn with_mana_cost(mut self, mana_cost: u32) -> Self {
         self.mana_cost = mana_cost;
         self
     }
@@ -106,7 +181,7 @@ impl Move {
     ///
     /// # Returns
     /// - The `Move` object.
-    fn with_level_requirement(mut self, level_requirement: u32) -> Self {
+    pub fn with_level_requirement(mut self, level_requirement: u32) -> Self {
         self.level_requirement = level_requirement;
         self
     }
@@ -119,7 +194,7 @@ impl Move {
     ///
     /// # Returns
     /// - The `Move` object.
-    fn with_element(mut self, element: ElementType) -> Self {
+    pub fn with_element(mut self, element: ElementType) -> Self {
         self.element = element;
         self
     }
@@ -132,23 +207,67 @@ impl Move {
     ///
     /// # Returns
     /// - The `Move` object.
-    fn with_applied_status(mut self, applied_status: Option<Status>) -> Self {
+    pub fn with_applied_status(mut self, applied_status: Option<Status>) -> Self {
         self.applied_status = applied_status;
         self
     }
 
+    /// Builder function for easily builing moves.
+    /// Sets the buffs or debuffs of the Move object and returns it.
+    ///
+    /// # Params
+    /// - `modifiers` - The changes to the stats of the target of the move.
+    /// - `modifier_turns` - How many of the target's turns the changes last.
+    ///
+    /// # Returns
+    /// - The `Move` object.
+    pub fn with_modifiers(mut self, modifiers: Vec<StatModifier>, modifier_turns: u32) -> Self {
+        self.modifiers = modifiers;
+        self.modifier_turns = modifier_turns;
+        self
+    }
+
+    /// Builder function for easily builing moves.
+    /// Sets what the Move object does and who it is aimed at, and returns it.
+    ///
+    /// # Params
+    /// - `effect` - What the move does.
+    /// - `target` - Who the move is aimed at.
+    ///
+    /// # Returns
+    /// - The `Move` object.
+    pub fn with_effect(mut self, effect: MoveEffect, target: MoveTarget) -> Self {
+        self.effect = effect;
+        self.target = target;
+        self
+    }
+
+    /// Builder function for easily builing moves.
+    /// Sets the focus cost of the Move object and returns it.
+    ///
+    /// # Params
+    /// - `focus_cost` - The focus needed to cast the move, which makes it a special move.
+    ///
+    /// # Returns
+    /// - The `Move` object.
+    pub fn with_focus_cost(mut self, focus_cost: u32) -> Self {
+        self.focus_cost = focus_cost;
+        self
+    }
+
     /// Generates a random damage/healing value for this `Move`.
     ///
     /// # Params
     /// - `magic_strength` - The magic strength stat of the user to enhance the Move's damage/healing.
+    /// - `rng` - The random number generator of the game.
     ///
     /// # Returns
     /// - The random value of healing/damage this `Move` will do.
-    pub fn generate_random_amount(&self, magic_strength: u32) -> u32 {
+    pub fn generate_random_amount(&self, magic_strength: u32, rng: &mut GameRng) -> u32 {
         // magic_strength + (random number between 0 and magic_strength/2)
         magic_strength
             + self.base_amount
-            + (rand::random::<u32>() % (magic_strength + self.base_amount / 2))
+            + rng.gen_range(0..(magic_strength + self.base_amount / 2).max(1))
     }
 
     /// Creates and returns a `Move` list of all the moves that are in the game.
@@ -157,11 +276,11 @@ impl Move {
     ///
     /// # Returns
     /// - A `Vec` of all the `Move`s in the game.
-    pub fn get_move_list(full_move_list: &Vec<Move>, entity_level: u32) -> usize {
+    pub fn get_move_list(full_move_list: &[Move], entity_level: u32) -> usize {
         let mut valid_len: usize = 0;
 
-        for i in 0..full_move_list.len() {
-            if full_move_list[i].is_meeting_requirements(entity_level) {
+        for the_move in full_move_list {
+            if the_move.is_meeting_requirements(entity_level) {
                 valid_len += 1;
             } else {
                 // break out of the for loop, as moves are ordered by level requirement
@@ -192,6 +311,22 @@ impl Move {
         self.mana_cost
     }
 
+    /// Get the focus cost of this move.
+    ///
+    /// # Returns
+    /// - the focus cost of the `Move`, which is 0 for moves that aren't special moves.
+    pub fn focus_cost(&self) -> u32 {
+        self.focus_cost
+    }
+
+    /// Get the base amount of damage/healing of this move.
+    ///
+    /// # Returns
+    /// - the base amount of the `Move`, before magic strength and the random roll.
+    pub fn base_amount(&self) -> u32 {
+        self.base_amount
+    }
+
     /// Get the name of this move.
     ///
     /// # Returns
@@ -200,11 +335,18 @@ impl Move {
         self.name.clone()
     }
 
-    pub fn roll_status_chance(&self) -> bool {
+    /// Rolls to see if this move applies its status.
+    ///
+    /// # Params
+    /// - `rng` - The random number generator of the game.
+    ///
+    /// # Returns
+    /// - `true` if the status should be applied, `false` otherwise (or if there is no status).
+    pub fn roll_status_chance(&self, rng: &mut GameRng) -> bool {
         let mut result = false;
         if self.applied_status.is_some() {
-            let rand_num = (random::<u32>() % 100) + 1;
-            let chance = (Status::status_chance() * 100 as f64) as u32;
+            let rand_num = rng.gen_range(1..=100);
+            let chance = (Status::status_chance() * 100_f64) as u32;
 
             if rand_num <= chance {
                 result = true
@@ -214,27 +356,35 @@ impl Move {
         result
     }
 
+    /// Get the element of this move.
+    ///
+    /// # Returns
+    /// - the `ElementType` of the `Move`.
+    pub fn element(&self) -> ElementType {
+        self.element
+    }
+
     pub fn get_status(&self) -> Option<Status> {
         self.applied_status.clone()
     }
 
-    /// Creates a `Move` list for the game
-    ///
-    /// # Returns
-    /// - The full `Move` list for the game.
-    pub fn create_move_list(status_list: &Vec<Status>) -> Vec<Move> {
-        vec![
-            Move::new(
-                String::from("FireOne"),
-                12,
-                2,
-                1,
-                ElementType::Fire,
-                Status::get_status_from("Burn", status_list),
-            ),
-            Move::new(String::from("WindOne"), 14, 2, 3, ElementType::Wind, None),
-            Move::new(String::from("EarthOne"), 16, 2, 5, ElementType::Earth, None),
-            Move::new(String::from("WaterOne"), 20, 2, 6, ElementType::Water, None),
-        ]
+    /// Get the buffs or debuffs this move gives its target.
+    pub fn modifiers(&self) -> &[StatModifier] {
+        &self.modifiers
+    }
+
+    /// Get how many of the target's turns the modifiers of this move last.
+    pub fn modifier_turns(&self) -> u32 {
+        self.modifier_turns
+    }
+
+    /// Get what this move does.
+    pub fn effect(&self) -> MoveEffect {
+        self.effect
+    }
+
+    /// Get who this move is aimed at.
+    pub fn target(&self) -> MoveTarget {
+        self.target
     }
 }
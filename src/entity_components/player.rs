@@ -1,76 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+use super::affinity::Affinities;
+use super::combatant::CombatantCore;
+use super::equipment::{EquipSlot, Equipment, Gear};
+use super::item::{Inventory, Item, ItemEffect};
+use crate::battle::event::BattleEvent;
+use crate::battle::outcome::{ActionError, ActionOutcome};
 use crate::entity_components::entity::Entity;
 use crate::entity_components::moves::Move;
-use crate::entity_components::moves::MoveType;
-use crate::entity_components::stats::Stats;
-use std::collections::VecDeque;
-
-use super::status::Status;
+use crate::entity_components::stats::{ModifierSource, Stats};
+use crate::rng::GameRng;
 
 const XP_TO_LEVEL_UP: u32 = 100;
 pub const DEFAULT_PLAYER_LEVEL: u32 = 1;
 pub const DEFAULT_PLAYER_XP: u32 = 0;
-pub const DEFAULT_HAS_GONE_STATE: bool = false;
 pub const DEFAULT_NAME: &str = "Louie";
+pub const MAX_FOCUS: u32 = 100;
+const FOCUS_PER_HIT: u32 = 20; // focus built up by each attack that lands
 
 ///Struct to represent the Player.
 ///Implements the Entity trait
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Player {
-    name: String,
-    health: u32,
-    max_health: u32,
-    mana: u32,
-    max_mana: u32,
-    stats: Stats,
-    level: u32,
+    #[serde(flatten)]
+    core: CombatantCore, // the name, health, mana, stats, level and statuses
     xp: u32,
-    has_gone: bool,
-    statuses: Vec<Status>,
+    #[serde(default)]
+    focus: u32, // built up by landing attacks, and spent on special moves
+    #[serde(default)]
+    inventory: Inventory, // the items the player is carrying
+    #[serde(default)]
+    equipment: Equipment, // the gear the player is wearing and carrying
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LevelUpType {
     Strength,
     Magic,
     Health,
 }
 
+impl LevelUpType {
+    /// Every level up choice, in the order they are shown.
+    pub const ALL: [LevelUpType; 3] = [
+        LevelUpType::Strength,
+        LevelUpType::Magic,
+        LevelUpType::Health,
+    ];
+
+    /// Gets the name of a level up choice, as it is typed in a command.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LevelUpType::Strength => "strength",
+            LevelUpType::Magic => "magic",
+            LevelUpType::Health => "health",
+        }
+    }
+
+    /// Gets a level up choice from its name.
+    ///
+    /// # Params
+    /// - `name` - The name of the choice, such as `"strength"`.
+    ///
+    /// # Returns
+    /// - The `LevelUpType`, or `None` if there is no choice with that name.
+    pub fn from_name(name: &str) -> Option<LevelUpType> {
+        LevelUpType::ALL
+            .into_iter()
+            .find(|level_type| level_type.name() == name)
+    }
+}
+
 impl Player {
-    pub fn new(name: String, stats: Stats, level: u32, xp: u32, has_gone: bool) -> Self {
-        // start with this mana and hp
-        let starting_health = stats.calculate_max_health();
-        let starting_mana = stats.calculate_max_mana();
+    pub fn new(name: String, stats: Stats, level: u32, xp: u32) -> Self {
         Self {
-            name,
-            health: starting_health,
-            max_health: starting_health,
-            mana: starting_mana,
-            max_mana: starting_mana,
-            stats,
-            level,
+            core: CombatantCore::new(name, stats, level),
             xp,
-            has_gone,
-            statuses: Vec::new(), // start with no statuses
+            focus: 0,
+            inventory: Inventory::default(),
+            equipment: Equipment::default(),
         }
     }
 
-    pub fn default() -> Self {
-        // start with this mana and hp
-        let stats = Stats::default();
-        let starting_health = stats.calculate_max_health();
-        let starting_mana = stats.calculate_max_mana();
+    /// Builder function for setting the items a `Player` is carrying.
+    ///
+    /// # Params
+    /// - `inventory` - The items the `Player` starts with.
+    ///
+    /// # Returns
+    /// - The `Player` object.
+    pub fn with_inventory(mut self, inventory: Inventory) -> Self {
+        self.inventory = inventory;
+        self
+    }
+}
+
+impl Default for Player {
+    fn default() -> Self {
         Self {
-            name: String::from(DEFAULT_NAME),
-            health: starting_health,
-            max_health: starting_health,
-            mana: starting_mana,
-            max_mana: starting_mana,
-            stats,
-            level: DEFAULT_PLAYER_LEVEL,
+            core: CombatantCore::new(
+                String::from(DEFAULT_NAME),
+                Stats::default(),
+                DEFAULT_PLAYER_LEVEL,
+            ),
             xp: DEFAULT_PLAYER_XP,
-            has_gone: DEFAULT_HAS_GONE_STATE,
-            statuses: Vec::new(), // start with no statuses
+            focus: 0,
+            inventory: Inventory::default(),
+            equipment: Equipment::default(),
         }
     }
+}
 
+impl Player {
     /// Makes the Player gain xp and level up if they reach the xp to level up.
     ///
     /// # Returns
@@ -92,268 +133,470 @@ impl Player {
     /// The player levels up and gets to choose stats to increase
     pub fn level_up(&mut self, level_type: LevelUpType) {
         // increment level
-        self.level += 1;
+        self.core.level_up();
 
         match level_type {
-            LevelUpType::Strength => self.stats.increase_physical(),
-            LevelUpType::Magic => self.stats.increase_magic(),
-            LevelUpType::Health => self.stats.increase_health(),
+            LevelUpType::Strength => self.stats_mut().increase_physical(),
+            LevelUpType::Magic => self.stats_mut().increase_magic(),
+            LevelUpType::Health => self.stats_mut().increase_health(),
         }
         self.reset_stats();
     }
 
     /// Recalculates stats and gives the player max health and mana
     fn reset_stats(&mut self) {
-        let new_health = self.stats.calculate_max_health();
-        let new_mana = self.stats.calculate_max_mana();
-        self.health = new_health;
-        self.max_health = new_health;
-        self.mana = new_mana;
-        self.max_mana = new_mana;
-    }
-
-    /// Displays attack text for the `Player` attacking another `Entity`.
-    fn display_attack_text(
-        &self,
-        victim_entity_name: String,
-        damage_dealt: u32,
-        text_vec: &mut VecDeque<String>,
-    ) {
-        text_vec.push_back(format!(
-            "You did {} damage to {}",
-            damage_dealt.to_string(),
-            victim_entity_name
-        ));
-    }
-
-    /// The `Player` performs a magic move against another `Entity`.
+        self.update_max_resources();
+        self.core.health_mut().refill();
+        self.core.mana_mut().refill();
+    }
+
+    /// The `Player` casts a magic move.
     ///
     /// # Params
-    /// - `target` - The target of the attack.
+    /// - `targets` - The enemies the move is aimed at, which is ignored by moves aimed at the `Player`.
     /// - `the_move` - The `Move` for the `Player` object to perform.
+    /// - `rng` - The random number generator of the game.
+    /// - `events` - The events of the battle, to push what the move did.
+    ///
+    /// # Returns
+    /// - The move that was cast, or why it couldn't be cast.
     pub fn magic_move(
         &mut self,
-        target: &mut dyn Entity,
+        targets: Vec<&mut dyn Entity>,
         the_move: &Move,
-        text_vec: &mut VecDeque<String>,
-    ) -> bool {
-        // check whether the user has already gone or if the move costs too much
-        if self.has_gone || self.mana < the_move.cost() {
-            return false; // error
-        }
+        rng: &mut GameRng,
+        events: &mut Vec<BattleEvent>,
+    ) -> Result<ActionOutcome, ActionError> {
+        self.check_cast(the_move)?;
 
-        let random_damage = the_move.generate_random_amount(self.magic_strength());
-
-        let damage_dealt = self.attack_entity(random_damage, target);
-        // use the mana from this move
+        let random_damage = the_move.generate_random_amount(self.magic_strength(), rng);
+        // use the mana and focus from this move
         self.use_mana(the_move.cost());
-        // display the damage that was dealt
-        self.display_attack_text(target.name(), damage_dealt, text_vec);
+        self.focus -= the_move.focus_cost();
 
-        // roll for random chance to apply status if it exists
-        if the_move.roll_status_chance() {
-            target.apply_status(&the_move.get_status().unwrap(), text_vec);
-        }
+        self.cast_move(the_move, random_damage, targets, rng, events);
 
-        // the player has gone
-        self.has_gone = true;
+        Ok(ActionOutcome::Cast {
+            move_name: the_move.name(),
+        })
+    }
 
-        // no error
-        true
+    /// Gets the mana the `Player` regains at the end of each turn.
+    pub fn mana_regen(&self) -> u32 {
+        self.stats().calc_mana_regen()
     }
 
-    pub fn defend_move(&mut self, text_vec: &mut VecDeque<String>) -> bool {
-        if self.has_gone {
-            return false;
+    /// The `Player` regains mana for killing an enemy.
+    ///
+    /// # Params
+    /// - `events` - The events of the battle, to push the mana regained.
+    pub fn restore_kill_mana(&mut self, events: &mut Vec<BattleEvent>) {
+        let amount = self.restore_mana(self.stats().calc_kill_mana());
+        if amount > 0 {
+            events.push(BattleEvent::ManaRestored {
+                target: self.name(),
+                amount,
+            });
         }
-        self.start_defending();
-
-        // tell player that they started defending
-        let mut output_str = String::new();
-        output_str.push_str(self.name.as_str());
-        output_str.push_str(" began defending for 1 turn.");
-
-        text_vec.push_back(format!("{} began defending for 1 turn.", self.name));
-
-        // no error
-        true
     }
 
-    pub fn experience(&self) -> u32 {
-        self.xp
-    }
+    /// The `Player` uses an item from their inventory.
+    ///
+    /// # Params
+    /// - `stack_idx` - The index of the item's stack in the inventory.
+    /// - `target` - The target of the item, if it is used against an enemy.
+    /// - `events` - The events of the battle, to push what the item did.
+    ///
+    /// # Returns
+    /// - The item that was used, or why it couldn't be used.
+    pub fn item_move(
+        &mut self,
+        stack_idx: usize,
+        target: &mut dyn Entity,
+        events: &mut Vec<BattleEvent>,
+    ) -> Result<ActionOutcome, ActionError> {
+        let item = self.check_item(stack_idx)?.clone();
+
+        self.inventory.take(stack_idx);
+        events.push(BattleEvent::ItemUsed {
+            user: self.name(),
+            item: item.name(),
+        });
+
+        match item.effect() {
+            ItemEffect::Heal { amount } => {
+                let healed = self.heal(*amount);
+                events.push(BattleEvent::Healed {
+                    target: self.name(),
+                    amount: healed,
+                });
+            }
 
-    pub fn max_experience(&self) -> u32 {
-        XP_TO_LEVEL_UP
-    }
+            ItemEffect::RestoreMana { amount } => {
+                let restored = self.restore_mana(*amount);
+                events.push(BattleEvent::ManaRestored {
+                    target: self.name(),
+                    amount: restored,
+                });
+            }
 
-    pub fn mana(&self) -> u32 {
-        self.mana
-    }
+            ItemEffect::Cure { status } => {
+                self.cure_status(status);
+                events.push(BattleEvent::StatusCured {
+                    status: status.clone(),
+                    target: self.name(),
+                });
+            }
+
+            ItemEffect::Bomb { amount, element } => {
+                self.hit_with_element(*amount, *element, target, events);
+            }
+        }
 
-    pub fn max_mana(&self) -> u32 {
-        self.max_mana
+        Ok(ActionOutcome::UsedItem { item: item.name() })
     }
-}
 
-//entity implementation for player
-impl Entity for Player {
-    ///Makes the Player take damage
-    fn take_damage(&mut self, amount: u32) -> u32 {
-        let damage_taken = self.stats.calc_damage_taken(amount);
-
-        if damage_taken > self.health {
-            self.health = 0;
-        } else {
-            self.health -= damage_taken;
+    /// Checks if the `Player` can use an item from their inventory.
+    ///
+    /// # Params
+    /// - `stack_idx` - The index of the item's stack in the inventory.
+    ///
+    /// # Returns
+    /// - The item, or why it can't be used.
+    pub fn check_item(&self, stack_idx: usize) -> Result<&Item, ActionError> {
+        let item = match self.inventory.stacks().get(stack_idx) {
+            Some(stack) => stack.item(),
+            None => return Err(ActionError::UnknownItem { stack_idx }),
+        };
+
+        // don't waste a cure on a status the player doesn't have
+        if let ItemEffect::Cure { status } = item.effect() {
+            if !self
+                .statuses()
+                .iter()
+                .any(|applied| applied.name() == *status)
+            {
+                return Err(ActionError::NothingToCure {
+                    status: status.clone(),
+                });
+            }
         }
 
-        damage_taken
+        Ok(item)
     }
 
-    ///Heals the Player
-    fn heal(&mut self, amount: u32) {
-        self.health += amount;
+    /// Gives the `Player` items.
+    ///
+    /// # Params
+    /// - `item` - The item to give.
+    /// - `count` - How many of the item to give.
+    pub fn add_item(&mut self, item: Item, count: u32) {
+        self.inventory.add(item, count);
     }
 
-    /// Makes the Player use mana
-    fn use_mana(&mut self, amount: u32) {
-        if amount > self.mana {
-            self.mana = 0;
-        } else {
-            self.mana -= amount;
-        }
+    /// Gets the items the `Player` is carrying.
+    pub fn inventory(&self) -> &Inventory {
+        &self.inventory
     }
 
-    ///Gets the speed of the Player
-    fn speed(&self) -> u32 {
-        self.stats.get_speed()
+    /// Gives the `Player` a piece of gear, which goes into their bag.
+    ///
+    /// # Params
+    /// - `gear` - The gear to give.
+    pub fn add_gear(&mut self, gear: Gear) {
+        self.equipment.add(gear);
     }
 
-    ///Checks to see if the Player is dead
-    fn is_dead(&self) -> bool {
-        self.health == 0
-    }
+    /// The `Player` puts on a piece of gear from their bag.
+    ///
+    /// # Params
+    /// - `bag_idx` - The index of the gear in the bag.
+    ///
+    /// # Returns
+    /// - `true` if the gear was equipped, `false` if there is no gear at `bag_idx`.
+    pub fn equip(&mut self, bag_idx: usize) -> bool {
+        let gear = match self.equipment.bag().get(bag_idx) {
+            Some(gear) => gear.clone(),
+            None => return false, // error
+        };
+
+        // the gear already in the slot goes back into the bag
+        if let Some(old_gear) = self.equipment.equipped(gear.slot()) {
+            self.core
+                .stats_mut()
+                .remove_modifiers(&ModifierSource::Equipment(old_gear.name()));
+        }
+        self.equipment.equip(bag_idx);
+
+        // gear lasts until it is taken off
+        for modifier in gear.modifiers() {
+            self.core.stats_mut().push_modifier(
+                ModifierSource::Equipment(gear.name()),
+                modifier.clone(),
+                None,
+            );
+        }
+        self.update_max_resources();
+        self.update_affinities();
 
-    ///Checks to see if the Player has gone this turn
-    fn gone_this_turn(&self) -> bool {
-        self.has_gone
+        // no error
+        true
     }
 
-    ///Player chooses attack type, and it is returned.
+    /// The `Player` takes off the gear in a slot, putting it in their bag.
     ///
-    /// # NOTE: This method is now defunct
-    fn get_turn_type(&mut self) -> Option<MoveType> {
-        panic!()
-    }
+    /// # Params
+    /// - `slot` - The slot to take the gear out of.
+    ///
+    /// # Returns
+    /// - `true` if the gear was taken off, `false` if the slot was empty.
+    pub fn unequip(&mut self, slot: EquipSlot) -> bool {
+        let gear_name = match self.equipment.equipped(slot) {
+            Some(gear) => gear.name(),
+            None => return false, // error
+        };
+
+        self.equipment.unequip(slot);
+        self.core
+            .stats_mut()
+            .remove_modifiers(&ModifierSource::Equipment(gear_name));
+        self.update_max_resources();
+        self.update_affinities();
 
-    fn get_random_attack_dmg(&self) -> u32 {
-        self.stats.generate_random_attack_dmg()
+        // no error
+        true
     }
 
-    /// Get the name of the Player
-    fn name(&self) -> String {
-        self.name.clone()
-    }
+    /// Works out the `Player`'s affinities from the gear they are wearing, since the
+    /// `Player` has none of their own.
+    fn update_affinities(&mut self) {
+        let affinities = EquipSlot::ALL
+            .iter()
+            .filter_map(|slot| self.equipment.equipped(*slot))
+            .fold(Affinities::default(), |affinities, gear| {
+                affinities.combine(gear.affinities())
+            });
 
-    /// Get the level of the Player
-    fn level(&self) -> u32 {
-        self.level
+        self.core.set_affinities(affinities);
     }
 
-    fn magic_strength(&self) -> u32 {
-        self.stats.get_magic_strength()
+    /// Gets the gear the `Player` is wearing and carrying.
+    pub fn equipment(&self) -> &Equipment {
+        &self.equipment
     }
 
-    fn start_defending(&mut self) {
-        self.stats.start_defending();
+    pub fn experience(&self) -> u32 {
+        self.xp
     }
 
-    fn stop_defending(&mut self) {
-        self.stats.stop_defending();
+    pub fn max_experience(&self) -> u32 {
+        XP_TO_LEVEL_UP
     }
+}
 
-    /// Ticks statuses and goes through the list
-    fn tick_statuses(&mut self, text_vec: &mut VecDeque<String>) {
-        let mut indicies_to_remove: Vec<usize> = Vec::new();
+//entity implementation for player
+impl Entity for Player {
+    fn core(&self) -> &CombatantCore {
+        &self.core
+    }
 
-        for i in 0..self.statuses.len() {
-            let amount = self.statuses[i].calculate_amount();
+    fn core_mut(&mut self) -> &mut CombatantCore {
+        &mut self.core
+    }
 
-            // mark this status for removal if it has no turns left
-            if self.statuses[i].tick() {
-                // push this index
-                indicies_to_remove.push(i);
-            }
+    fn focus(&self) -> u32 {
+        self.focus
+    }
 
-            // print what the status effect did and apply effect
-            if self.statuses[i].is_healing() {
-                text_vec.push_back(format!(
-                    "{} healed {} health from {}",
-                    self.name,
-                    amount,
-                    self.statuses[i].name()
-                ));
-                self.heal(amount);
-            } else {
-                text_vec.push_back(format!(
-                    "{} took {} damage from {}",
-                    self.name,
+    fn attack_move(
+        &mut self,
+        target: &mut dyn Entity,
+        rng: &mut GameRng,
+        events: &mut Vec<BattleEvent>,
+    ) -> Result<ActionOutcome, ActionError> {
+        // a miss still uses up the turn, but only a hit builds focus
+        let damage = self.strike(target, rng, events);
+        if damage.is_some() {
+            let amount = FOCUS_PER_HIT.min(MAX_FOCUS - self.focus);
+            if amount > 0 {
+                self.focus += amount;
+                events.push(BattleEvent::FocusGained {
+                    target: self.name(),
                     amount,
-                    self.statuses[i].name()
-                ));
-                self.take_damage(amount);
+                });
             }
         }
 
-        // remove all statuses that were marked for removal
-        let mut cur_num_removed = 0;
-
-        for i in 0..indicies_to_remove.len() {
-            // since the indices of the elements will change due to the removal
-            let index = indicies_to_remove[i] - cur_num_removed;
-            self.statuses.remove(index);
-            cur_num_removed += 1; // we have removed another status
-        }
-    }
-
-    fn apply_status(&mut self, status: &Status, text_vec: &mut VecDeque<String>) {
-        text_vec.push_back(format!("{} applied to {}", status.name(), self.name));
-        self.statuses.push(status.clone());
+        Ok(ActionOutcome::Attacked { damage })
     }
+}
 
-    fn attack_move(&mut self, target: &mut dyn Entity, text_vec: &mut VecDeque<String>) -> bool {
-        if self.has_gone {
-            return false; // error, did not go
-        }
-        // attack the enemy with a random amount of damage
-        let random_damage = self.get_random_attack_dmg();
-
-        let damage_dealt = self.attack_entity(random_damage, target);
-        // display the damage dealt
-        self.display_attack_text(target.name(), damage_dealt, text_vec);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity_components::{
+        affinity::Affinity,
+        enemy::Enemy,
+        moves::ElementType,
+        stats::{ModifierKind, StatModifier, StatType},
+    };
+    use crate::rng::create_rng;
 
-        // the player has gone
-        self.has_gone = true;
+    /// Creates gear that gives an affinity to fire.
+    fn fire_gear(name: &str, slot: EquipSlot, affinity: &str) -> Gear {
+        let affinities = serde_json::from_str(&format!(r#"{{"Fire": "{}"}}"#, affinity)).unwrap();
 
-        // no error
-        true
+        Gear::new(String::from(name), slot, Vec::new()).with_affinities(affinities)
     }
 
-    fn has_gone(&self) -> bool {
-        self.has_gone
-    }
+    #[test]
+    fn player_has_no_affinities_of_their_own() {
+        let player = Player::default();
 
-    fn health(&self) -> u32 {
-        self.health
+        for element in Affinities::ELEMENTS {
+            assert_eq!(player.affinity(element), Affinity::Normal);
+        }
     }
 
-    fn max_health(&self) -> u32 {
-        self.max_health
-    }
+    #[test]
+    fn worn_gear_gives_its_affinities() {
+        let mut player = Player::default();
+        player.add_gear(fire_gear("Ember Robe", EquipSlot::Armor, "Resist"));
+
+        assert!(player.equip(0));
+        assert_eq!(player.affinity(ElementType::Fire), Affinity::Resist);
+
+        assert!(player.unequip(EquipSlot::Armor));
+        assert_eq!(player.affinity(ElementType::Fire), Affinity::Normal);
+    }
+
+    #[test]
+    fn most_protective_gear_wins() {
+        let mut player = Player::default();
+        player.add_gear(fire_gear("Silk Robe", EquipSlot::Armor, "Weak"));
+        player.add_gear(fire_gear("Ember Charm", EquipSlot::Accessory, "Immune"));
+        player.equip(0);
+        assert_eq!(player.affinity(ElementType::Fire), Affinity::Weak);
+
+        // the charm is now first in the bag
+        player.equip(0);
+        assert_eq!(player.affinity(ElementType::Fire), Affinity::Immune);
+    }
+
+    #[test]
+    fn swapping_gear_drops_the_old_affinities() {
+        let mut player = Player::default();
+        player.add_gear(fire_gear("Silk Robe", EquipSlot::Armor, "Weak"));
+        player.add_gear(fire_gear("Ember Robe", EquipSlot::Armor, "Resist"));
+        player.equip(0);
+
+        // the ember robe takes the silk robe's place
+        player.equip(0);
+        assert_eq!(player.affinity(ElementType::Fire), Affinity::Resist);
+    }
+
+    /// Creates a player with 50 max mana and 30 magic strength, out of mana.
+    fn drained_mage() -> Player {
+        let mut player = Player::new(
+            String::from("Mage"),
+            Stats::new(10, 20, 10, 10, 30, 0),
+            1,
+            0,
+        );
+        player.use_mana(player.max_mana());
+        player
+    }
+
+    #[test]
+    fn regen_grows_with_max_mana_and_magic() {
+        let mut player = drained_mage();
+        let mut events = Vec::new();
+
+        // 5% of 50, and 1 for every 10 magic strength
+        assert_eq!(player.mana_regen(), 2 + 3);
+        player.regen_mana(&mut events);
+
+        assert_eq!(player.mana(), 5);
+        assert_eq!(
+            events,
+            [BattleEvent::ManaRegenerated {
+                target: String::from("Mage"),
+                amount: 5,
+            }]
+        );
+    }
+
+    #[test]
+    fn regen_stops_at_max_mana() {
+        let mut player = drained_mage();
+        player.restore_mana(player.max_mana() - 2);
+        let mut events = Vec::new();
+
+        player.regen_mana(&mut events);
+        assert!(player.mana_bar().is_full());
+        player.regen_mana(&mut events);
+
+        assert_eq!(
+            events,
+            [BattleEvent::ManaRegenerated {
+                target: String::from("Mage"),
+                amount: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn kill_restores_some_mana() {
+        let mut player = drained_mage();
+        let mut events = Vec::new();
+
+        player.restore_kill_mana(&mut events);
+
+        // 15% of 50
+        assert_eq!(player.mana(), 7);
+        assert_eq!(
+            events,
+            [BattleEvent::ManaRestored {
+                target: String::from("Mage"),
+                amount: 7,
+            }]
+        );
+    }
+
+    #[test]
+    fn special_move_needs_focus_and_spends_it() {
+        let mut player = Player::default();
+        // sure hits, so each attack builds focus
+        player.stats_mut().push_modifier(
+            ModifierSource::Move(String::from("Aim")),
+            StatModifier::new(StatType::Accuracy, ModifierKind::Flat, 100),
+            None,
+        );
+        let mut enemy = Enemy::new(String::from("Wall"), Stats::new(100, 0, 1, 0, 0, 0), 1);
+        let special = Move::default().with_focus_cost(FOCUS_PER_HIT * 2);
+        let mut rng = create_rng(1);
+        let mana = player.mana();
+
+        assert_eq!(
+            player.magic_move(vec![&mut enemy], &special, &mut rng, &mut Vec::new()),
+            Err(ActionError::NotEnoughFocus {
+                need: FOCUS_PER_HIT * 2,
+                have: 0,
+            })
+        );
+        assert_eq!(player.mana(), mana);
+
+        for _ in 0..2 {
+            player
+                .attack_move(&mut enemy, &mut rng, &mut Vec::new())
+                .unwrap();
+        }
+        assert_eq!(player.focus(), FOCUS_PER_HIT * 2);
 
-    fn allow_move(&mut self) {
-        // we can move again
-        self.has_gone = false;
+        assert!(player
+            .magic_move(vec![&mut enemy], &special, &mut rng, &mut Vec::new())
+            .is_ok());
+        assert_eq!(player.focus(), 0);
+        assert_eq!(player.mana(), mana - special.cost());
     }
 }
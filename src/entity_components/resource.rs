@@ -0,0 +1,198 @@
+// file for the bounded resources of an entity, such as health and mana
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// A resource that goes between 0 and a max, like health or mana.
+///
+/// The current amount can never go past the max or below 0, whatever amounts
+/// it is changed by. Anything healed past the max is lost, unless it is given as a
+/// shield, which soaks up losses before the current amount does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Resource {
+    current: u32,
+    max: u32,
+    #[serde(default)]
+    shield: u32, // soaks up losses before the current amount
+}
+
+impl Resource {
+    /// Creates a full resource.
+    ///
+    /// # Params
+    /// - `max` - The most the resource can hold.
+    pub const fn new(max: u32) -> Self {
+        Self {
+            current: max,
+            max,
+            shield: 0,
+        }
+    }
+
+    /// Gets how much of the resource there is right now.
+    pub fn current(&self) -> u32 {
+        self.current
+    }
+
+    /// Gets the most the resource can hold.
+    pub fn max(&self) -> u32 {
+        self.max
+    }
+
+    /// Gets the shield on top of the resource.
+    pub fn shield(&self) -> u32 {
+        self.shield
+    }
+
+    /// Checks if the resource has run out.
+    pub fn is_empty(&self) -> bool {
+        self.current == 0
+    }
+
+    /// Checks if the resource is at its max.
+    pub fn is_full(&self) -> bool {
+        self.current == self.max
+    }
+
+    /// Gets how full the resource is, from 0 to 1.
+    pub fn fraction(&self) -> f64 {
+        self.current as f64 / self.max.max(1) as f64
+    }
+
+    /// Adds to the resource, stopping at the max.
+    ///
+    /// # Params
+    /// - `amount` - The amount to add.
+    ///
+    /// # Returns
+    /// - How much was actually added.
+    pub fn restore(&mut self, amount: u32) -> u32 {
+        let restored = amount.min(self.max.saturating_sub(self.current));
+        self.current += restored;
+        restored
+    }
+
+    /// Takes from the resource, stopping at 0. The shield is ignored, so this is for
+    /// spending the resource, like paying mana for a move.
+    ///
+    /// # Params
+    /// - `amount` - The amount to take.
+    ///
+    /// # Returns
+    /// - How much was actually taken.
+    pub fn spend(&mut self, amount: u32) -> u32 {
+        let spent = amount.min(self.current);
+        self.current -= spent;
+        spent
+    }
+
+    /// Takes from the resource, with the shield soaking up what it can first.
+    /// This is for losing the resource, like taking damage.
+    ///
+    /// # Params
+    /// - `amount` - The amount to lose.
+    ///
+    /// # Returns
+    /// - How much was taken from the current amount, after the shield.
+    pub fn lose(&mut self, amount: u32) -> u32 {
+        let absorbed = amount.min(self.shield);
+        self.shield -= absorbed;
+        self.spend(amount - absorbed)
+    }
+
+    /// Adds a shield on top of the resource, which soaks up losses before the current amount.
+    ///
+    /// # Params
+    /// - `amount` - The amount of shield to add.
+    pub fn add_shield(&mut self, amount: u32) {
+        self.shield = self.shield.saturating_add(amount);
+    }
+
+    /// Changes the most the resource can hold, lowering the current amount if it
+    /// is now over the max.
+    ///
+    /// # Params
+    /// - `max` - The new max.
+    pub fn set_max(&mut self, max: u32) {
+        self.max = max;
+        self.current = self.current.min(max);
+    }
+
+    /// Fills the resource up to its max.
+    pub fn refill(&mut self) {
+        self.current = self.max;
+    }
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.current, self.max)?;
+
+        // only show the shield while there is one
+        if self.shield > 0 {
+            write!(f, " (+{} shield)", self.shield)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A resource with `current` out of `max`
+    fn resource(current: u32, max: u32) -> Resource {
+        let mut resource = Resource::new(max);
+        resource.spend(max - current);
+        resource
+    }
+
+    #[test]
+    fn restore_stops_at_the_max() {
+        let mut health = resource(8, 10);
+
+        assert_eq!(health.restore(5), 2);
+        assert_eq!(health.current(), 10);
+        assert_eq!(health.restore(5), 0);
+    }
+
+    #[test]
+    fn shield_soaks_up_losses_first() {
+        let mut health = resource(10, 10);
+        health.add_shield(4);
+
+        assert_eq!(health.lose(3), 0);
+        assert_eq!(health.shield(), 1);
+        assert_eq!(health.current(), 10);
+
+        assert_eq!(health.lose(5), 4);
+        assert_eq!(health.shield(), 0);
+        assert_eq!(health.current(), 6);
+    }
+
+    #[test]
+    fn spend_stops_at_zero() {
+        let mut mana = resource(3, 10);
+        mana.add_shield(5);
+
+        // spending ignores the shield, and only takes what there is
+        assert_eq!(mana.spend(7), 3);
+        assert!(mana.is_empty());
+        assert_eq!(mana.shield(), 5);
+        assert_eq!(mana.spend(1), 0);
+    }
+
+    #[test]
+    fn lowering_the_max_lowers_the_current_amount() {
+        let mut health = resource(8, 10);
+
+        health.set_max(5);
+        assert_eq!(health.current(), 5);
+        assert!(health.is_full());
+
+        // raising it again doesn't fill it back up
+        health.set_max(10);
+        assert_eq!(health.current(), 5);
+    }
+}
@@ -1,17 +1,219 @@
-use rand::random;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-const DEFENSE_AMOUNT: u32 = 50;
+use crate::rng::GameRng;
+
+const DEFENSE_AMOUNT: i32 = 50;
+const DEFENSE_TURNS: u32 = 2; // the end of the turn the entity defends on, and the end of its next turn
+const DEFAULT_ACCURACY: u32 = 95;
+const DEFAULT_EVASION: u32 = 5;
+const DEFAULT_CRIT_CHANCE: u32 = 5;
+const DEFAULT_CRIT_DAMAGE: u32 = 150;
+const MIN_HIT_CHANCE: u32 = 10; // even the most evasive entity can be hit sometimes
+const PARRY_CHANCE: f64 = 0.35;
+const BRACE_MANA_PERCENT: u32 = 10; // of the max mana, regained at the end of each turn
+const MANA_REGEN_PERCENT: u32 = 5; // of the max mana, regained at the end of every turn
+const MAGIC_PER_MANA_REGEN: u32 = 10; // magic strength needed for each extra point of regen
+const KILL_MANA_PERCENT: u32 = 15; // of the max mana, regained for each enemy killed
+
+/// How an entity defends. Every stance raises defense, and each has its own extra effect.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DefendStance {
+    #[default]
+    Guard, // blocks the statuses that would be applied
+    Parry, // has a chance to counterattack physical attacks
+    Brace, // regains mana at the end of each turn
+}
+
+impl DefendStance {
+    /// Every stance, in the order they are shown.
+    pub const ALL: [DefendStance; 3] = [
+        DefendStance::Guard,
+        DefendStance::Parry,
+        DefendStance::Brace,
+    ];
+
+    /// Gets the name of a stance, as it is shown to the player.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DefendStance::Guard => "guard",
+            DefendStance::Parry => "parry",
+            DefendStance::Brace => "brace",
+        }
+    }
+
+    /// Gets a stance from its name.
+    ///
+    /// # Params
+    /// - `name` - The name of the stance, such as `"parry"`.
+    ///
+    /// # Returns
+    /// - The `DefendStance`, or `None` if there is no stance with that name.
+    pub fn from_name(name: &str) -> Option<DefendStance> {
+        DefendStance::ALL
+            .into_iter()
+            .find(|stance| stance.name() == name)
+    }
+}
+
+/// A stat that can be changed by a `StatModifier`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatType {
+    Strength,
+    MagicStrength,
+    Defense,
+    Speed,
+    MaxHealth,
+    MaxMana,
+    Accuracy,
+    Evasion,
+    CritChance,
+    CritDamage,
+}
+
+/// How a `StatModifier` changes its stat.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModifierKind {
+    Flat,    // adds the amount to the stat
+    Percent, // multiplies the stat, adding the amount as a percentage of it
+}
+
+/// A change to one stat, such as from a piece of equipment or a spell.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatModifier {
+    stat: StatType,
+    kind: ModifierKind,
+    amount: i32, // can be negative, to lower the stat
+}
+
+impl StatModifier {
+    /// Create a new `StatModifier`.
+    ///
+    /// # Params
+    /// - `stat` - The stat to change.
+    /// - `kind` - Whether the amount is flat or a percentage.
+    /// - `amount` - How much to change the stat by.
+    pub fn new(stat: StatType, kind: ModifierKind, amount: i32) -> Self {
+        Self { stat, kind, amount }
+    }
+
+    pub fn stat(&self) -> StatType {
+        self.stat
+    }
+
+    pub fn kind(&self) -> ModifierKind {
+        self.kind
+    }
+
+    pub fn amount(&self) -> i32 {
+        self.amount
+    }
+}
+
+/// Applies modifiers to the base value of a stat. Flat modifiers are added
+/// first, then the percentages are applied to the result.
+///
+/// # Params
+/// - `stat` - The stat being modified.
+/// - `base` - The value of the stat without any modifiers.
+/// - `modifiers` - The modifiers to apply, which are skipped if they are for other stats.
+///
+/// # Returns
+/// - The modified value of the stat, which is never below 0.
+pub fn apply_modifiers<'a>(
+    stat: StatType,
+    base: u32,
+    modifiers: impl IntoIterator<Item = &'a StatModifier>,
+) -> u32 {
+    let mut flat = 0_i64;
+    let mut percent = 0_i64;
+
+    for modifier in modifiers {
+        if modifier.stat == stat {
+            match modifier.kind {
+                ModifierKind::Flat => flat += modifier.amount as i64,
+                ModifierKind::Percent => percent += modifier.amount as i64,
+            }
+        }
+    }
+
+    let value = (base as i64 + flat) * (100 + percent) / 100;
+    value.clamp(0, u32::MAX as i64) as u32
+}
+
+/// Where a modifier on an entity's stats came from, so it can be found again.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModifierSource {
+    Defending,
+    Equipment(String), // the name of the gear
+    Status(String),    // the name of the status
+    Move(String),      // the name of the move
+}
+
+/// A modifier that has been pushed onto an entity's stats.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ActiveModifier {
+    source: ModifierSource,
+    modifier: StatModifier,
+    turns_left: Option<u32>, // `None` lasts until the modifier is removed by its source
+}
+
+impl ActiveModifier {
+    pub fn source(&self) -> &ModifierSource {
+        &self.source
+    }
+
+    pub fn modifier(&self) -> &StatModifier {
+        &self.modifier
+    }
+
+    pub fn turns_left(&self) -> Option<u32> {
+        self.turns_left
+    }
+}
 
 ///struct for the stats of an entity
-#[derive(Clone)]
+///
+///The stats themselves are the base stats of the entity, which only change when
+///it levels up. Everything else that changes a stat (defending, statuses, equipment
+///and spells) pushes a modifier, and the getters apply them.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Stats {
     health: u32,
     mana: u32,
-    speed: u32, // TODO: might remove
+    speed: u32, // how quickly the entity gets its turns, see `battle::initiative`
     strength: u32,
     magic_strength: u32,
     defense: u32,
-    is_defending: bool, // if the entity is defending (this value is here so we can change the other stats)
+    #[serde(default = "default_accuracy")]
+    accuracy: u32, // percent chance to hit, before the target's evasion
+    #[serde(default = "default_evasion")]
+    evasion: u32, // taken off the accuracy of attacks against the entity
+    #[serde(default = "default_crit_chance")]
+    crit_chance: u32, // percent chance for an attack to be a critical hit
+    #[serde(default = "default_crit_damage")]
+    crit_damage: u32, // percent of the damage a critical hit does
+    #[serde(default)]
+    modifiers: Vec<ActiveModifier>, // temporary and equipment changes to the stats
+    #[serde(default)]
+    stance: Option<DefendStance>, // how the entity is defending, if it is
+}
+
+fn default_accuracy() -> u32 {
+    DEFAULT_ACCURACY
+}
+
+fn default_evasion() -> u32 {
+    DEFAULT_EVASION
+}
+
+fn default_crit_chance() -> u32 {
+    DEFAULT_CRIT_CHANCE
+}
+
+fn default_crit_damage() -> u32 {
+    DEFAULT_CRIT_DAMAGE
 }
 
 impl Stats {
@@ -31,12 +233,19 @@ impl Stats {
             strength,
             magic_strength,
             defense,
-            is_defending: false, // always start off not defending
+            accuracy: DEFAULT_ACCURACY,
+            evasion: DEFAULT_EVASION,
+            crit_chance: DEFAULT_CRIT_CHANCE,
+            crit_damage: DEFAULT_CRIT_DAMAGE,
+            modifiers: Vec::new(), // always start off without modifiers
+            stance: None,
         }
     }
+}
 
+impl Default for Stats {
     /// Create a default Stats object
-    pub fn default() -> Self {
+    fn default() -> Self {
         Self {
             health: 10,
             mana: 10,
@@ -44,54 +253,231 @@ impl Stats {
             strength: 10,
             magic_strength: 10,
             defense: 0,
-            is_defending: false,
+            accuracy: DEFAULT_ACCURACY,
+            evasion: DEFAULT_EVASION,
+            crit_chance: DEFAULT_CRIT_CHANCE,
+            crit_damage: DEFAULT_CRIT_DAMAGE,
+            modifiers: Vec::new(),
+            stance: None,
         }
     }
+}
 
+impl Stats {
     ///Generates the health of the entity
     pub fn calculate_max_health(&self) -> u32 {
         //TODO: change this with
-        (self.health as f64 * 5.5) as u32
+        self.modify(StatType::MaxHealth, (self.health as f64 * 5.5) as u32)
     }
 
     ///Generates the mana of the entity
     pub fn calculate_max_mana(&self) -> u32 {
         //TODO: change this
-        (self.mana as f64 * 2.5) as u32
+        self.modify(StatType::MaxMana, (self.mana as f64 * 2.5) as u32)
     }
 
     ///Generates random attack damage based on stats
-    pub fn generate_random_attack_dmg(&self) -> u32 {
-        // TODO: random crits?
+    ///
+    /// # Params
+    /// - `rng` - The random number generator of the game.
+    pub fn generate_random_attack_dmg(&self, rng: &mut GameRng) -> u32 {
+        let strength = self.get_strength();
 
         // strength + (random number between 0 and strength / 2)
-        self.strength + (random::<u32>() % (self.strength / 2))
+        strength + rng.gen_range(0..(strength / 2).max(1))
+    }
+
+    /// Rolls to see if an attack with these stats hits its target.
+    ///
+    /// # Params
+    /// - `target` - The stats of the entity being attacked.
+    /// - `rng` - The random number generator of the game.
+    ///
+    /// # Returns
+    /// - `true` if the attack hits, `false` if it misses.
+    pub fn roll_hit(&self, target: &Stats, rng: &mut GameRng) -> bool {
+        let hit_chance = self
+            .get_accuracy()
+            .saturating_sub(target.get_evasion())
+            .clamp(MIN_HIT_CHANCE, 100);
+
+        rng.gen_range(1..=100) <= hit_chance
+    }
+
+    /// Rolls to see if an attack with these stats is a critical hit.
+    ///
+    /// # Params
+    /// - `rng` - The random number generator of the game.
+    pub fn roll_crit(&self, rng: &mut GameRng) -> bool {
+        rng.gen_range(1..=100) <= self.get_crit_chance()
+    }
+
+    /// Gets the damage of a critical hit with these stats.
+    ///
+    /// # Params
+    /// - `damage` - The damage of the attack before the critical hit.
+    pub fn calc_crit_damage(&self, damage: u32) -> u32 {
+        // widened so huge crit damage from gear can't overflow, then capped back down
+        let crit_damage = damage as u64 * self.get_crit_damage() as u64 / 100;
+        crit_damage.min(u32::MAX as u64) as u32
     }
 
     pub fn calc_damage_taken(&self, damage_amount: u32) -> u32 {
-        (damage_amount as f64 - ((self.defense as f64 / 100.0) * damage_amount as f64)) as u32
+        let defense = self.get_defense() as f64;
+        (damage_amount as f64 - ((defense / 100.0) * damage_amount as f64)) as u32
+    }
+
+    pub fn get_strength(&self) -> u32 {
+        self.modify(StatType::Strength, self.strength)
+    }
+
+    pub fn get_defense(&self) -> u32 {
+        self.modify(StatType::Defense, self.defense)
     }
 
     pub fn get_speed(&self) -> u32 {
-        self.speed
+        self.modify(StatType::Speed, self.speed)
     }
 
     pub fn get_magic_strength(&self) -> u32 {
-        self.magic_strength
+        self.modify(StatType::MagicStrength, self.magic_strength)
+    }
+
+    pub fn get_accuracy(&self) -> u32 {
+        self.modify(StatType::Accuracy, self.accuracy)
+    }
+
+    pub fn get_evasion(&self) -> u32 {
+        self.modify(StatType::Evasion, self.evasion)
+    }
+
+    pub fn get_crit_chance(&self) -> u32 {
+        self.modify(StatType::CritChance, self.crit_chance)
+    }
+
+    pub fn get_crit_damage(&self) -> u32 {
+        self.modify(StatType::CritDamage, self.crit_damage)
+    }
+
+    /// Applies the modifiers to the base value of a stat.
+    fn modify(&self, stat: StatType, base: u32) -> u32 {
+        apply_modifiers(
+            stat,
+            base,
+            self.modifiers.iter().map(|active| &active.modifier),
+        )
+    }
+
+    /// Pushes a modifier onto these stats.
+    ///
+    /// # Params
+    /// - `source` - Where the modifier came from.
+    /// - `modifier` - The change to the stat.
+    /// - `turns` - How many of the entity's turns the modifier lasts, counting the end of
+    ///   the current turn if it is the entity's own. `None` lasts until it is removed.
+    pub fn push_modifier(
+        &mut self,
+        source: ModifierSource,
+        modifier: StatModifier,
+        turns: Option<u32>,
+    ) {
+        self.modifiers.push(ActiveModifier {
+            source,
+            modifier,
+            turns_left: turns,
+        });
     }
 
-    pub fn start_defending(&mut self) {
-        if !self.is_defending {
-            self.defense += DEFENSE_AMOUNT;
-            self.is_defending = true;
+    /// Removes every modifier from a source.
+    ///
+    /// # Params
+    /// - `source` - The source of the modifiers to remove.
+    pub fn remove_modifiers(&mut self, source: &ModifierSource) {
+        self.modifiers.retain(|active| active.source != *source);
+
+        if *source == ModifierSource::Defending {
+            self.stance = None;
         }
     }
 
-    pub fn stop_defending(&mut self) {
-        if self.is_defending {
-            self.defense -= DEFENSE_AMOUNT;
-            self.is_defending = false;
+    /// Gets the modifiers on these stats.
+    pub fn modifiers(&self) -> &[ActiveModifier] {
+        &self.modifiers
+    }
+
+    /// Counts down the modifiers at the end of the entity's turn, removing those that run out.
+    ///
+    /// # Returns
+    /// - The sources of the modifiers that ran out, without repeats.
+    pub fn tick_modifiers(&mut self) -> Vec<ModifierSource> {
+        let mut expired: Vec<ModifierSource> = Vec::new();
+
+        self.modifiers
+            .retain_mut(|active| match &mut active.turns_left {
+                Some(turns_left) => {
+                    *turns_left = turns_left.saturating_sub(1);
+                    if *turns_left == 0 && !expired.contains(&active.source) {
+                        expired.push(active.source.clone());
+                    }
+                    *turns_left > 0
+                }
+                None => true,
+            });
+
+        if expired.contains(&ModifierSource::Defending) {
+            self.stance = None;
         }
+
+        expired
+    }
+
+    /// Starts defending in a stance, until the end of the entity's next turn.
+    ///
+    /// # Params
+    /// - `stance` - How the entity defends.
+    pub fn start_defending(&mut self, stance: DefendStance) {
+        // defending again refreshes it, rather than stacking
+        self.remove_modifiers(&ModifierSource::Defending);
+        self.push_modifier(
+            ModifierSource::Defending,
+            StatModifier::new(StatType::Defense, ModifierKind::Flat, DEFENSE_AMOUNT),
+            Some(DEFENSE_TURNS),
+        );
+        self.stance = Some(stance);
+    }
+
+    /// Gets the stance the entity is defending in, or `None` if it is not defending.
+    pub fn stance(&self) -> Option<DefendStance> {
+        self.stance
+    }
+
+    /// Rolls to see if the entity counterattacks a physical attack.
+    ///
+    /// # Params
+    /// - `rng` - The random number generator of the game.
+    ///
+    /// # Returns
+    /// - `true` if the entity is parrying and the roll succeeded, `false` otherwise.
+    pub fn roll_parry(&self, rng: &mut GameRng) -> bool {
+        self.stance == Some(DefendStance::Parry) && rng.gen_bool(PARRY_CHANCE)
+    }
+
+    /// Gets the mana the entity regains at the end of a turn while bracing.
+    pub fn calc_brace_mana(&self) -> u32 {
+        (self.calculate_max_mana() * BRACE_MANA_PERCENT / 100).max(1)
+    }
+
+    /// Gets the mana the entity regains at the end of every turn, which grows with
+    /// its max mana and its magic strength.
+    pub fn calc_mana_regen(&self) -> u32 {
+        (self.calculate_max_mana() * MANA_REGEN_PERCENT / 100
+            + self.get_magic_strength() / MAGIC_PER_MANA_REGEN)
+            .max(1)
+    }
+
+    /// Gets the mana the entity regains for killing an enemy.
+    pub fn calc_kill_mana(&self) -> u32 {
+        (self.calculate_max_mana() * KILL_MANA_PERCENT / 100).max(1)
     }
 
     pub fn increase_physical(&mut self) {
@@ -107,3 +493,124 @@ impl Stats {
         self.health += 1;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crit_damage_is_a_percent_of_the_damage() {
+        let stats = Stats::default();
+
+        assert_eq!(stats.calc_crit_damage(10), 15);
+        assert_eq!(stats.calc_crit_damage(0), 0);
+    }
+
+    #[test]
+    fn crit_damage_does_not_overflow() {
+        let mut stats = Stats::default();
+        stats.push_modifier(
+            ModifierSource::Equipment(String::from("Giant Axe")),
+            StatModifier::new(StatType::CritDamage, ModifierKind::Flat, i32::MAX),
+            None,
+        );
+
+        assert_eq!(stats.calc_crit_damage(u32::MAX / 2), u32::MAX);
+    }
+
+    fn modifier(stat: StatType, kind: ModifierKind, amount: i32) -> StatModifier {
+        StatModifier::new(stat, kind, amount)
+    }
+
+    #[test]
+    fn flat_modifiers_apply_before_percents() {
+        let modifiers = [
+            modifier(StatType::Strength, ModifierKind::Percent, 50),
+            modifier(StatType::Strength, ModifierKind::Flat, 10),
+            modifier(StatType::Defense, ModifierKind::Flat, 100),
+        ];
+
+        // (10 + 10) * 1.5, rather than 10 * 1.5 + 10
+        assert_eq!(apply_modifiers(StatType::Strength, 10, &modifiers), 30);
+    }
+
+    #[test]
+    fn modifiers_run_out_after_their_turns() {
+        let mut stats = Stats::default();
+        let spell = ModifierSource::Move(String::from("Bulk"));
+        stats.push_modifier(
+            spell.clone(),
+            modifier(StatType::Defense, ModifierKind::Flat, 20),
+            Some(2),
+        );
+        stats.push_modifier(
+            ModifierSource::Equipment(String::from("Iron Helm")),
+            modifier(StatType::Defense, ModifierKind::Flat, 5),
+            None,
+        );
+
+        assert!(stats.tick_modifiers().is_empty());
+        assert_eq!(stats.get_defense(), 25);
+
+        assert_eq!(stats.tick_modifiers(), [spell]);
+        assert_eq!(stats.get_defense(), 5);
+        assert!(stats.tick_modifiers().is_empty());
+    }
+
+    #[test]
+    fn removing_a_source_keeps_the_others() {
+        let mut stats = Stats::default();
+        let helm = ModifierSource::Equipment(String::from("Iron Helm"));
+        stats.push_modifier(
+            helm.clone(),
+            modifier(StatType::Defense, ModifierKind::Flat, 5),
+            None,
+        );
+        stats.push_modifier(
+            helm.clone(),
+            modifier(StatType::Speed, ModifierKind::Flat, -2),
+            None,
+        );
+        stats.push_modifier(
+            ModifierSource::Status(String::from("Haste")),
+            modifier(StatType::Speed, ModifierKind::Flat, 4),
+            Some(3),
+        );
+
+        stats.remove_modifiers(&helm);
+
+        assert_eq!(stats.modifiers().len(), 1);
+        assert_eq!(stats.get_defense(), 0);
+        assert_eq!(stats.get_speed(), 14);
+    }
+
+    #[test]
+    fn stats_never_go_below_zero() {
+        let mut stats = Stats::new(10, 10, 10, 10, 10, 5);
+        stats.push_modifier(
+            ModifierSource::Status(String::from("Corrode")),
+            modifier(StatType::Defense, ModifierKind::Flat, -20),
+            Some(2),
+        );
+
+        assert_eq!(stats.get_defense(), 0);
+        assert_eq!(stats.calc_damage_taken(10), 10);
+    }
+
+    #[test]
+    fn defending_wears_off_at_the_end_of_the_next_turn() {
+        let mut stats = Stats::default();
+        stats.start_defending(DefendStance::Brace);
+        stats.start_defending(DefendStance::Parry);
+
+        // defending again refreshes the stance rather than stacking
+        assert_eq!(stats.get_defense(), DEFENSE_AMOUNT as u32);
+        assert_eq!(stats.stance(), Some(DefendStance::Parry));
+
+        stats.tick_modifiers();
+        assert_eq!(stats.stance(), Some(DefendStance::Parry));
+        assert_eq!(stats.tick_modifiers(), [ModifierSource::Defending]);
+        assert_eq!(stats.stance(), None);
+        assert_eq!(stats.get_defense(), 0);
+    }
+}
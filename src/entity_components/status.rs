@@ -1,13 +1,67 @@
 // file for status effects implementation
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-/// Very basic status effect struct
-#[derive(Clone)]
+use crate::entity_components::stats::StatModifier;
+use crate::rng::GameRng;
+
+/// What happens when a status is applied to an entity that already has it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "rule", deny_unknown_fields)]
+pub enum Stacking {
+    #[default]
+    Refresh, // the status starts over with its full duration
+    Stack {
+        max_stacks: u32, // the most times the status can build up
+    }, // the status gets more intense, and starts over with its full duration
+    Ignore, // the new status is ignored
+}
+
+/// Something a status does when an event happens to the entity that has it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", deny_unknown_fields)]
+pub enum StatusTrigger {
+    Thorns { percent: u32 }, // deals a percentage of the damage from an attack back to the attacker
+}
+
+/// What happened when a status was added to a list of statuses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusAdded {
+    Applied,
+    Refreshed,
+    Stacked(u32), // how many stacks the status now has
+    Ignored,
+}
+
+/// A status effect.
+///
+/// The statuses loaded from the data files are templates. A caster applies an instance
+/// of one made with `cast_by`, which remembers who applied it and how strong they were.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Status {
     name: String,
-    base_amount: u32,
+    base_amount: u32, // 0 if the status does not damage or heal each turn
     is_healing: bool,
     magic_strength_when_applied: u32, // the magic strength of the caster when the status was applied
     num_turns: u32,                   // duration of effect
+    #[serde(default)]
+    source: Option<String>, // the name of the entity that applied the status, if any
+    #[serde(default)]
+    modifiers: Vec<StatModifier>, // changes to the stats of the entity for as long as the status lasts
+    #[serde(default)]
+    stacking: Stacking,
+    #[serde(default = "default_stacks")]
+    stacks: u32, // how intense the status is, which multiplies its amount
+    #[serde(default)]
+    skips_turn: bool, // if the entity can't act, such as when stunned
+    #[serde(default)]
+    blocks_magic: bool, // if the entity can't cast magic, such as when silenced
+    #[serde(default)]
+    triggers: Vec<StatusTrigger>,
+}
+
+fn default_stacks() -> u32 {
+    1
 }
 
 impl Status {
@@ -25,21 +79,168 @@ impl Status {
             is_healing,
             magic_strength_when_applied,
             num_turns,
+            source: None,
+            modifiers: Vec::new(),
+            stacking: Stacking::default(),
+            stacks: default_stacks(),
+            skips_turn: false,
+            blocks_magic: false,
+            triggers: Vec::new(),
+        }
+    }
+
+    /// Builder function for setting how a status changes the stats of the entity it is applied to.
+    ///
+    /// # Params
+    /// - `modifiers` - The changes to the stats, which last as long as the status.
+    ///
+    /// # Returns
+    /// - The `Status` object.
+    pub fn with_modifiers(mut self, modifiers: Vec<StatModifier>) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    /// Builder function for setting what happens when a status is applied more than once.
+    ///
+    /// # Params
+    /// - `stacking` - The stacking rule of the status.
+    ///
+    /// # Returns
+    /// - The `Status` object.
+    pub fn with_stacking(mut self, stacking: Stacking) -> Self {
+        self.stacking = stacking;
+        self
+    }
+
+    /// Builder function for setting what a status stops the entity from doing.
+    ///
+    /// # Params
+    /// - `skips_turn` - If the entity skips its turns while it has the status.
+    /// - `blocks_magic` - If the entity can't cast magic while it has the status.
+    ///
+    /// # Returns
+    /// - The `Status` object.
+    pub fn with_restrictions(mut self, skips_turn: bool, blocks_magic: bool) -> Self {
+        self.skips_turn = skips_turn;
+        self.blocks_magic = blocks_magic;
+        self
+    }
+
+    /// Builder function for setting what a status does when events happen to its entity.
+    ///
+    /// # Params
+    /// - `triggers` - The triggers of the status.
+    ///
+    /// # Returns
+    /// - The `Status` object.
+    pub fn with_triggers(mut self, triggers: Vec<StatusTrigger>) -> Self {
+        self.triggers = triggers;
+        self
+    }
+
+    /// Creates an instance of this status, applied by an entity.
+    ///
+    /// # Params
+    /// - `caster` - The name of the entity applying the status.
+    /// - `magic_strength` - The magic strength of the caster, which the status scales with.
+    ///
+    /// # Returns
+    /// - The new `Status`.
+    pub fn cast_by(&self, caster: String, magic_strength: u32) -> Status {
+        let mut status = self.clone();
+        status.source = Some(caster);
+        status.magic_strength_when_applied = magic_strength;
+        status
+    }
+
+    /// Adds this status to a list of statuses, following its stacking rule
+    /// if the list already has it.
+    ///
+    /// # Params
+    /// - `statuses` - The statuses of an entity.
+    ///
+    /// # Returns
+    /// - What happened to the status.
+    pub fn add_to(&self, statuses: &mut Vec<Status>) -> StatusAdded {
+        let existing = match statuses.iter_mut().find(|status| status.name == self.name) {
+            Some(existing) => existing,
+            None => {
+                statuses.push(self.clone());
+                return StatusAdded::Applied;
+            }
+        };
+
+        match self.stacking {
+            Stacking::Refresh => {
+                existing.num_turns = self.num_turns;
+                existing.magic_strength_when_applied = self.magic_strength_when_applied;
+                existing.source = self.source.clone();
+                StatusAdded::Refreshed
+            }
+            Stacking::Stack { max_stacks } => {
+                // the latest caster takes over the whole stack
+                existing.num_turns = self.num_turns;
+                existing.magic_strength_when_applied = self.magic_strength_when_applied;
+                existing.source = self.source.clone();
+                existing.stacks = (existing.stacks + 1).min(max_stacks.max(1));
+                StatusAdded::Stacked(existing.stacks)
+            }
+            Stacking::Ignore => StatusAdded::Ignored,
         }
     }
 
-    /// Calculates the amount of damange/health the status does.
+    /// Calculates the amount of damange/health the status does. The whole amount,
+    /// including the caster's magic strength and the random roll, is multiplied by the stacks.
     ///
     /// # Params
-    /// - `magic_strength` - The magic strength of the entity that caused the status
+    /// - `rng` - The random number generator of the game.
     ///
     /// # Returns
     /// - A u32 containing the amount healed/damage done.
-    pub fn calculate_amount(&self) -> u32 {
+    pub fn calculate_amount(&self, rng: &mut GameRng) -> u32 {
         // TODO: balance
-        self.magic_strength_when_applied
+        // half of the caster's magic strength, since a status hits every turn
+        let magic_strength = self.magic_strength_when_applied / 2;
+        let amount = magic_strength
             + self.base_amount
-            + (rand::random::<u32>() % (self.magic_strength_when_applied + self.base_amount / 2))
+            + rng.gen_range(0..(magic_strength + self.base_amount / 2).max(1));
+
+        amount * self.stacks
+    }
+
+    /// Checks if this status damages or heals its entity each turn.
+    pub fn has_amount(&self) -> bool {
+        self.base_amount > 0
+    }
+
+    /// Checks if this status hinders its entity, by hurting it, stopping it from acting
+    /// or lowering one of its stats.
+    pub fn is_harmful(&self) -> bool {
+        (self.has_amount() && !self.is_healing)
+            || self.skips_turn
+            || self.blocks_magic
+            || self.modifiers.iter().any(|modifier| modifier.amount() < 0)
+    }
+
+    /// Calculates the damage dealt back to an attacker by this status.
+    ///
+    /// # Params
+    /// - `damage_taken` - The damage the attack did to the entity with this status.
+    ///
+    /// # Returns
+    /// - The damage to deal to the attacker, which is 0 if this status has no thorns.
+    pub fn thorns_damage(&self, damage_taken: u32) -> u32 {
+        self.triggers
+            .iter()
+            .map(|trigger| match trigger {
+                // widened so a huge hit can't overflow, then capped back down
+                StatusTrigger::Thorns { percent } => {
+                    (damage_taken as u64 * *percent as u64 / 100).min(u32::MAX as u64) as u32
+                }
+            })
+            .fold(0, u32::saturating_add)
+            .saturating_mul(self.stacks)
     }
 
     /// Ticks the status effect, marking the end of the
@@ -48,7 +249,8 @@ impl Status {
     /// # Returns
     /// - `true` if the status has no turns left, `false` otherwise
     pub fn tick(&mut self) -> bool {
-        self.num_turns -= 1;
+        // a status with no turns left is already over, rather than wrapping around
+        self.num_turns = self.num_turns.saturating_sub(1);
 
         self.num_turns == 0
     }
@@ -69,36 +271,160 @@ impl Status {
         self.name.clone()
     }
 
-    pub fn status_chance() -> f64 {
-        0.2
+    /// Gets the name of the entity that applied this status, or `None` if nothing did.
+    pub fn source(&self) -> Option<String> {
+        self.source.clone()
     }
 
-    /// Create the status list for the game.
-    ///
-    /// # Returns
-    /// - The full status list for the game
-    pub fn create_status_list() -> Vec<Status> {
-        vec![
-            Status::new(String::from("Burn"), 10, false, 0, 5),
-            Status::new(String::from("Frostburn"), 12, false, 0, 5),
-        ]
+    /// Gets how many more of its owner's turns this status lasts.
+    pub fn turns_left(&self) -> u32 {
+        self.num_turns
+    }
+
+    /// Gets how many times this status has built up.
+    pub fn stacks(&self) -> u32 {
+        self.stacks
+    }
+
+    /// Checks if this status makes its entity skip its turns.
+    pub fn skips_turn(&self) -> bool {
+        self.skips_turn
+    }
+
+    /// Checks if this status stops its entity from casting magic.
+    pub fn blocks_magic(&self) -> bool {
+        self.blocks_magic
+    }
+
+    /// Gets how this status changes the stats of the entity it is applied to.
+    pub fn modifiers(&self) -> &[StatModifier] {
+        &self.modifiers
+    }
+
+    pub fn status_chance() -> f64 {
+        0.2
     }
 
     /// Searches for a `Status` in a `Status` list vector.
     ///
     /// # Returns
     /// - The `Status` that was found based on the name, or `None` if no `Status` was found.
-    pub fn get_status_from(target_name: &str, status_list: &Vec<Status>) -> Option<Status> {
-        let mut result: Option<Status> = None;
-
+    pub fn get_status_from(target_name: &str, status_list: &[Status]) -> Option<Status> {
         // go through the status list and find the one that matches our target string
-        for i in 0..status_list.len() {
-            if target_name == status_list[i].name() {
-                result = Some(status_list[i].clone());
-                break;
-            }
-        }
+        status_list
+            .iter()
+            .find(|status| status.name == target_name)
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::create_rng;
+
+    const SEED: u64 = 7;
+
+    fn poison(stacking: Stacking) -> Status {
+        Status::new(String::from("Poison"), 4, false, 0, 4).with_stacking(stacking)
+    }
+
+    #[test]
+    fn new_status_is_applied() {
+        let mut statuses = Vec::new();
+
+        assert_eq!(
+            poison(Stacking::Refresh).add_to(&mut statuses),
+            StatusAdded::Applied
+        );
+        assert_eq!(statuses.len(), 1);
+    }
+
+    #[test]
+    fn refresh_starts_the_status_over() {
+        let status = poison(Stacking::Refresh);
+        let mut statuses = vec![status.cast_by(String::from("Spider"), 2)];
+        statuses[0].tick();
+
+        let added = status
+            .cast_by(String::from("Louie"), 10)
+            .add_to(&mut statuses);
+
+        assert_eq!(added, StatusAdded::Refreshed);
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].turns_left(), 4);
+        assert_eq!(statuses[0].stacks(), 1);
+        assert_eq!(statuses[0].source().as_deref(), Some("Louie"));
+    }
+
+    #[test]
+    fn stack_builds_up_to_its_max() {
+        let status = poison(Stacking::Stack { max_stacks: 3 });
+        let mut statuses = vec![status.clone()];
+        statuses[0].tick();
+
+        let added: Vec<StatusAdded> = (0..3).map(|_| status.add_to(&mut statuses)).collect();
+
+        assert_eq!(
+            added,
+            [
+                StatusAdded::Stacked(2),
+                StatusAdded::Stacked(3),
+                StatusAdded::Stacked(3)
+            ]
+        );
+        assert_eq!(statuses[0].turns_left(), 4);
+    }
+
+    #[test]
+    fn ignore_keeps_the_status_as_it_is() {
+        let status = poison(Stacking::Ignore);
+        let mut statuses = vec![status.clone()];
+        statuses[0].tick();
+
+        assert_eq!(status.add_to(&mut statuses), StatusAdded::Ignored);
+        assert_eq!(statuses[0].turns_left(), 3);
+        assert_eq!(statuses[0].stacks(), 1);
+    }
+
+    #[test]
+    fn stacks_multiply_the_whole_amount() {
+        let status = poison(Stacking::Stack { max_stacks: 3 }).cast_by(String::from("Louie"), 10);
+        let mut statuses = vec![status.clone()];
+        status.add_to(&mut statuses);
+        status.add_to(&mut statuses);
+
+        // the same roll, with one stack and with three
+        let single = status.calculate_amount(&mut create_rng(SEED));
+        let stacked = statuses[0].calculate_amount(&mut create_rng(SEED));
+
+        assert!(single > status.base_amount);
+        assert_eq!(stacked, single * 3);
+    }
+
+    #[test]
+    fn status_runs_out_of_turns() {
+        let mut status = poison(Stacking::Refresh);
+
+        let ticks: Vec<bool> = (0..5).map(|_| status.tick()).collect();
+        assert_eq!(ticks, [false, false, false, true, true]);
+    }
+
+    #[test]
+    fn thorns_deal_back_a_percent_of_the_damage() {
+        let thorns = Status::new(String::from("Thorns"), 0, false, 0, 3)
+            .with_triggers(vec![StatusTrigger::Thorns { percent: 50 }]);
+
+        assert_eq!(thorns.thorns_damage(10), 5);
+        assert_eq!(thorns.thorns_damage(u32::MAX), u32::MAX / 2);
+        assert_eq!(poison(Stacking::Refresh).thorns_damage(10), 0);
+    }
+
+    #[test]
+    fn zero_turn_status_is_already_over() {
+        let mut status = Status::new(String::from("Flash"), 1, false, 0, 0);
 
-        result
+        assert!(status.tick());
+        assert_eq!(status.turns_left(), 0);
     }
 }
@@ -0,0 +1,150 @@
+// file for turning battle events into text for the battle log
+use starstruck::battle::event::BattleEvent;
+use starstruck::entity_components::affinity::Affinity;
+use starstruck::entity_components::moves::MoveType;
+use starstruck::entity_components::stats::ModifierSource;
+
+/// Creates the battle log text for an event.
+///
+/// # Params
+/// - `event` - The event that happened in the battle.
+///
+/// # Returns
+/// - The text to show the player for this event.
+pub fn event_text(event: &BattleEvent) -> String {
+    match event {
+        BattleEvent::MagicCast { caster, move_name } => format!("{} cast {}!", caster, move_name),
+        BattleEvent::Clash {
+            winner,
+            winning_move,
+            loser,
+            ..
+        } => match winning_move {
+            MoveType::DefendMove => format!("{} blocked {}'s attack!", winner, loser),
+            MoveType::AttackMove => format!("{} interrupted {}'s spell!", winner, loser),
+            MoveType::MagicMove => format!("{}'s magic pierced {}'s guard!", winner, loser),
+            MoveType::ItemMove => format!("{} outplayed {}!", winner, loser),
+        },
+        BattleEvent::AttackMissed { attacker, target } => {
+            format!("Miss! {}'s attack missed {}", attacker, target)
+        }
+        BattleEvent::CriticalHit { .. } => String::from("Critical hit!"),
+        BattleEvent::DamageDealt {
+            attacker,
+            target,
+            amount,
+        } => format!("{} did {} damage to {}", attacker, amount, target),
+        BattleEvent::ElementalHit {
+            target,
+            element,
+            affinity,
+        } => match affinity {
+            Affinity::Weak => String::from("It's super effective!"),
+            Affinity::Normal => String::new(),
+            Affinity::Resist => String::from("It's not very effective..."),
+            Affinity::Immune => format!("{} is immune to {:?}!", target, element),
+            Affinity::Absorb => format!("{} absorbed the {:?}!", target, element),
+        },
+        BattleEvent::Healed { target, amount } => format!("{} healed {} health", target, amount),
+        BattleEvent::ManaRestored { target, amount } => {
+            format!("{} restored {} mana", target, amount)
+        }
+        // these happen every turn, so they are shown in the player panel instead
+        BattleEvent::ManaRegenerated { .. } | BattleEvent::FocusGained { .. } => String::new(),
+        BattleEvent::ItemUsed { user, item } => format!("{} used a {}!", user, item),
+        BattleEvent::ItemDropped { source, item } => format!("{} dropped a {}!", source, item),
+        BattleEvent::ManaDrained {
+            caster,
+            target,
+            amount,
+        } => format!("{} drained {} mana from {}", caster, amount, target),
+        BattleEvent::Shielded { target, amount } => {
+            format!("{} is shielded from the next {} damage", target, amount)
+        }
+        BattleEvent::StatusCured { status, target } => {
+            format!("{} was cured of {}", target, status)
+        }
+        BattleEvent::StatModified {
+            target,
+            stat,
+            is_raised,
+        } => {
+            let direction = if *is_raised { "rose" } else { "fell" };
+            format!("{}'s {:?} {}!", target, stat, direction)
+        }
+        BattleEvent::ModifierExpired { source, target } => match source {
+            ModifierSource::Defending => format!("{} stopped defending", target),
+            ModifierSource::Equipment(name)
+            | ModifierSource::Status(name)
+            | ModifierSource::Move(name) => format!("{}'s {} wore off", target, name),
+        },
+        BattleEvent::Defending {
+            name,
+            stance,
+            num_turns,
+        } => format!(
+            "{} began to {} for {} turn(s).",
+            name,
+            stance.name(),
+            num_turns
+        ),
+        BattleEvent::Countered {
+            attacker,
+            target,
+            amount,
+        } => format!(
+            "{} parried and countered {} for {} damage!",
+            attacker, target, amount
+        ),
+        BattleEvent::StatusBlocked { status, target } => {
+            format!("{} guarded against {}!", target, status)
+        }
+        BattleEvent::StatusStacked {
+            status,
+            target,
+            stacks,
+        } => format!("{}'s {} built up to x{}", target, status, stacks),
+        BattleEvent::StatusTriggered {
+            status,
+            owner,
+            target,
+            amount,
+        } => format!(
+            "{}'s {} dealt {} damage to {}",
+            owner, status, amount, target
+        ),
+        BattleEvent::TurnSkipped { name, status } => {
+            format!("{} can't move because of {}!", name, status)
+        }
+        BattleEvent::StatusApplied { status, target } => {
+            format!("{} applied to {}", status, target)
+        }
+        BattleEvent::StatusTicked {
+            status,
+            source,
+            target,
+            amount,
+            is_healing,
+        } => {
+            // name whoever applied the status, so the log shows where the damage came from
+            let status = match source {
+                Some(source) => format!("{}'s {}", source, status),
+                None => status.clone(),
+            };
+            if *is_healing {
+                format!("{} healed {} health from {}", target, amount, status)
+            } else {
+                format!("{} took {} damage from {}", target, amount, status)
+            }
+        }
+        BattleEvent::StatusExpired { status, target } => {
+            format!("{} wore off of {}", status, target)
+        }
+        BattleEvent::EnemySpawned { name, level } => {
+            format!("A level {} {} appeared!", level, name)
+        }
+        BattleEvent::EntityDied { name } => format!("{} died!", name),
+        BattleEvent::XpGained { source, amount } => format!("{} dropped {} xp!", source, amount),
+        BattleEvent::LevelUp { name, level } => format!("{} reached level {}!", name, level),
+    }
+}
@@ -1,31 +1,37 @@
+//terminal front end for the game
 use std::collections::VecDeque;
-//simple turn-based game logic
-use std::{error::Error, io};
-
-use crate::entity_components::enemy::Enemy;
-use crate::entity_components::moves::{ElementType, Move, MoveType};
-use crate::entity_components::status::Status;
-use crate::entity_components::{entity::Entity, player::LevelUpType, player::Player, stats::Stats};
-use colored::Colorize;
-use rand::random;
-use ratatui::style::Styled;
-use ratatui::widgets::{BorderType, ListState, StatefulWidget};
+use std::io;
+use std::path::PathBuf;
+
+use ratatui::widgets::{BorderType, ListState};
 use ratatui::{
-    backend::{Backend, CrosstermBackend},
-    crossterm::{
-        event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
-        execute,
-        terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-    },
+    backend::Backend,
+    crossterm::event::{self, Event, KeyCode},
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
     text::{Line, Span, Text},
     widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
     Frame, Terminal,
 };
+use starstruck::battle::engine::{Action, Battle, BattleState};
+use starstruck::battle::initiative::Combatant;
+use starstruck::battle::rules::RuleSet;
+use starstruck::entity_components::{
+    affinity::Affinity,
+    entity::Entity,
+    equipment::{EquipSlot, Gear},
+    item::ItemEffect,
+    moves::{Move, MoveEffect, MoveTarget},
+    player::{LevelUpType, MAX_FOCUS},
+    stats::{DefendStance, ModifierKind},
+};
+use starstruck::save::{save_exists, SaveFile};
+
+use crate::event_text::event_text;
 
 const MAX_ATTACK_STR_HISTORY: usize = 200;
 const RESET_MAGIC_CHOICE: bool = false; // if we want to reset the magic choice after a move is chosen
+const TURN_ORDER_PREVIEW_LEN: usize = 6; // how many upcoming turns to show
 
 /// This keeps track of the current screen that the app is on.
 #[derive(Clone, Copy)]
@@ -33,74 +39,72 @@ pub enum CurrentScreen {
     Main,       // Main gameplay screen
     LevelingUp, // player is leveling up
     Magic,      // choosing a magic move
+    Targeting,  // choosing the enemy to aim an action at
+    Items,      // choosing an item to use
+    Defend,     // choosing a stance to defend in
+    Equipment,  // changing the gear the player is wearing
     Warning,    // warning popup text
     Died,       // player died
+    Continue,   // choosing to continue a saved game or start a new one
     Exiting,
+    Saving, // choosing to save before quitting
 }
 
 ///Struct to hold the game state.
 pub struct GameState {
-    player: Player,
-    enemy: Enemy,
-    is_playing: bool,
-    enemy_list: Vec<Enemy>,         // all game enemies
-    status_list: Vec<Status>,       // all game statuses
-    move_list: Vec<Move>,           // all game moves
-    move_list_available_len: usize, // the length of available moves to the player
+    battle: Battle,
 
     // TUI
     current_screen: CurrentScreen,
-    cur_attack_text_idx: usize, // for scrolling through the attack text
     cur_move_list_idx: usize,
-    attack_text: VecDeque<String>, // NOTE: always push_front() to this.
-    last_screen: CurrentScreen,    // Last screen to return to from the current (in case we need to)
+    cur_target_idx: usize, // the enemy that is selected on the targeting screen
+    cur_item_idx: usize,   // the item that is selected on the items screen
+    cur_stance_idx: usize, // the stance that is selected on the defend screen
+    cur_equip_idx: usize,  // the slot (then the gear in the bag) selected on the equipment screen
+    pending_action: Option<Action>, // the action waiting on a target to be chosen
+    attack_text: VecDeque<String>, // NOTE: always push_back() to this.
+    last_screen: CurrentScreen, // Last screen to return to from the current (in case we need to)
     warning_text: String,
+    save_path: PathBuf, // where the game is saved and continued from
 }
 
 impl GameState {
     ///Create new GameState object
-    pub fn new(player: Player, enemy: Option<Enemy>) -> Self {
-        let is_playing = !player.is_dead();
-        let the_enemy;
-
-        if let Some(temp_enemy) = enemy {
-            //if we were given an enemy, use this
-            the_enemy = temp_enemy;
+    ///
+    /// # Params
+    /// - `battle` - The battle for a new game.
+    /// - `save_path` - The path of the save file. If there is a save here, the player
+    ///   can choose to continue it instead of playing the new game.
+    pub fn new(battle: Battle, save_path: PathBuf) -> Self {
+        let first_screen = if save_exists(&save_path) {
+            CurrentScreen::Continue
         } else {
-            //otherwise, create a new temporary one until we create a random one
-            the_enemy = create_temp_monster();
-        }
+            CurrentScreen::Main
+        };
 
-        let status_list = Status::create_status_list();
-        let move_list = Move::create_move_list(&status_list);
-        let move_list_available_len = Move::get_move_list(&move_list, player.level());
-
-        GameState {
-            player,
-            enemy: the_enemy,
-            is_playing,
-            enemy_list: Enemy::create_enemy_list(),
-            status_list,
-            move_list,
-            move_list_available_len,
-            current_screen: CurrentScreen::Main,
-            cur_attack_text_idx: 0, // start at the first index
-            cur_move_list_idx: 0,   // start at first index
-            attack_text: VecDeque::<String>::new(),
-            last_screen: CurrentScreen::Main,
+        let mut game = GameState {
+            battle,
+            current_screen: first_screen,
+            cur_move_list_idx: 0, // start at first index
+            cur_target_idx: 0,
+            cur_item_idx: 0,
+            cur_stance_idx: 0,
+            cur_equip_idx: 0,
+            pending_action: None,
+            attack_text: VecDeque::new(),
+            last_screen: first_screen,
             warning_text: String::new(),
-        }
+            save_path,
+        };
+        game.update_attack_text();
+
+        game
     }
 
     ///the main game loop
     pub fn game_loop<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
-        //create a new random monster
-        // TODO: this will cause issues if you want to start off with a specific enemy
-        self.enemy = self.create_random_enemy();
-
         loop {
-            // each loop is a tick, with the player or enemy able to try attacking.
-            // but they can only both go again once both of them have gone
+            // each loop is a tick, with the player able to choose an action for the battle to resolve
 
             terminal.draw(|f| self.ui(f))?;
 
@@ -116,72 +120,74 @@ impl GameState {
                         KeyCode::Char('q') => {
                             self.change_screen(CurrentScreen::Exiting);
                         }
-                        // TODO: for attacking moves, give back some value to let the game know when to change screens (like when a player levels up)
                         KeyCode::Char('1') => {
-                            self.do_player_turn(MoveType::AttackMove);
+                            self.choose_target(Action::Attack);
                         }
                         KeyCode::Char('2') => {
                             self.change_screen(CurrentScreen::Magic);
                         }
                         KeyCode::Char('3') => {
-                            self.do_player_turn(MoveType::DefendMove);
+                            self.change_screen(CurrentScreen::Defend);
+                        }
+                        KeyCode::Char('4') => {
+                            self.change_screen(CurrentScreen::Items);
+                        }
+                        KeyCode::Char('5') => {
+                            self.change_screen(CurrentScreen::Equipment);
                         }
                         // nothing
                         _ => {}
                     },
 
                     // leveling up
-                    CurrentScreen::LevelingUp => {
-                        match key.code {
-                            KeyCode::Char('q') => {
-                                self.change_screen(CurrentScreen::Exiting);
-                            }
-                            KeyCode::Char('1') => {
-                                self.player.level_up(LevelUpType::Strength);
-                            }
-                            KeyCode::Char('2') => {
-                                self.player.level_up(LevelUpType::Magic);
-                            }
-                            KeyCode::Char('3') => {
-                                self.player.level_up(LevelUpType::Health);
-                            }
-                            // nothing
-                            _ => {}
-                        }
-
-                        // since we leveled up, we can now check what moves that are available.
-                        self.move_list_available_len =
-                            Move::get_move_list(&self.move_list, self.player.level());
-                        // after we have chosen one of these, we now go back to the normal game
-                        self.current_screen = CurrentScreen::Main;
-                    }
-
-                    CurrentScreen::Died => match key.code {
+                    CurrentScreen::LevelingUp => match key.code {
                         KeyCode::Char('q') => {
                             self.change_screen(CurrentScreen::Exiting);
                         }
+                        KeyCode::Char('1') => {
+                            self.battle.level_up(LevelUpType::Strength);
+                        }
+                        KeyCode::Char('2') => {
+                            self.battle.level_up(LevelUpType::Magic);
+                        }
+                        KeyCode::Char('3') => {
+                            self.battle.level_up(LevelUpType::Health);
+                        }
                         // nothing
                         _ => {}
                     },
 
+                    CurrentScreen::Died => {
+                        if let KeyCode::Char('q') = key.code {
+                            self.change_screen(CurrentScreen::Exiting);
+                        }
+                    }
+
                     CurrentScreen::Warning => match key.code {
-                        KeyCode::Char('q') => {
+                        KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => {
                             self.change_screen_no_save(self.last_screen);
                         }
-                        KeyCode::Esc => {
-                            self.change_screen_no_save(self.last_screen);
+                        // nothing
+                        _ => {}
+                    },
+
+                    CurrentScreen::Continue => match key.code {
+                        KeyCode::Char('c') => {
+                            self.continue_game();
                         }
-                        KeyCode::Enter => {
-                            self.change_screen_no_save(self.last_screen);
+                        KeyCode::Char('n') => {
+                            self.change_screen(CurrentScreen::Main);
                         }
-                        // nothing
                         _ => {}
                     },
 
-                    // TODO: make screen for choosing magic skill
                     CurrentScreen::Exiting => match key.code {
                         KeyCode::Char('y') => {
-                            break;
+                            // there is nothing worth saving once the player has died
+                            if !self.battle.is_playing() {
+                                break;
+                            }
+                            self.change_screen_no_save(CurrentScreen::Saving);
                         }
                         KeyCode::Char('n') => {
                             self.current_screen = self.last_screen;
@@ -189,32 +195,163 @@ impl GameState {
                         _ => {}
                     },
 
-                    CurrentScreen::Magic => match key.code {
-                        KeyCode::Char('q') => {
-                            self.change_screen(CurrentScreen::Main);
+                    CurrentScreen::Saving => match key.code {
+                        KeyCode::Char('y') => {
+                            match SaveFile::write(&self.save_path, &self.battle, &self.attack_text)
+                            {
+                                Ok(()) => break,
+                                Err(err) => {
+                                    self.display_warning(&format!("Could not save! {}", err))
+                                }
+                            }
+                        }
+                        KeyCode::Char('n') => {
+                            break;
                         }
                         KeyCode::Esc => {
+                            self.current_screen = self.last_screen;
+                        }
+                        _ => {}
+                    },
+
+                    CurrentScreen::Magic => match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => {
                             self.change_screen(CurrentScreen::Main);
                         }
                         // move up and down through the move list
-                        KeyCode::Up => {
-                            if self.cur_move_list_idx > 0 {
-                                self.cur_move_list_idx -= 1;
+                        KeyCode::Up if self.cur_move_list_idx > 0 => {
+                            self.cur_move_list_idx -= 1;
+                        }
+                        KeyCode::Down
+                            if self.cur_move_list_idx + 1 < self.battle.available_moves().len() =>
+                        {
+                            self.cur_move_list_idx += 1;
+                        }
+                        KeyCode::Enter => {
+                            let action = Action::Magic(self.cur_move_list_idx);
+                            let single_target = self
+                                .battle
+                                .available_moves()
+                                .get(self.cur_move_list_idx)
+                                .is_some_and(|the_move| {
+                                    the_move.target() == MoveTarget::SingleEnemy
+                                });
+
+                            // only moves aimed at one enemy need a target, unless the move clashes
+                            if single_target || self.battle.rules() == RuleSet::Triangle {
+                                self.choose_target(action);
+                            } else {
+                                self.perform_action(action, 0);
                             }
                         }
-                        KeyCode::Down => {
-                            if self.cur_move_list_idx < self.move_list_available_len - 1 {
-                                self.cur_move_list_idx += 1;
+                        _ => {}
+                    },
+
+                    CurrentScreen::Items => match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => {
+                            self.change_screen(CurrentScreen::Main);
+                        }
+                        // move up and down through the items
+                        KeyCode::Up if self.cur_item_idx > 0 => {
+                            self.cur_item_idx -= 1;
+                        }
+                        KeyCode::Down
+                            if self.cur_item_idx + 1
+                                < self.battle.player().inventory().stacks().len() =>
+                        {
+                            self.cur_item_idx += 1;
+                        }
+                        KeyCode::Enter => {
+                            let stacks = self.battle.player().inventory().stacks();
+
+                            // only items that hurt enemies need a target
+                            if let Some(stack) = stacks.get(self.cur_item_idx) {
+                                if stack.item().effect().needs_target() {
+                                    self.choose_target(Action::Item(self.cur_item_idx));
+                                } else {
+                                    self.perform_action(Action::Item(self.cur_item_idx), 0);
+                                }
                             }
                         }
+                        _ => {}
+                    },
+
+                    CurrentScreen::Defend => match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => {
+                            self.change_screen(CurrentScreen::Main);
+                        }
+                        // move up and down through the stances
+                        KeyCode::Up if self.cur_stance_idx > 0 => {
+                            self.cur_stance_idx -= 1;
+                        }
+                        KeyCode::Down if self.cur_stance_idx + 1 < DefendStance::ALL.len() => {
+                            self.cur_stance_idx += 1;
+                        }
                         KeyCode::Enter => {
-                            if self.do_player_turn(MoveType::MagicMove) {
-                                self.change_screen(self.last_screen);
+                            let action = Action::Defend(DefendStance::ALL[self.cur_stance_idx]);
+
+                            // defending only needs a target when it clashes with one
+                            if self.battle.rules() == RuleSet::Triangle {
+                                self.choose_target(action);
                             } else {
-                                // display a warning if we can't do this move
-                                self.display_warning(
-                                    "Could not perform magic move! Have enough mana?",
-                                );
+                                self.perform_action(action, 0);
+                            }
+                        }
+                        _ => {}
+                    },
+
+                    CurrentScreen::Equipment => match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => {
+                            self.change_screen(CurrentScreen::Main);
+                        }
+                        // move up and down through the slots, then the bag
+                        KeyCode::Up if self.cur_equip_idx > 0 => {
+                            self.cur_equip_idx -= 1;
+                        }
+                        KeyCode::Down if self.cur_equip_idx + 1 < self.num_equip_choices() => {
+                            self.cur_equip_idx += 1;
+                        }
+                        KeyCode::Enter => {
+                            // choosing a slot takes off its gear, choosing gear in the bag puts it on
+                            match EquipSlot::ALL.get(self.cur_equip_idx) {
+                                Some(slot) => self.battle.unequip(*slot),
+                                None => {
+                                    self.battle.equip(self.cur_equip_idx - EquipSlot::ALL.len())
+                                }
+                            };
+                        }
+                        _ => {}
+                    },
+
+                    CurrentScreen::Targeting => match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => {
+                            // go back to where the action was chosen
+                            match self.pending_action {
+                                Some(Action::Magic(_)) => {
+                                    self.change_screen_no_save(CurrentScreen::Magic)
+                                }
+                                Some(Action::Item(_)) => {
+                                    self.change_screen_no_save(CurrentScreen::Items)
+                                }
+                                Some(Action::Defend(_)) => {
+                                    self.change_screen_no_save(CurrentScreen::Defend)
+                                }
+                                _ => self.change_screen_no_save(CurrentScreen::Main),
+                            }
+                            self.pending_action = None;
+                        }
+                        // move up and down through the enemies
+                        KeyCode::Up | KeyCode::Left if self.cur_target_idx > 0 => {
+                            self.cur_target_idx -= 1;
+                        }
+                        KeyCode::Down | KeyCode::Right
+                            if self.cur_target_idx + 1 < self.battle.enemies().len() =>
+                        {
+                            self.cur_target_idx += 1;
+                        }
+                        KeyCode::Enter => {
+                            if let Some(action) = self.pending_action.take() {
+                                self.perform_action(action, self.cur_target_idx);
                             }
                         }
                         _ => {}
@@ -222,18 +359,146 @@ impl GameState {
                 }
             }
 
-            // only let the other events occur when the player is still playing
-            if self.is_playing {
-                self.perform_entity_check();
-                self.do_enemy_turn();
+            self.update_attack_text();
+            self.sync_screen();
+        }
+
+        Ok(())
+    }
+
+    /// Asks the player which enemy to aim an action at, or performs it right
+    /// away if there is only one enemy.
+    ///
+    /// # Params
+    /// - `action` - The action that needs a target.
+    fn choose_target(&mut self, action: Action) {
+        if self.battle.enemies().len() == 1 {
+            self.perform_action(action, 0);
+        } else {
+            self.pending_action = Some(action);
+            self.change_screen_no_save(CurrentScreen::Targeting);
+        }
+    }
+
+    /// Makes the player take their turn, returning to the main screen.
+    ///
+    /// # Params
+    /// - `action` - The action the player is taking.
+    /// - `target` - The index of the enemy the action is aimed at.
+    fn perform_action(&mut self, action: Action, target: usize) {
+        let result = self.battle.take_turn(action, target);
+
+        if RESET_MAGIC_CHOICE && matches!(action, Action::Magic(_)) {
+            // reset the move list index
+            self.cur_move_list_idx = 0;
+        }
 
-                // do cleanup if both the player and enemy have gone
-                self.perform_entity_check();
-                self.truncate_attack_text();
+        if let Err(err) = result {
+            // display why the action couldn't be done, going back to where it was chosen after
+            let (screen, what) = match action {
+                Action::Attack => (CurrentScreen::Main, "attack"),
+                Action::Magic(_) => (CurrentScreen::Magic, "perform magic move"),
+                Action::Defend(_) => (CurrentScreen::Defend, "defend"),
+                Action::Item(_) => (CurrentScreen::Items, "use item"),
+            };
+            self.change_screen_no_save(screen);
+            self.display_warning(&format!("Could not {}! {}.", what, err));
+            return;
+        }
+
+        self.change_screen(CurrentScreen::Main);
+    }
+
+    /// Gets how many choices there are on the equipment screen, which is
+    /// every slot followed by the gear in the bag.
+    fn num_equip_choices(&self) -> usize {
+        EquipSlot::ALL.len() + self.battle.player().equipment().bag().len()
+    }
+
+    /// Loads the saved game, replacing the new game.
+    fn continue_game(&mut self) {
+        match SaveFile::read(&self.save_path) {
+            Ok(save) => {
+                self.battle = Battle::from_saved(save.battle, self.battle.content().clone());
+                self.attack_text = save.log;
+                self.change_screen(CurrentScreen::Main);
+
+                // the saved game may have been waiting on a level up
+                self.sync_screen();
+            }
+            Err(err) => self.display_warning(&format!("Could not load the saved game! {}", err)),
+        }
+    }
+
+    /// Takes the new events from the battle and adds their text to the attack text.
+    fn update_attack_text(&mut self) {
+        for event in self.battle.take_events() {
+            let text = event_text(&event);
+            if !text.is_empty() {
+                self.attack_text.push_back(text);
             }
         }
 
-        Ok(())
+        self.truncate_attack_text();
+    }
+
+    /// Truncates the attack text list so it doesn't overflow
+    /// memory if the history gets too long.
+    fn truncate_attack_text(&mut self) {
+        while self.attack_text.len() > MAX_ATTACK_STR_HISTORY {
+            self.attack_text.pop_front();
+        }
+    }
+
+    /// Switches to the screen that the battle is waiting on, if the battle
+    /// needs something other than a normal action from the player.
+    fn sync_screen(&mut self) {
+        // the selected enemy may have died
+        self.cur_target_idx = self
+            .cur_target_idx
+            .min(self.battle.enemies().len().saturating_sub(1));
+
+        // the selected item may have been the last of its stack
+        self.cur_item_idx = self.cur_item_idx.min(
+            self.battle
+                .player()
+                .inventory()
+                .stacks()
+                .len()
+                .saturating_sub(1),
+        );
+
+        // equipping gear can take it out of the bag
+        self.cur_equip_idx = self.cur_equip_idx.min(self.num_equip_choices() - 1);
+
+        match self.battle.state() {
+            BattleState::LevelingUp => {
+                if let CurrentScreen::Main
+                | CurrentScreen::Magic
+                | CurrentScreen::Targeting
+                | CurrentScreen::Items
+                | CurrentScreen::Defend = self.current_screen
+                {
+                    self.current_screen = CurrentScreen::LevelingUp;
+                }
+            }
+            BattleState::PlayerDied => {
+                if let CurrentScreen::Main
+                | CurrentScreen::Magic
+                | CurrentScreen::Targeting
+                | CurrentScreen::Items
+                | CurrentScreen::Defend = self.current_screen
+                {
+                    self.current_screen = CurrentScreen::Died;
+                }
+            }
+            BattleState::Fighting => {
+                // after we have chosen a level up, we now go back to the normal game
+                if let CurrentScreen::LevelingUp = self.current_screen {
+                    self.current_screen = CurrentScreen::Main;
+                }
+            }
+        }
     }
 
     /// Changes the current screen and saves the
@@ -263,7 +528,7 @@ impl GameState {
     /// - `warning_text` - The warning text to display.
     ///
     /// # Example
-    /// ```rust,norun
+    /// ```rust,ignore
     /// // something happens, causing an error
     /// self.display_warning("Could not perform magic attack.");
     /// ```
@@ -272,172 +537,6 @@ impl GameState {
         self.change_screen(CurrentScreen::Warning);
     }
 
-    /// Checks the state of entities and ends the turn if an entity has died or
-    /// all entities have gone.
-    fn perform_entity_check(&mut self) {
-        // end the turn if both entities have gone or if either one of the entities has died
-        if (self.player.has_gone() && self.enemy.has_gone()) || self.check_entities() {
-            self.end_turn();
-        }
-    }
-
-    /// Truncates the attack text list so it doesn't overflow
-    /// memory if the history gets too long.
-    fn truncate_attack_text(&mut self) {
-        let mut cur_len = self.attack_text.len();
-        while cur_len > MAX_ATTACK_STR_HISTORY {
-            self.attack_text.pop_front();
-            cur_len = self.attack_text.len();
-        }
-    }
-
-    /// Ends a turn and does any required activities before the turn is over.
-    fn end_turn(&mut self) {
-        // always stop defending at the end of a turn
-        self.player.stop_defending();
-        self.enemy.stop_defending();
-
-        self.player.tick_statuses(&mut self.attack_text);
-        self.enemy.tick_statuses(&mut self.attack_text);
-
-        self.player.allow_move();
-        self.enemy.allow_move();
-    }
-
-    /// Does the player's turn based on the player's choice of move.
-    ///
-    /// # Params
-    /// - `turn_type` - The type of turn the player is making.
-    fn do_player_turn(&mut self, turn_type: MoveType) -> bool {
-        let mut ret = false; // move not done
-
-        // if the player is faster and hasn't gone yet
-        if (self.player.speed() >= self.enemy.speed() && !self.player.has_gone())
-            || (self.enemy.has_gone() && !self.player.has_gone())
-        {
-            // do the action that the player wishes.
-            // It is possible that these actions fail, due to the Player already having gone.
-            // In this case, nothing occurs.
-            ret = match turn_type {
-                MoveType::AttackMove => self
-                    .player
-                    .attack_move(&mut self.enemy, &mut self.attack_text),
-
-                MoveType::MagicMove => {
-                    let temp = self.player.magic_move(
-                        &mut self.enemy,
-                        &self.move_list[self.cur_move_list_idx],
-                        &mut self.attack_text,
-                    );
-
-                    if RESET_MAGIC_CHOICE {
-                        // reset the move list index
-                        self.cur_move_list_idx = 0;
-                    }
-
-                    temp
-                }
-
-                MoveType::DefendMove => self.player.defend_move(&mut self.attack_text),
-            };
-        }
-
-        ret
-    }
-
-    /// Does the enemy's turn, allowing the enemy to choose
-    /// what to do in this turn.
-    fn do_enemy_turn(&mut self) {
-        // if the enemy is faster and hasn't gone yet
-        if (self.enemy.speed() > self.player.speed() && !self.enemy.has_gone())
-            || (self.player.has_gone() && !self.enemy.has_gone())
-        {
-            //get the turn type
-            if let Some(turn_type) = self.enemy.get_turn_type() {
-                match turn_type {
-                    MoveType::AttackMove => {
-                        self.enemy
-                            .attack_move(&mut self.player, &mut self.attack_text);
-                    }
-
-                    MoveType::MagicMove => {}
-                    MoveType::DefendMove => {}
-                    _ => {
-                        // we should never reach this unless something has gone wrong
-                        panic!("Invalid move type");
-                    }
-                }
-            }
-        }
-    }
-
-    ///Checks if entities are dead and creates
-    ///new random enemies if they die.
-    ///
-    ///If the player dies, the game is over.
-    /// # Returns
-    /// `true` if an entity died, false otherwise.
-    ///
-    /// # Notes
-    /// - This function can change the value of the current screen,
-    /// so ensure that care is taken that it does not override the current screen
-    /// repeatedly in unwanted situations.
-    fn check_entities(&mut self) -> bool {
-        let mut output = false;
-
-        if self.player.is_dead() {
-            self.current_screen = CurrentScreen::Died;
-            self.is_playing = false;
-
-            // entity died
-            output = true;
-        } else if self.enemy.is_dead() {
-            self.attack_text.push_back(String::from("The enemy died!"));
-            let xp_dropped = self
-                .enemy
-                .drop_xp(self.player.level(), &mut self.attack_text);
-
-            if self.player.gain_xp(xp_dropped) {
-                self.current_screen = CurrentScreen::LevelingUp;
-            }
-
-            // create the enemy after the xp is dropped
-            self.enemy = self.create_random_enemy();
-
-            //entity died
-            output = true;
-        }
-
-        output
-    }
-
-    ///Creates a new random monster
-    fn create_random_enemy(&self) -> Enemy {
-        let possible_enemies = self.get_possible_enemies();
-        // pick a random enemy from the list
-        let random_index = random::<usize>() % possible_enemies.len();
-
-        possible_enemies[random_index].clone()
-    }
-
-    /// Gets the possible enemies that the player can fight.
-    ///
-    /// # Returns
-    /// - A list of enemies that the player can fight, based on level.
-    fn get_possible_enemies(&self) -> Vec<Enemy> {
-        let mut result = Vec::new();
-
-        // iterate through all enemies
-        for i in 0..self.enemy_list.len() {
-            // we can fight an enemy if it is below or close to the player's level
-            if self.enemy_list[i].level() <= self.player.level() + 2 {
-                result.push(self.enemy_list[i].clone());
-            }
-        }
-
-        result
-    }
-
     /// Basically, Widgets are constructed and drawn onto the screen using a `Frame`, which is placed
     /// within a specified `Rect`.
     /// If we want to divide our renderable `Rect` area into three distinct areas, we can use the `Layout`
@@ -456,6 +555,7 @@ impl GameState {
             .constraints([
                 Constraint::Length(7), // top segment is 7 lines tall
                 Constraint::Length(7), // the second section should never be smaller than one line tall but can expand if needed
+                Constraint::Length(3), // the turn order is 3 lines tall
                 Constraint::Min(4),    // third section
                 Constraint::Length(3), // bottom section is 3 lines tall
             ])
@@ -465,6 +565,7 @@ impl GameState {
 
         // Create the title of the program using a Paragraph widget (which is used to display only text)
         let title_block = Block::default()
+            .title(format!("Seed: {}", self.battle.seed()))
             .borders(Borders::ALL)
             .style(Style::default().fg(Color::Magenta).bg(Color::Black));
 
@@ -494,34 +595,32 @@ impl GameState {
 
         let player_info_vec = Vec::<ListItem>::from([
             ListItem::new(Line::styled(
-                self.player.name(),
+                self.battle.player().name(),
                 Style::default().fg(Color::Blue),
             )),
             ListItem::new(Line::styled(
-                format!(
-                    "    Health: {}/{}",
-                    self.player.health(),
-                    self.player.max_health()
-                ),
+                format!("    Health: {}", self.battle.player().health_bar()),
                 Style::default().fg(Color::Green),
             )),
             ListItem::new(Line::styled(
                 format!(
-                    "    Mana: {}/{}",
-                    self.player.mana(),
-                    self.player.max_mana()
+                    "    Mana: {} (+{}/turn), Focus: {}/{}",
+                    self.battle.player().mana_bar(),
+                    self.battle.player().mana_regen(),
+                    self.battle.player().focus(),
+                    MAX_FOCUS
                 ),
                 Style::default().fg(Color::Blue),
             )),
             ListItem::new(Line::styled(
-                format!("    Level: {}", self.player.level(),),
+                format!("    Level: {}", self.battle.player().level(),),
                 Style::default().fg(Color::Blue),
             )),
             ListItem::new(Line::styled(
                 format!(
                     "    Experience: {}/{}",
-                    self.player.experience(),
-                    self.player.max_experience()
+                    self.battle.player().experience(),
+                    self.battle.player().max_experience()
                 ),
                 Style::default().fg(Color::Blue),
             )),
@@ -530,27 +629,64 @@ impl GameState {
 
         frame.render_widget(player_ui_list, game_info_chunks[0]);
 
-        let enemy_info_block = Block::new()
-            .borders(Borders::ALL)
-            .style(Style::default().fg(Color::Red).bg(Color::Black));
+        // give each enemy its own panel
+        let num_enemies = self.battle.enemies().len() as u32;
+        let enemy_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints((0..num_enemies).map(|_| Constraint::Ratio(1, num_enemies)))
+            .split(game_info_chunks[1]);
+
+        for (i, enemy) in self.battle.enemies().iter().enumerate() {
+            // show which enemy is being aimed at
+            let border_color = match self.current_screen {
+                CurrentScreen::Targeting if i == self.cur_target_idx => Color::Yellow,
+                _ => Color::Red,
+            };
+            let enemy_info_block = Block::new()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border_color))
+                .style(Style::default().fg(Color::Red).bg(Color::Black));
+
+            let enemy_info_vec = Vec::<ListItem>::from([
+                ListItem::new(Line::styled(enemy.name(), Style::default().fg(Color::Red))),
+                ListItem::new(Line::styled(
+                    format!("    Health: {}", enemy.health_bar()),
+                    Style::default().fg(Color::Green),
+                )),
+                ListItem::new(Line::styled(
+                    format!("    Level: {}", enemy.level()),
+                    Style::default().fg(Color::Red),
+                )),
+            ]);
+            let enemy_ui_list = List::new(enemy_info_vec).block(enemy_info_block);
 
-        let enemy_info_vec = Vec::<ListItem>::from([
-            ListItem::new(Line::styled(
-                self.enemy.name(),
-                Style::default().fg(Color::Red),
-            )),
-            ListItem::new(Line::styled(
-                format!(
-                    "    Health: {}/{}",
-                    self.enemy.health(),
-                    self.enemy.max_health()
-                ),
-                Style::default().fg(Color::Green),
-            )),
-        ]);
-        let enemy_ui_list = List::new(enemy_info_vec).block(enemy_info_block);
+            frame.render_widget(enemy_ui_list, enemy_chunks[i]);
+        }
 
-        frame.render_widget(enemy_ui_list, game_info_chunks[1]);
+        /* render the upcoming turn order */
+        let mut turn_order_spans = vec![Span::styled(
+            format!("{} (now)", self.battle.player().name()),
+            Style::default().fg(Color::Blue),
+        )];
+        for combatant in self.battle.turn_order(TURN_ORDER_PREVIEW_LEN) {
+            let color = match combatant {
+                Combatant::Player => Color::Blue,
+                Combatant::Enemy(_) => Color::Red,
+            };
+            turn_order_spans.push(Span::styled(" → ", Style::default().fg(Color::Gray)));
+            turn_order_spans.push(Span::styled(
+                self.battle.combatant_name(combatant),
+                Style::default().fg(color),
+            ));
+        }
+
+        let turn_order = Paragraph::new(Line::from(turn_order_spans)).block(
+            Block::default()
+                .title("Turn order")
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::Gray).bg(Color::Black)),
+        );
+        frame.render_widget(turn_order, chunks[2]);
 
         // now we create a vector of ListItems to display the game text
         let game_text_block = Block::default().style(Style::default().bg(Color::Black));
@@ -568,25 +704,46 @@ impl GameState {
         let game_text_list = List::new(list_items).block(game_text_block);
 
         //render the list
-        frame.render_stateful_widget(game_text_list, chunks[2], &mut game_text_state);
+        frame.render_stateful_widget(game_text_list, chunks[3], &mut game_text_state);
 
         // create the bottom navigational bar
         // This has the current screen and what keybinds are available
         let current_navigation_text = vec![
             // The first half of the text
             match self.current_screen {
-                CurrentScreen::Main => Span::styled("Playing", Style::default().fg(Color::Green)),
+                CurrentScreen::Main => match self.battle.rules() {
+                    RuleSet::Classic => Span::styled("Playing", Style::default().fg(Color::Green)),
+                    RuleSet::Triangle => Span::styled(
+                        "Playing (Defend > Attack > Magic > Defend)",
+                        Style::default().fg(Color::Green),
+                    ),
+                },
                 CurrentScreen::LevelingUp => {
                     Span::styled("Leveling up", Style::default().fg(Color::Blue))
                 }
                 CurrentScreen::Magic => {
                     Span::styled("Choosing a magic move", Style::default().fg(Color::Blue))
                 }
+                CurrentScreen::Targeting => {
+                    Span::styled("Choosing a target", Style::default().fg(Color::Yellow))
+                }
+                CurrentScreen::Items => {
+                    Span::styled("Choosing an item", Style::default().fg(Color::Green))
+                }
+                CurrentScreen::Defend => {
+                    Span::styled("Choosing a stance", Style::default().fg(Color::Magenta))
+                }
+                CurrentScreen::Equipment => {
+                    Span::styled("Changing equipment", Style::default().fg(Color::Cyan))
+                }
                 CurrentScreen::Warning => {
                     Span::styled("Warning", Style::default().fg(Color::Yellow))
                 }
                 CurrentScreen::Died => Span::styled("Died", Style::default().fg(Color::Red)),
-                CurrentScreen::Exiting => {
+                CurrentScreen::Continue => {
+                    Span::styled("Starting", Style::default().fg(Color::Green))
+                }
+                CurrentScreen::Exiting | CurrentScreen::Saving => {
                     Span::styled("Exiting", Style::default().fg(Color::LightRed))
                 }
             }
@@ -603,7 +760,7 @@ impl GameState {
         let current_keys_hint = {
             match self.current_screen {
                 CurrentScreen::Main => Span::styled(
-                    "(1) Attack, (2) Magic, (3) Defend (q) quit",
+                    "(1) Attack, (2) Magic, (3) Defend, (4) Items, (5) Equipment (q) quit",
                     Style::default().fg(Color::Red),
                 ),
                 CurrentScreen::LevelingUp => Span::styled(
@@ -614,14 +771,38 @@ impl GameState {
                     "(↑↓) Change choice, (enter) Select choice, (q) Go back",
                     Style::default().fg(Color::Blue),
                 ),
+                CurrentScreen::Targeting => Span::styled(
+                    "(↑↓) Change target, (enter) Select target, (q) Go back",
+                    Style::default().fg(Color::Yellow),
+                ),
+                CurrentScreen::Items => Span::styled(
+                    "(↑↓) Change choice, (enter) Use item, (q) Go back",
+                    Style::default().fg(Color::Green),
+                ),
+                CurrentScreen::Defend => Span::styled(
+                    "(↑↓) Change choice, (enter) Defend, (q) Go back",
+                    Style::default().fg(Color::Magenta),
+                ),
+                CurrentScreen::Equipment => Span::styled(
+                    "(↑↓) Change choice, (enter) Equip/Unequip, (q) Go back",
+                    Style::default().fg(Color::Cyan),
+                ),
                 CurrentScreen::Warning => Span::styled(
                     "(enter, q) Close Warning",
                     Style::default().fg(Color::Yellow),
                 ),
                 CurrentScreen::Died => Span::styled("(q) to quit", Style::default().fg(Color::Red)),
+                CurrentScreen::Continue => Span::styled(
+                    "(c) Continue, (n) New game",
+                    Style::default().fg(Color::Green),
+                ),
                 CurrentScreen::Exiting => {
                     Span::styled("(q) to quit", Style::default().fg(Color::Red))
                 }
+                CurrentScreen::Saving => Span::styled(
+                    "(y) Save and quit, (n) Quit, (esc) Go back",
+                    Style::default().fg(Color::Red),
+                ),
             }
         };
 
@@ -634,7 +815,7 @@ impl GameState {
         let footer_chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(chunks[3]);
+            .split(chunks[4]);
 
         // render footer paragraphs in their appropriate spaces
         frame.render_widget(mode_footer, footer_chunks[0]);
@@ -723,6 +904,43 @@ impl GameState {
                 frame.render_widget(exit_paragraph, area);
             }
 
+            CurrentScreen::Saving => {
+                frame.render_widget(Clear, frame.area()); //this clears the entire screen and anything already drawn
+                let popup_block = Block::default()
+                    .title("Y/N")
+                    .borders(Borders::NONE)
+                    .style(Style::default().bg(Color::DarkGray));
+
+                let save_text = Text::styled(
+                    "Do you want to save your game before quitting? (y/n)",
+                    Style::default().fg(Color::Red),
+                );
+                let save_paragraph = Paragraph::new(save_text)
+                    .block(popup_block)
+                    .wrap(Wrap { trim: false });
+
+                let area = self.centered_rect(60, 25, frame.area());
+                frame.render_widget(save_paragraph, area);
+            }
+
+            CurrentScreen::Continue => {
+                let popup_block = Block::default()
+                    .title("Continue?")
+                    .borders(Borders::ALL)
+                    .style(Style::default().bg(Color::DarkGray));
+
+                let area = self.centered_rect(60, 25, frame.area());
+                frame.render_widget(Clear, area);
+
+                let continue_text = Paragraph::new(
+                    "A saved game was found.\n(c) Continue the saved game\n(n) Start a new game",
+                )
+                .block(popup_block)
+                .wrap(Wrap { trim: false });
+
+                frame.render_widget(continue_text, area);
+            }
+
             CurrentScreen::Magic => {
                 let popup_block = Block::default()
                     .title("Choosing Move")
@@ -733,26 +951,51 @@ impl GameState {
                 let mut ui_move_list_items = Vec::<ListItem>::new();
 
                 // add the elements of move_list to the ui list (should be a small list)
-                for i in 0..self.move_list_available_len {
-                    if i == self.cur_move_list_idx {
-                        ui_move_list_items.push(ListItem::new(Line::from(Span::styled(
-                            format!(
-                                "Name: {}, Cost: {}",
-                                self.move_list[i].name(),
-                                self.move_list[i].cost()
-                            ),
-                            Style::default().bg(Color::Blue).fg(Color::White),
-                        ))));
+                for (i, the_move) in self.battle.available_moves().iter().enumerate() {
+                    let style = if i == self.cur_move_list_idx {
+                        Style::default().bg(Color::Blue).fg(Color::White)
+                    } else {
+                        Style::default().fg(Color::Blue)
+                    };
+
+                    // only moves that hit an enemy care about its affinities, and the
+                    // target isn't picked yet, so every enemy that stands out is named
+                    let hint = if the_move.effect().is_hostile() {
+                        let hints: Vec<String> = self
+                            .battle
+                            .enemies()
+                            .iter()
+                            .filter_map(|enemy| {
+                                affinity_text(enemy.affinity(the_move.element()))
+                                    .map(|text| format!("{}: {}", enemy.name(), text))
+                            })
+                            .collect();
+                        if hints.is_empty() {
+                            String::new()
+                        } else {
+                            format!(" ({})", hints.join(", "))
+                        }
                     } else {
-                        ui_move_list_items.push(ListItem::new(Line::from(Span::styled(
-                            format!(
-                                "Name: {}, Cost: {}",
-                                self.move_list[i].name(),
-                                self.move_list[i].cost()
-                            ),
-                            Style::default().fg(Color::Blue),
-                        ))));
+                        String::new()
                     };
+
+                    // special moves also cost focus
+                    let cost = match the_move.focus_cost() {
+                        0 => the_move.cost().to_string(),
+                        focus_cost => format!("{} (+{} focus)", the_move.cost(), focus_cost),
+                    };
+
+                    ui_move_list_items.push(ListItem::new(Line::from(Span::styled(
+                        format!(
+                            "Name: {}, Cost: {}, Element: {:?}, {}{}",
+                            the_move.name(),
+                            cost,
+                            the_move.element(),
+                            move_effect_text(the_move),
+                            hint
+                        ),
+                        style,
+                    ))));
                 }
 
                 let mut move_list_state =
@@ -763,6 +1006,207 @@ impl GameState {
                 frame.render_stateful_widget(ui_move_list, area, &mut move_list_state);
             }
 
+            CurrentScreen::Items => {
+                let popup_block = Block::default()
+                    .title("Choosing Item")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Green))
+                    .border_type(BorderType::Rounded);
+
+                let stacks = self.battle.player().inventory().stacks();
+                let mut ui_item_list_items = Vec::<ListItem>::new();
+
+                for (i, stack) in stacks.iter().enumerate() {
+                    let style = if i == self.cur_item_idx {
+                        Style::default().bg(Color::Green).fg(Color::Black)
+                    } else {
+                        Style::default().fg(Color::Green)
+                    };
+
+                    ui_item_list_items.push(ListItem::new(Line::from(Span::styled(
+                        format!(
+                            "{} x{}: {}",
+                            stack.item().name(),
+                            stack.count(),
+                            item_effect_text(stack.item().effect())
+                        ),
+                        style,
+                    ))));
+                }
+
+                if stacks.is_empty() {
+                    ui_item_list_items.push(ListItem::new(Line::styled(
+                        "You have no items.",
+                        Style::default().fg(Color::Green),
+                    )));
+                }
+
+                let mut item_list_state =
+                    ListState::default().with_selected(Some(self.cur_item_idx));
+                let ui_item_list = List::new(ui_item_list_items).block(popup_block);
+
+                let area = self.centered_rect(60, 60, frame.area());
+                frame.render_widget(Clear, area);
+                frame.render_stateful_widget(ui_item_list, area, &mut item_list_state);
+            }
+
+            CurrentScreen::Defend => {
+                let popup_block = Block::default()
+                    .title("Choosing Stance")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Magenta))
+                    .border_type(BorderType::Rounded);
+
+                let mut ui_stance_list_items = Vec::<ListItem>::new();
+                for (i, stance) in DefendStance::ALL.iter().enumerate() {
+                    let style = if i == self.cur_stance_idx {
+                        Style::default().bg(Color::Magenta).fg(Color::Black)
+                    } else {
+                        Style::default().fg(Color::Magenta)
+                    };
+
+                    ui_stance_list_items.push(ListItem::new(Line::from(Span::styled(
+                        format!("{:?}: {}", stance, stance_text(*stance)),
+                        style,
+                    ))));
+                }
+
+                let mut stance_list_state =
+                    ListState::default().with_selected(Some(self.cur_stance_idx));
+                let ui_stance_list = List::new(ui_stance_list_items).block(popup_block);
+
+                let area = self.centered_rect(60, 30, frame.area());
+                frame.render_widget(Clear, area);
+                frame.render_stateful_widget(ui_stance_list, area, &mut stance_list_state);
+            }
+
+            CurrentScreen::Equipment => {
+                let popup_block = Block::default()
+                    .title("Equipment")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan))
+                    .border_type(BorderType::Rounded);
+
+                let area = self.centered_rect(60, 60, frame.area());
+                frame.render_widget(Clear, area);
+                let inner_area = popup_block.inner(area);
+                frame.render_widget(popup_block, area);
+
+                let equipment_chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(3), Constraint::Min(1)])
+                    .split(inner_area);
+
+                // show the stats with the current gear, so the player can compare
+                let player = self.battle.player();
+                let stats = player.stats();
+                let stats_text = Paragraph::new(format!(
+                    "Strength: {}, Magic: {}, Defense: {}, Speed: {}\nMax Health: {}, Max Mana: {}\nAccuracy: {}, Evasion: {}, Crit: {}% for {}%",
+                    stats.get_strength(),
+                    stats.get_magic_strength(),
+                    stats.get_defense(),
+                    stats.get_speed(),
+                    player.max_health(),
+                    player.max_mana(),
+                    stats.get_accuracy(),
+                    stats.get_evasion(),
+                    stats.get_crit_chance(),
+                    stats.get_crit_damage()
+                ))
+                .style(Style::default().fg(Color::Cyan));
+                frame.render_widget(stats_text, equipment_chunks[0]);
+
+                let equipment = player.equipment();
+                let mut choices = Vec::<String>::new();
+                for slot in EquipSlot::ALL {
+                    choices.push(match equipment.equipped(slot) {
+                        Some(gear) => format!("{:?}: {}", slot, gear_text(gear)),
+                        None => format!("{:?}: (empty)", slot),
+                    });
+                }
+                for gear in equipment.bag() {
+                    choices.push(format!("  Bag - {:?}: {}", gear.slot(), gear_text(gear)));
+                }
+
+                let mut ui_equip_list_items = Vec::<ListItem>::new();
+                for (i, choice) in choices.into_iter().enumerate() {
+                    let style = if i == self.cur_equip_idx {
+                        Style::default().bg(Color::Cyan).fg(Color::Black)
+                    } else {
+                        Style::default().fg(Color::Cyan)
+                    };
+
+                    ui_equip_list_items
+                        .push(ListItem::new(Line::from(Span::styled(choice, style))));
+                }
+
+                let mut equip_list_state =
+                    ListState::default().with_selected(Some(self.cur_equip_idx));
+                let ui_equip_list = List::new(ui_equip_list_items);
+
+                frame.render_stateful_widget(
+                    ui_equip_list,
+                    equipment_chunks[1],
+                    &mut equip_list_state,
+                );
+            }
+
+            CurrentScreen::Targeting => {
+                let popup_block = Block::default()
+                    .title("Choosing Target")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .border_type(BorderType::Rounded);
+
+                // the element being aimed, to show how well it will work on each enemy
+                let element = match self.pending_action {
+                    Some(Action::Magic(move_idx)) => self
+                        .battle
+                        .available_moves()
+                        .get(move_idx)
+                        .map(|the_move| the_move.element()),
+                    Some(Action::Item(stack_idx)) => self
+                        .battle
+                        .player()
+                        .inventory()
+                        .stacks()
+                        .get(stack_idx)
+                        .and_then(|stack| match stack.item().effect() {
+                            ItemEffect::Bomb { element, .. } => Some(*element),
+                            _ => None,
+                        }),
+                    _ => None,
+                };
+
+                let mut ui_target_list_items = Vec::<ListItem>::new();
+                for (i, enemy) in self.battle.enemies().iter().enumerate() {
+                    let style = if i == self.cur_target_idx {
+                        Style::default().bg(Color::Yellow).fg(Color::Black)
+                    } else {
+                        Style::default().fg(Color::Yellow)
+                    };
+
+                    let hint =
+                        match element.and_then(|element| affinity_text(enemy.affinity(element))) {
+                            Some(text) => format!(" ({})", text),
+                            None => String::new(),
+                        };
+
+                    ui_target_list_items.push(ListItem::new(Line::from(Span::styled(
+                        format!("{}, Health: {}{}", enemy.name(), enemy.health_bar(), hint),
+                        style,
+                    ))));
+                }
+
+                let mut target_list_state =
+                    ListState::default().with_selected(Some(self.cur_target_idx));
+                let ui_target_list = List::new(ui_target_list_items).block(popup_block);
+
+                let area = self.centered_rect(60, 40, frame.area());
+                frame.render_widget(Clear, area);
+                frame.render_stateful_widget(ui_target_list, area, &mut target_list_state);
+            }
+
             _ => {}
         }
     }
@@ -796,13 +1240,112 @@ impl GameState {
     }
 }
 
-// TODO: do we really need this??
-fn create_temp_monster() -> Enemy {
-    let random_health_stat: u32 = (random::<u32>() % 10) + 1;
-    Enemy::new(
-        String::from("test_enemy"),
-        Stats::new(random_health_stat, 10, 10, 10, 10, 0),
-        1,
-        false,
-    )
+/// Creates the text for what an item does.
+///
+/// # Params
+/// - `effect` - The effect of the item.
+///
+/// # Returns
+/// - The text to show next to the item.
+fn item_effect_text(effect: &ItemEffect) -> String {
+    match effect {
+        ItemEffect::Heal { amount } => format!("Heals {} health", amount),
+        ItemEffect::RestoreMana { amount } => format!("Restores {} mana", amount),
+        ItemEffect::Cure { status } => format!("Cures {}", status),
+        ItemEffect::Bomb { amount, element } => format!("Deals {} {:?} damage", amount, element),
+    }
+}
+
+/// Creates the text for what a defending stance does, besides raising defense.
+///
+/// # Params
+/// - `stance` - The stance to describe.
+///
+/// # Returns
+/// - The text to show next to the stance.
+fn stance_text(stance: DefendStance) -> &'static str {
+    match stance {
+        DefendStance::Guard => "Blocks statuses",
+        DefendStance::Parry => "Chance to counter attacks",
+        DefendStance::Brace => "Regains mana each turn",
+    }
+}
+
+/// Creates the text for a piece of gear and how it changes stats and affinities.
+///
+/// # Params
+/// - `gear` - The gear to describe.
+///
+/// # Returns
+/// - The text to show for the gear.
+fn gear_text(gear: &Gear) -> String {
+    let modifiers: Vec<String> = gear
+        .modifiers()
+        .iter()
+        .map(|modifier| {
+            let sign = if modifier.amount() < 0 { "" } else { "+" };
+            let percent = match modifier.kind() {
+                ModifierKind::Flat => "",
+                ModifierKind::Percent => "%",
+            };
+            format!(
+                "{}{}{} {:?}",
+                sign,
+                modifier.amount(),
+                percent,
+                modifier.stat()
+            )
+        })
+        .chain(
+            gear.affinities()
+                .listed()
+                .into_iter()
+                .map(|(element, affinity)| format!("{:?} {:?}", affinity, element)),
+        )
+        .collect();
+
+    format!("{} ({})", gear.name(), modifiers.join(", "))
+}
+
+/// Creates the text describing what a move does and who it is aimed at.
+///
+/// # Params
+/// - `the_move` - The move to describe.
+///
+/// # Returns
+/// - The text to show next to the move.
+fn move_effect_text(the_move: &Move) -> String {
+    let effect = match the_move.effect() {
+        MoveEffect::Damage => "Damage",
+        MoveEffect::HealSelf => "Heal",
+        MoveEffect::Buff => "Buff",
+        MoveEffect::Cleanse => "Cleanse",
+        MoveEffect::DrainHealth => "Drain Health",
+        MoveEffect::DrainMana => "Drain Mana",
+        MoveEffect::Shield => "Shield",
+    };
+    let target = match the_move.target() {
+        MoveTarget::User => "Self",
+        MoveTarget::SingleEnemy => "One Enemy",
+        MoveTarget::AllEnemies => "All Enemies",
+    };
+
+    format!("{} ({})", effect, target)
+}
+
+/// Creates a hint for how well a move will work against an enemy.
+///
+/// # Params
+/// - `affinity` - The affinity of the enemy to the move's element.
+///
+/// # Returns
+/// - The text to show next to the move, or `None` if it works as normal.
+fn affinity_text(affinity: Affinity) -> Option<&'static str> {
+    match affinity {
+        Affinity::Weak => Some("super effective!"),
+        Affinity::Normal => None,
+        Affinity::Resist => Some("not very effective"),
+        Affinity::Immune => Some("no effect"),
+        Affinity::Absorb => Some("absorbed!"),
+    }
 }
@@ -0,0 +1,424 @@
+//front end for playing the game without a terminal, driven by commands
+use std::io::{self, BufRead, Write};
+
+use starstruck::battle::engine::{Action, Battle, BattleState};
+use starstruck::entity_components::{player::LevelUpType, stats::DefendStance};
+use starstruck::protocol::{
+    ActionMessage, GameMessage, Observation, ProtocolError, PROTOCOL_VERSION,
+};
+
+use crate::event_text::event_text;
+
+/// How the battle log is written in headless mode.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text, // the same text as the battle log of the terminal front end
+    Json, // one JSON object per event
+}
+
+/// A command read in headless mode, from one line of the input.
+#[derive(Debug, PartialEq)]
+enum Command {
+    Act { action: Action, target: usize }, // take a turn, aimed at the enemy at `target`
+    LevelUp(LevelUpType),                  // choose the stat to level up
+}
+
+/// Plays the game by reading one command per line, writing the battle log as it goes.
+///
+/// Blank lines and lines starting with `#` are skipped. A command that can't be
+/// understood or done is reported on stderr, and the game carries on with the next
+/// one. The game ends when the input runs out or the player dies.
+///
+/// # Params
+/// - `battle` - The battle to play.
+/// - `input` - Where the commands are read from.
+/// - `output` - Where the battle log is written.
+/// - `format` - How the battle log is written.
+pub fn run_headless(
+    mut battle: Battle,
+    input: impl BufRead,
+    mut output: impl Write,
+    format: LogFormat,
+) -> io::Result<()> {
+    // the enemies may have already appeared and acted
+    write_events(&mut battle, &mut output, format)?;
+
+    for (line_idx, line) in input.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if !battle.is_playing() {
+            break;
+        }
+
+        if format == LogFormat::Text {
+            writeln!(output, "> {}", line)?;
+        }
+        if let Err(err) = run_command(&mut battle, line) {
+            eprintln!("line {}: {}", line_idx + 1, err);
+        }
+        write_events(&mut battle, &mut output, format)?;
+
+        if format == LogFormat::Text && battle.state() == BattleState::LevelingUp {
+            writeln!(
+                output,
+                "Choose a trait to level up! (strength, magic or health)"
+            )?;
+        }
+        output.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Plays the game with an agent over the JSON-lines protocol (see `starstruck::protocol`).
+///
+/// An observation is written when the game starts, then each line that is read
+/// is an `ActionMessage` that gets exactly one `GameMessage` written back. The game
+/// ends when the input runs out or the player dies.
+///
+/// # Params
+/// - `battle` - The battle to play.
+/// - `input` - Where the actions are read from.
+/// - `output` - Where the observations are written.
+pub fn run_protocol(
+    mut battle: Battle,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    let events = battle.take_events();
+    write_message(
+        &mut output,
+        &GameMessage::Observation(Box::new(Observation::new(&battle, events))),
+    )?;
+
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let result = serde_json::from_str::<ActionMessage>(line)
+            .map_err(ProtocolError::Parse)
+            .and_then(|message| message.apply(&mut battle));
+        let message = match result {
+            Ok(()) => {
+                let events = battle.take_events();
+                GameMessage::Observation(Box::new(Observation::new(&battle, events)))
+            }
+            Err(err) => GameMessage::Error {
+                version: PROTOCOL_VERSION,
+                message: err.to_string(),
+            },
+        };
+        write_message(&mut output, &message)?;
+
+        // the agent has seen the player die, so there is nothing left to do
+        if !battle.is_playing() {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes one protocol message as a line of JSON, flushing it so the agent sees it straight away.
+///
+/// # Params
+/// - `output` - Where the message is written.
+/// - `message` - The message to write.
+fn write_message(output: &mut impl Write, message: &GameMessage) -> io::Result<()> {
+    writeln!(output, "{}", serde_json::to_string(message)?)?;
+    output.flush()
+}
+
+/// Runs one command against the battle.
+///
+/// # Params
+/// - `battle` - The battle to run the command in.
+/// - `line` - The command, such as `magic FireOne 2`.
+///
+/// # Returns
+/// - A message describing why the command couldn't be understood or done, if it wasn't.
+fn run_command(battle: &mut Battle, line: &str) -> Result<(), String> {
+    match parse_command(battle, line)? {
+        Command::Act { action, target } => {
+            let what = match action {
+                Action::Attack => "attack",
+                Action::Magic(_) => "perform magic move",
+                Action::Defend(_) => "defend",
+                Action::Item(_) => "use item",
+            };
+            battle
+                .take_turn(action, target)
+                .map(|_| ())
+                .map_err(|err| format!("could not {}: {}", what, err))
+        }
+
+        Command::LevelUp(level_type) => {
+            if battle.level_up(level_type) {
+                Ok(())
+            } else {
+                Err(String::from("you aren't leveling up"))
+            }
+        }
+    }
+}
+
+/// Parses a command, looking up the moves and items it names in the battle.
+///
+/// The commands are:
+/// - `attack [enemy]`
+/// - `magic <move> [enemy]`
+/// - `defend [guard|parry|brace] [enemy]`
+/// - `item <item> [enemy]`
+/// - `levelup <strength|magic|health>`
+///
+/// Enemies are numbered from 1, in the order they are shown, and default to the first.
+/// Names are matched ignoring case.
+///
+/// # Params
+/// - `battle` - The battle the command is for.
+/// - `line` - The command.
+///
+/// # Returns
+/// - The parsed `Command`, or a message describing what is wrong with it.
+fn parse_command(battle: &Battle, line: &str) -> Result<Command, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let (verb, args) = match words.split_first() {
+        Some((verb, args)) => (verb.to_lowercase(), args),
+        None => return Err(String::from("empty command")),
+    };
+
+    match verb.as_str() {
+        "attack" => {
+            let (name, target) = split_target(args)?;
+            if !name.is_empty() {
+                return Err(format!("attack doesn't take a name: {}", name));
+            }
+            Ok(Command::Act {
+                action: Action::Attack,
+                target,
+            })
+        }
+
+        "magic" => {
+            let (name, target) = split_target(args)?;
+            if name.is_empty() {
+                return Err(String::from("magic needs the name of a move"));
+            }
+            // moves are indexed by their place in the whole move list, which the
+            // battle checks against the moves the player has learned
+            let move_idx = battle
+                .content()
+                .moves()
+                .iter()
+                .position(|the_move| the_move.name().eq_ignore_ascii_case(&name))
+                .ok_or_else(|| format!("unknown move: {}", name))?;
+            Ok(Command::Act {
+                action: Action::Magic(move_idx),
+                target,
+            })
+        }
+
+        "defend" => {
+            // the enemy only matters under the triangle, where it is who the defend clashes with
+            let (name, target) = split_target(args)?;
+            let stance = match name.as_str() {
+                "" => DefendStance::default(),
+                name => DefendStance::from_name(&name.to_lowercase())
+                    .ok_or_else(|| format!("unknown stance: {}", name))?,
+            };
+            Ok(Command::Act {
+                action: Action::Defend(stance),
+                target,
+            })
+        }
+
+        "item" => {
+            let (name, target) = split_target(args)?;
+            if name.is_empty() {
+                return Err(String::from("item needs the name of an item"));
+            }
+            let stack_idx = battle
+                .player()
+                .inventory()
+                .stacks()
+                .iter()
+                .position(|stack| stack.item().name().eq_ignore_ascii_case(&name))
+                .ok_or_else(|| format!("you don't have {}", name))?;
+            Ok(Command::Act {
+                action: Action::Item(stack_idx),
+                target,
+            })
+        }
+
+        "levelup" => match args {
+            [name] => LevelUpType::from_name(&name.to_lowercase())
+                .map(Command::LevelUp)
+                .ok_or_else(|| format!("unknown trait: {}", name)),
+            _ => Err(String::from(
+                "levelup needs one of strength, magic or health",
+            )),
+        },
+
+        _ => Err(format!("unknown command: {}", verb)),
+    }
+}
+
+/// Splits the arguments of a command into a name and the enemy it is aimed at,
+/// which is the last argument if it is a number.
+///
+/// # Params
+/// - `args` - The arguments after the command's name.
+///
+/// # Returns
+/// - The name (which may be empty) and the index of the enemy, or a message
+///   describing what is wrong with the enemy number.
+fn split_target(args: &[&str]) -> Result<(String, usize), String> {
+    let (name_words, target) = match args.split_last() {
+        Some((last, rest)) => match last.parse::<usize>() {
+            Ok(0) => return Err(String::from("enemies are numbered from 1")),
+            Ok(number) => (rest, number - 1),
+            Err(_) => (args, 0),
+        },
+        None => (args, 0),
+    };
+
+    Ok((name_words.join(" "), target))
+}
+
+/// Writes the new events of the battle to the output.
+///
+/// # Params
+/// - `battle` - The battle to take the events from.
+/// - `output` - Where the events are written.
+/// - `format` - How the events are written.
+fn write_events(battle: &mut Battle, output: &mut impl Write, format: LogFormat) -> io::Result<()> {
+    for event in battle.take_events() {
+        match format {
+            LogFormat::Text => {
+                let text = event_text(&event);
+                if !text.is_empty() {
+                    writeln!(output, "{}", text)?;
+                }
+            }
+            LogFormat::Json => writeln!(output, "{}", serde_json::to_string(&event)?)?,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starstruck::content::Content;
+    use starstruck::entity_components::player::Player;
+
+    fn battle() -> Battle {
+        Battle::new(Player::default(), None, 1, Content::built_in())
+    }
+
+    #[test]
+    fn attack_is_aimed_at_a_numbered_enemy() {
+        let battle = battle();
+        assert_eq!(
+            parse_command(&battle, "attack").unwrap(),
+            Command::Act {
+                action: Action::Attack,
+                target: 0,
+            }
+        );
+        assert_eq!(
+            parse_command(&battle, "ATTACK 2").unwrap(),
+            Command::Act {
+                action: Action::Attack,
+                target: 1,
+            }
+        );
+        assert_eq!(
+            parse_command(&battle, "attack 0").unwrap_err(),
+            "enemies are numbered from 1"
+        );
+    }
+
+    #[test]
+    fn magic_is_looked_up_by_name() {
+        let battle = battle();
+        let fire_idx = battle
+            .content()
+            .moves()
+            .iter()
+            .position(|the_move| the_move.name() == "FireOne")
+            .unwrap();
+
+        assert_eq!(
+            parse_command(&battle, "magic fireone 2").unwrap(),
+            Command::Act {
+                action: Action::Magic(fire_idx),
+                target: 1,
+            }
+        );
+        assert_eq!(
+            parse_command(&battle, "magic IceOne").unwrap_err(),
+            "unknown move: IceOne"
+        );
+    }
+
+    #[test]
+    fn defend_takes_a_stance_and_an_enemy() {
+        let battle = battle();
+        assert_eq!(
+            parse_command(&battle, "defend").unwrap(),
+            Command::Act {
+                action: Action::Defend(DefendStance::Guard),
+                target: 0,
+            }
+        );
+        assert_eq!(
+            parse_command(&battle, "defend Parry 2").unwrap(),
+            Command::Act {
+                action: Action::Defend(DefendStance::Parry),
+                target: 1,
+            }
+        );
+        assert_eq!(
+            parse_command(&battle, "defend 2").unwrap(),
+            Command::Act {
+                action: Action::Defend(DefendStance::Guard),
+                target: 1,
+            }
+        );
+        assert_eq!(
+            parse_command(&battle, "defend dodge").unwrap_err(),
+            "unknown stance: dodge"
+        );
+    }
+
+    #[test]
+    fn levelup_takes_a_trait() {
+        let battle = battle();
+        assert_eq!(
+            parse_command(&battle, "levelup Magic").unwrap(),
+            Command::LevelUp(LevelUpType::Magic)
+        );
+        assert_eq!(
+            parse_command(&battle, "levelup luck").unwrap_err(),
+            "unknown trait: luck"
+        );
+    }
+
+    #[test]
+    fn unknown_commands_are_refused() {
+        let battle = battle();
+        assert_eq!(
+            parse_command(&battle, "Run away").unwrap_err(),
+            "unknown command: run"
+        );
+        assert_eq!(parse_command(&battle, "   ").unwrap_err(), "empty command");
+    }
+}
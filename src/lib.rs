@@ -0,0 +1,12 @@
+//! Starstruck game library.
+//!
+//! Contains the entities and the headless battle engine, so that combat can be
+//! driven by the terminal UI or by any other front end.
+
+//create modules
+pub mod battle;
+pub mod content;
+pub mod entity_components;
+pub mod protocol;
+pub mod rng;
+pub mod save;
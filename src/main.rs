@@ -1,27 +1,156 @@
 //create modules
-mod entity_components;
+mod event_text;
 mod game;
+mod headless;
 
-use std::{error::Error, io};
-
-use crate::game::{CurrentScreen, GameState};
-use entity_components::{
-    enemy::Enemy,
-    entity::Entity,
-    player::{Player, DEFAULT_HAS_GONE_STATE, DEFAULT_PLAYER_LEVEL, DEFAULT_PLAYER_XP},
-    stats::Stats,
+use std::{
+    error::Error,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::PathBuf,
+    process,
 };
+
+use crate::game::GameState;
+use crate::headless::{run_headless, run_protocol, LogFormat};
 use ratatui::{
-    backend::{Backend, CrosstermBackend},
+    backend::CrosstermBackend,
     crossterm::{
-        event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+        event::{DisableMouseCapture, EnableMouseCapture},
         execute,
         terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     },
     Terminal,
 };
+use starstruck::battle::engine::Battle;
+use starstruck::battle::rules::RuleSet;
+use starstruck::content::Content;
+use starstruck::entity_components::player::Player;
+use starstruck::rng::random_seed;
+use starstruck::save::DEFAULT_SAVE_PATH;
+
+/// The options the game was started with from the command line.
+struct Options {
+    seed: Option<u64>,         // the seed for the game, or `None` to pick a random one
+    save_path: PathBuf,        // where the game is saved and continued from
+    data_dir: Option<PathBuf>, // where the content is loaded from, or `None` for the default
+    rules: RuleSet,            // the rules a new game is played with
+    free_defend: bool,         // if defending does not use up the player's turn in a new game
+    headless: bool,            // if the game is played from commands instead of the terminal
+    script: Option<PathBuf>,   // where headless commands are read from, or `None` for stdin
+    log_format: LogFormat,     // how the battle log is written in headless mode
+    protocol: bool, // if headless mode speaks the JSON-lines protocol instead of commands
+}
+
+impl Options {
+    /// Parses the command line arguments.
+    ///
+    /// # Returns
+    /// - The parsed `Options`, or a message describing the bad argument.
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            seed: None,
+            save_path: PathBuf::from(DEFAULT_SAVE_PATH),
+            data_dir: None,
+            rules: RuleSet::default(),
+            free_defend: false,
+            headless: false,
+            script: None,
+            log_format: LogFormat::Text,
+            protocol: false,
+        };
+        let mut args = args.skip(1); // skip the program name
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let value = args.next().ok_or("--seed needs a value")?;
+                    let seed = value
+                        .parse::<u64>()
+                        .map_err(|_| format!("invalid seed: {}", value))?;
+                    options.seed = Some(seed);
+                }
+                "--save" => {
+                    let value = args.next().ok_or("--save needs a path")?;
+                    options.save_path = PathBuf::from(value);
+                }
+                "--data" => {
+                    let value = args.next().ok_or("--data needs a directory")?;
+                    options.data_dir = Some(PathBuf::from(value));
+                }
+                "--rules" => {
+                    let value = args.next().ok_or("--rules needs a rule set")?;
+                    options.rules = RuleSet::from_name(&value)
+                        .ok_or_else(|| format!("unknown rule set: {}", value))?;
+                }
+                "--free-defend" => options.free_defend = true,
+                "--headless" => options.headless = true,
+                "--script" => {
+                    let value = args.next().ok_or("--script needs a path")?;
+                    options.script = Some(PathBuf::from(value));
+                    options.headless = true; // a script is only read in headless mode
+                }
+                "--json" => options.log_format = LogFormat::Json,
+                "--protocol" => {
+                    options.protocol = true;
+                    options.headless = true; // the protocol is only spoken in headless mode
+                }
+                _ => return Err(format!("unknown argument: {}", arg)),
+            }
+        }
+
+        if options.log_format == LogFormat::Json && !options.headless {
+            return Err(String::from("--json only works with --headless"));
+        }
+        if options.log_format == LogFormat::Json && options.protocol {
+            return Err(String::from("--json can't be used with --protocol"));
+        }
+
+        Ok(options)
+    }
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    // read the options and content before we take over the terminal
+    let options = Options::parse(std::env::args())?;
+    let seed = options.seed.unwrap_or_else(random_seed);
+    let content = match &options.data_dir {
+        Some(dir) => Content::load(dir)?,
+        None => Content::load_default()?,
+    };
+
+    let player = Player::default().with_inventory(content.starting_inventory());
+    let battle = Battle::new(player, None, seed, content)
+        .with_rules(options.rules)
+        .with_free_defend(options.free_defend);
+
+    // play from commands, without touching the terminal or the save
+    if options.headless {
+        let stdout = io::stdout().lock();
+        let input: Box<dyn BufRead> = match &options.script {
+            Some(path) => {
+                let file = File::open(path)
+                    .map_err(|err| format!("could not open {}: {}", path.display(), err))?;
+                Box::new(BufReader::new(file))
+            }
+            None => Box::new(io::stdin().lock()),
+        };
+
+        if options.protocol {
+            run_protocol(battle, input, stdout)?;
+        } else {
+            run_headless(battle, input, stdout, options.log_format)?;
+        }
+        return Ok(());
+    }
 
-fn main() -> Result<(), Box<dyn Error>> {
     // set up terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -32,8 +161,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut terminal = Terminal::new(backend).unwrap();
 
     // create app and run it
-    let player = Player::default();
-    let mut the_game = GameState::new(player, None);
+    let mut the_game = GameState::new(battle, options.save_path);
     the_game.game_loop(&mut terminal)?;
 
     // undo changes made to the user's terminal to exit
@@ -0,0 +1,572 @@
+// file for the JSON-lines protocol that lets external agents play the game
+use std::{error::Error, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::battle::engine::{Action, Battle, BattleState};
+use crate::battle::event::BattleEvent;
+use crate::battle::outcome::ActionError;
+use crate::battle::rules::RuleSet;
+use crate::entity_components::{
+    entity::Entity,
+    item::ItemEffect,
+    moves::{ElementType, MoveEffect, MoveTarget},
+    player::{LevelUpType, MAX_FOCUS},
+    resource::Resource,
+    stats::DefendStance,
+};
+
+/// The version of the protocol that this build speaks.
+///
+/// Bump this whenever a message changes shape in a way that older agents would
+/// misread. Adding a new field or event is not a breaking change.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// A message the game sends to an agent, as one line of JSON. Its variant name is
+/// in `type`, such as `{"type": "Error", "version": 1, "message": "..."}`.
+///
+/// The game sends an `Observation` when it starts, and then exactly one message
+/// for each line it reads.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum GameMessage {
+    /// The state of the game after the last action, or at the start.
+    Observation(Box<Observation>),
+    /// The last line could not be understood or done, so nothing happened.
+    Error { version: u32, message: String },
+}
+
+/// Everything an agent can see about the game, and what it can do next.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Observation {
+    pub version: u32,                      // the `PROTOCOL_VERSION` of the game
+    pub seed: u64,                         // the seed the game was started with
+    pub rules: RuleSet,                    // the rules the game is played with
+    pub state: BattleState,                // what the game is waiting on
+    pub player: PlayerView,                // the player
+    pub enemies: Vec<CombatantView>,       // the living enemies, which actions aim at by index
+    pub moves: Vec<MoveView>,              // the moves the player has learned
+    pub items: Vec<ItemView>,              // the items the player is carrying
+    pub legal_actions: Vec<ActionMessage>, // every action that can be taken right now
+    pub events: Vec<BattleEvent>,          // what happened since the last observation, oldest first
+}
+
+/// What can be seen of an entity in the fight.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CombatantView {
+    pub name: String,
+    pub level: u32,
+    pub health: Resource,
+    pub mana: Resource,
+    pub statuses: Vec<StatusView>,
+}
+
+/// What can be seen of the player, on top of what can be seen of every entity.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerView {
+    #[serde(flatten)]
+    pub combatant: CombatantView,
+    pub xp: u32,
+    pub max_xp: u32, // the xp needed to level up
+    pub focus: u32,
+    pub max_focus: u32,
+}
+
+/// A status applied to an entity.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusView {
+    pub name: String,
+    pub turns_left: u32,
+    pub stacks: u32,
+}
+
+/// A move the player has learned.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveView {
+    pub name: String,
+    pub mana_cost: u32,
+    pub focus_cost: u32,
+    pub base_amount: u32,
+    pub element: ElementType,
+    pub effect: MoveEffect,
+    pub target: MoveTarget,
+}
+
+/// A stack of items the player is carrying.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemView {
+    pub name: String,
+    pub count: u32,
+    pub effect: ItemEffect,
+}
+
+/// An action an agent takes, as one line of JSON. Its variant name is in `action`,
+/// such as `{"action": "Magic", "move_name": "FireOne", "target": 0}`.
+///
+/// `target` is the index of the enemy in `Observation::enemies`, and is the first
+/// enemy if it isn't given. Under `RuleSet::Triangle`, it is also the enemy that a
+/// defend or a move that isn't aimed at one enemy clashes with. Moves and items are
+/// named, ignoring case.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action")]
+pub enum ActionMessage {
+    Attack {
+        #[serde(default)]
+        target: usize,
+    },
+    Magic {
+        move_name: String,
+        #[serde(default)]
+        target: usize,
+    },
+    Defend {
+        #[serde(default)]
+        stance: DefendStance,
+        #[serde(default)]
+        target: usize,
+    },
+    Item {
+        item: String,
+        #[serde(default)]
+        target: usize,
+    },
+    LevelUp {
+        stat: LevelUpType,
+    },
+}
+
+/// Why a line from an agent could not be done.
+#[derive(Debug)]
+pub enum ProtocolError {
+    Parse(serde_json::Error),     // the line is not a valid `ActionMessage`
+    UnknownMove { name: String }, // the player hasn't learned a move with this name
+    UnknownItem { name: String }, // the player isn't carrying an item with this name
+    NotLevelingUp,                // a `LevelUp` was sent while not leveling up
+    Action(ActionError),          // the battle refused the action
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtocolError::Parse(err) => write!(f, "invalid action message: {}", err),
+            ProtocolError::UnknownMove { name } => write!(f, "unknown move: {}", name),
+            ProtocolError::UnknownItem { name } => write!(f, "you don't have {}", name),
+            ProtocolError::NotLevelingUp => write!(f, "you aren't leveling up"),
+            ProtocolError::Action(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for ProtocolError {}
+
+impl From<ActionError> for ProtocolError {
+    fn from(err: ActionError) -> Self {
+        ProtocolError::Action(err)
+    }
+}
+
+impl Observation {
+    /// Creates an observation of the battle as it is now.
+    ///
+    /// # Params
+    /// - `battle` - The battle to observe.
+    /// - `events` - The events since the last observation, taken from the battle.
+    pub fn new(battle: &Battle, events: Vec<BattleEvent>) -> Self {
+        let player = battle.player();
+
+        Self {
+            version: PROTOCOL_VERSION,
+            seed: battle.seed(),
+            rules: battle.rules(),
+            state: battle.state(),
+            player: PlayerView {
+                combatant: CombatantView::new(player),
+                xp: player.experience(),
+                max_xp: player.max_experience(),
+                focus: player.focus(),
+                max_focus: MAX_FOCUS,
+            },
+            enemies: battle
+                .enemies()
+                .iter()
+                .map(|enemy| CombatantView::new(enemy))
+                .collect(),
+            moves: battle
+                .available_moves()
+                .iter()
+                .map(|the_move| MoveView {
+                    name: the_move.name(),
+                    mana_cost: the_move.cost(),
+                    focus_cost: the_move.focus_cost(),
+                    base_amount: the_move.base_amount(),
+                    element: the_move.element(),
+                    effect: the_move.effect(),
+                    target: the_move.target(),
+                })
+                .collect(),
+            items: player
+                .inventory()
+                .stacks()
+                .iter()
+                .map(|stack| ItemView {
+                    name: stack.item().name(),
+                    count: stack.count(),
+                    effect: stack.item().effect().clone(),
+                })
+                .collect(),
+            legal_actions: legal_actions(battle),
+            events,
+        }
+    }
+}
+
+impl CombatantView {
+    /// Creates a view of an entity.
+    ///
+    /// # Params
+    /// - `entity` - The entity to view.
+    pub fn new(entity: &dyn Entity) -> Self {
+        Self {
+            name: entity.name(),
+            level: entity.level(),
+            health: *entity.health_bar(),
+            mana: *entity.mana_bar(),
+            statuses: entity
+                .statuses()
+                .iter()
+                .map(|status| StatusView {
+                    name: status.name(),
+                    turns_left: status.turns_left(),
+                    stacks: status.stacks(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl ActionMessage {
+    /// Takes this action in the battle.
+    ///
+    /// # Params
+    /// - `battle` - The battle to take the action in.
+    ///
+    /// # Returns
+    /// - Why the action could not be done, if it wasn't. Nothing happens in the battle when it isn't.
+    pub fn apply(&self, battle: &mut Battle) -> Result<(), ProtocolError> {
+        let (action, target) = match self {
+            ActionMessage::Attack { target } => (Action::Attack, *target),
+
+            ActionMessage::Magic { move_name, target } => {
+                // moves are indexed by their place in the available moves
+                let move_idx = battle
+                    .available_moves()
+                    .iter()
+                    .position(|the_move| the_move.name().eq_ignore_ascii_case(move_name))
+                    .ok_or_else(|| ProtocolError::UnknownMove {
+                        name: move_name.clone(),
+                    })?;
+                (Action::Magic(move_idx), *target)
+            }
+
+            ActionMessage::Defend { stance, target } => (Action::Defend(*stance), *target),
+
+            ActionMessage::Item { item, target } => {
+                let stack_idx = battle
+                    .player()
+                    .inventory()
+                    .stacks()
+                    .iter()
+                    .position(|stack| stack.item().name().eq_ignore_ascii_case(item))
+                    .ok_or_else(|| ProtocolError::UnknownItem { name: item.clone() })?;
+                (Action::Item(stack_idx), *target)
+            }
+
+            ActionMessage::LevelUp { stat } => {
+                return if battle.level_up(*stat) {
+                    Ok(())
+                } else {
+                    Err(ProtocolError::NotLevelingUp)
+                };
+            }
+        };
+
+        battle.take_turn(action, target)?;
+        Ok(())
+    }
+}
+
+/// Gets every action that can be taken in the battle right now, checked against
+/// the same rules as `Battle::take_turn`.
+///
+/// Actions that aren't aimed at an enemy are only listed once, with a `target` of 0,
+/// except under `RuleSet::Triangle`, where defends and moves are listed for each enemy
+/// they can clash with.
+///
+/// # Params
+/// - `battle` - The battle to check the actions in.
+///
+/// # Returns
+/// - The legal actions, or an empty list if the player has died.
+pub fn legal_actions(battle: &Battle) -> Vec<ActionMessage> {
+    let mut actions = Vec::new();
+
+    match battle.state() {
+        BattleState::Fighting => {
+            let num_enemies = battle.enemies().len();
+            // aims an action at every enemy, or only at the first if it doesn't need aiming
+            let targets = |is_aimed: bool| 0..if is_aimed { num_enemies } else { 1 };
+            // under the triangle, the target of a defend or move is also who it clashes with
+            let is_triangle = battle.rules() == RuleSet::Triangle;
+            let clash_targets = |is_aimed: bool| targets(is_aimed || is_triangle);
+            let is_legal =
+                |action: Action, target: usize| battle.check_action(action, target).is_ok();
+
+            for target in targets(true) {
+                if is_legal(Action::Attack, target) {
+                    actions.push(ActionMessage::Attack { target });
+                }
+            }
+
+            for (move_idx, the_move) in battle.available_moves().iter().enumerate() {
+                for target in clash_targets(the_move.target() == MoveTarget::SingleEnemy) {
+                    if is_legal(Action::Magic(move_idx), target) {
+                        actions.push(ActionMessage::Magic {
+                            move_name: the_move.name(),
+                            target,
+                        });
+                    }
+                }
+            }
+
+            for stance in DefendStance::ALL {
+                for target in clash_targets(false) {
+                    if is_legal(Action::Defend(stance), target) {
+                        actions.push(ActionMessage::Defend { stance, target });
+                    }
+                }
+            }
+
+            let stacks = battle.player().inventory().stacks();
+            for (stack_idx, stack) in stacks.iter().enumerate() {
+                for target in targets(stack.item().effect().needs_target()) {
+                    if is_legal(Action::Item(stack_idx), target) {
+                        actions.push(ActionMessage::Item {
+                            item: stack.item().name(),
+                            target,
+                        });
+                    }
+                }
+            }
+        }
+
+        BattleState::LevelingUp => {
+            for stat in LevelUpType::ALL {
+                actions.push(ActionMessage::LevelUp { stat });
+            }
+        }
+
+        BattleState::PlayerDied => {}
+    }
+
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::Content;
+    use crate::entity_components::{enemy::Enemy, player::Player, stats::Stats};
+    use serde_json::{json, Value};
+
+    const MAX_STEPS: u32 = 100;
+
+    /// Starts a fight with two weak enemies, so actions can be aimed at either.
+    fn two_enemy_battle(seed: u64) -> Battle {
+        let enemies = (0..2)
+            .map(|i| Enemy::new(format!("Dummy {}", i), Stats::new(2, 0, 1, 1, 0, 0), 1))
+            .collect();
+
+        Battle::new(Player::default(), Some(enemies), seed, Content::built_in())
+    }
+
+    /// Checks that an action is written as the documented JSON, and is read back the same.
+    fn assert_round_trip(action: ActionMessage, expected: Value) {
+        assert_eq!(serde_json::to_value(&action).unwrap(), expected);
+        assert_eq!(
+            serde_json::from_value::<ActionMessage>(expected).unwrap(),
+            action
+        );
+    }
+
+    #[test]
+    fn actions_have_the_documented_shape() {
+        assert_round_trip(
+            ActionMessage::Attack { target: 1 },
+            json!({"action": "Attack", "target": 1}),
+        );
+        assert_round_trip(
+            ActionMessage::Magic {
+                move_name: String::from("FireOne"),
+                target: 0,
+            },
+            json!({"action": "Magic", "move_name": "FireOne", "target": 0}),
+        );
+        assert_round_trip(
+            ActionMessage::Defend {
+                stance: DefendStance::Parry,
+                target: 1,
+            },
+            json!({"action": "Defend", "stance": "Parry", "target": 1}),
+        );
+        assert_round_trip(
+            ActionMessage::Item {
+                item: String::from("Potion"),
+                target: 0,
+            },
+            json!({"action": "Item", "item": "Potion", "target": 0}),
+        );
+        assert_round_trip(
+            ActionMessage::LevelUp {
+                stat: LevelUpType::Magic,
+            },
+            json!({"action": "LevelUp", "stat": "Magic"}),
+        );
+    }
+
+    #[test]
+    fn actions_fill_in_missing_fields() {
+        let attack: ActionMessage = serde_json::from_str(r#"{"action": "Attack"}"#).unwrap();
+        let defend: ActionMessage = serde_json::from_str(r#"{"action": "Defend"}"#).unwrap();
+
+        assert_eq!(attack, ActionMessage::Attack { target: 0 });
+        assert_eq!(
+            defend,
+            ActionMessage::Defend {
+                stance: DefendStance::Guard,
+                target: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_action_is_not_parsed() {
+        assert!(serde_json::from_str::<ActionMessage>(r#"{"action": "Flee"}"#).is_err());
+    }
+
+    #[test]
+    fn error_has_the_documented_shape() {
+        let message = GameMessage::Error {
+            version: PROTOCOL_VERSION,
+            message: String::from("unknown move: IceOne"),
+        };
+
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            json!({"type": "Error", "version": PROTOCOL_VERSION, "message": "unknown move: IceOne"})
+        );
+    }
+
+    #[test]
+    fn observation_round_trips() {
+        let mut battle = two_enemy_battle(3);
+        let events = battle.take_events();
+        let observation = Observation::new(&battle, events);
+
+        let value =
+            serde_json::to_value(GameMessage::Observation(Box::new(observation.clone()))).unwrap();
+        assert_eq!(value["type"], "Observation");
+        assert_eq!(value["version"], PROTOCOL_VERSION);
+        assert_eq!(value["player"]["health"]["current"], 55);
+        assert_eq!(value["enemies"].as_array().unwrap().len(), 2);
+
+        let read_back = match serde_json::from_value(value).unwrap() {
+            GameMessage::Observation(read_back) => read_back,
+            GameMessage::Error { message, .. } => panic!("read an error: {}", message),
+        };
+        assert_eq!(read_back.player, observation.player);
+        assert_eq!(read_back.enemies, observation.enemies);
+        assert_eq!(read_back.legal_actions, observation.legal_actions);
+        assert_eq!(read_back.events.len(), observation.events.len());
+    }
+
+    #[test]
+    fn every_legal_action_can_be_applied() {
+        check_every_legal_action(RuleSet::Classic);
+    }
+
+    #[test]
+    fn every_legal_action_can_be_applied_under_the_triangle() {
+        check_every_legal_action(RuleSet::Triangle);
+
+        // defends clash with an enemy, so each one can be chosen
+        let battle = two_enemy_battle(5).with_rules(RuleSet::Triangle);
+        assert!(legal_actions(&battle).contains(&ActionMessage::Defend {
+            stance: DefendStance::Guard,
+            target: 1,
+        }));
+    }
+
+    /// Plays out a battle under `rules`, applying every legal action to a copy along the way
+    fn check_every_legal_action(rules: RuleSet) {
+        let mut battle = two_enemy_battle(5).with_rules(rules);
+        let mut states_seen = Vec::new();
+
+        for _ in 0..MAX_STEPS {
+            let legal = legal_actions(&battle);
+            if !states_seen.contains(&battle.state()) {
+                states_seen.push(battle.state());
+            }
+            if legal.is_empty() {
+                break;
+            }
+
+            for action in &legal {
+                let mut copy = battle.clone();
+                if let Err(err) = action.apply(&mut copy) {
+                    panic!("{:?} is legal but failed: {}", action, err);
+                }
+            }
+
+            // play on with the first action, so later states are checked too
+            legal[0].apply(&mut battle).unwrap();
+        }
+
+        assert!(states_seen.contains(&BattleState::Fighting));
+        assert!(states_seen.contains(&BattleState::LevelingUp));
+    }
+
+    #[test]
+    fn unknown_names_are_refused() {
+        let mut battle = two_enemy_battle(1);
+        let magic = ActionMessage::Magic {
+            move_name: String::from("IceOne"),
+            target: 0,
+        };
+        let item = ActionMessage::Item {
+            item: String::from("Elixir"),
+            target: 0,
+        };
+
+        assert!(matches!(
+            magic.apply(&mut battle),
+            Err(ProtocolError::UnknownMove { .. })
+        ));
+        assert!(matches!(
+            item.apply(&mut battle),
+            Err(ProtocolError::UnknownItem { .. })
+        ));
+    }
+
+    #[test]
+    fn level_up_is_refused_while_fighting() {
+        let mut battle = two_enemy_battle(1);
+        let level_up = ActionMessage::LevelUp {
+            stat: LevelUpType::Health,
+        };
+
+        assert!(matches!(
+            level_up.apply(&mut battle),
+            Err(ProtocolError::NotLevelingUp)
+        ));
+    }
+}
@@ -0,0 +1,27 @@
+// file for the random number generator used by the game
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// The random number generator used for every random roll in the game.
+///
+/// A game made with the same seed and the same inputs will always play out the same way.
+pub type GameRng = ChaCha8Rng;
+
+/// Creates the random number generator for a game.
+///
+/// # Params
+/// - `seed` - The seed of the game.
+///
+/// # Returns
+/// - A new `GameRng` seeded with `seed`.
+pub fn create_rng(seed: u64) -> GameRng {
+    GameRng::seed_from_u64(seed)
+}
+
+/// Picks a seed for a game when the player did not choose one.
+///
+/// # Returns
+/// - A random seed.
+pub fn random_seed() -> u64 {
+    rand::random()
+}
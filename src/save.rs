@@ -0,0 +1,507 @@
+// file for saving and loading games
+use std::{collections::VecDeque, error::Error, fmt, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::battle::engine::{Battle, SavedBattle};
+
+/// The version of the save file format that this build writes.
+///
+/// Bump this whenever the saved data changes shape, and add a migration to
+/// `MIGRATIONS` that upgrades the previous version.
+pub const SAVE_VERSION: u32 = 4;
+
+/// Where the game is saved when no other path is given.
+pub const DEFAULT_SAVE_PATH: &str = "starstruck_save.json";
+
+/// Upgrades a save of one version to the next. `MIGRATIONS[i]` upgrades
+/// version `i + 1` to version `i + 2`.
+type Migration = fn(&mut Value);
+
+const MIGRATIONS: [Migration; (SAVE_VERSION - 1) as usize] =
+    [migrate_v1_to_v2, migrate_v2_to_v3, migrate_v3_to_v4];
+
+/// Everything that is read from a save file.
+#[derive(Deserialize)]
+pub struct SaveFile {
+    pub version: u32,          // the format version of this save
+    pub battle: SavedBattle,   // the state of the fight, see `Battle::from_saved`
+    pub log: VecDeque<String>, // the battle log shown to the player
+}
+
+/// Everything that is written to a save file, borrowed from the running game.
+#[derive(Serialize)]
+struct SaveFileRef<'a> {
+    version: u32,
+    battle: &'a Battle,
+    log: &'a VecDeque<String>,
+}
+
+/// An error from saving or loading a game.
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),                       // could not read or write the file
+    Format(serde_json::Error),           // the file is not a valid save
+    MissingVersion,                      // the file has no format version
+    UnsupportedVersion { version: u32 }, // the save is from a newer version of the game
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "could not access the save file: {}", err),
+            SaveError::Format(err) => write!(f, "the save file is not valid: {}", err),
+            SaveError::MissingVersion => write!(f, "the save file has no version"),
+            SaveError::UnsupportedVersion { version } => write!(
+                f,
+                "the save file is version {}, but only up to version {} is supported",
+                version, SAVE_VERSION
+            ),
+        }
+    }
+}
+
+impl Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(err: io::Error) -> Self {
+        SaveError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(err: serde_json::Error) -> Self {
+        SaveError::Format(err)
+    }
+}
+
+impl SaveFile {
+    /// Writes a game to a file, replacing any save that is already there.
+    ///
+    /// # Params
+    /// - `path` - The path of the save file.
+    /// - `battle` - The battle to save.
+    /// - `log` - The battle log shown to the player.
+    pub fn write(path: &Path, battle: &Battle, log: &VecDeque<String>) -> Result<(), SaveError> {
+        let save = SaveFileRef {
+            version: SAVE_VERSION,
+            battle,
+            log,
+        };
+        let contents = serde_json::to_string_pretty(&save)?;
+        fs::write(path, contents)?;
+
+        Ok(())
+    }
+
+    /// Reads a save from a file, upgrading it if it was made by an older version of the game.
+    ///
+    /// # Params
+    /// - `path` - The path of the save file.
+    pub fn read(path: &Path) -> Result<Self, SaveError> {
+        SaveFile::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Reads a save from the text of a save file, upgrading it if it was made by an
+    /// older version of the game.
+    ///
+    /// # Params
+    /// - `contents` - The text of the save file.
+    pub fn from_json(contents: &str) -> Result<Self, SaveError> {
+        let mut value: Value = serde_json::from_str(contents)?;
+
+        migrate(&mut value)?;
+
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+/// Upgrades a save to the current version.
+///
+/// # Params
+/// - `value` - The save, which is changed in place.
+fn migrate(value: &mut Value) -> Result<(), SaveError> {
+    let mut version = value
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or(SaveError::MissingVersion)? as u32;
+
+    if version > SAVE_VERSION || version == 0 {
+        return Err(SaveError::UnsupportedVersion { version });
+    }
+
+    // run each migration in order until we reach the current version
+    while version < SAVE_VERSION {
+        MIGRATIONS[(version - 1) as usize](value);
+        version += 1;
+    }
+    value["version"] = Value::from(SAVE_VERSION);
+
+    Ok(())
+}
+
+/// Version 2 fights groups of enemies, where version 1 only had one enemy.
+///
+/// # Params
+/// - `value` - The version 1 save, which is changed in place.
+fn migrate_v1_to_v2(value: &mut Value) {
+    if let Some(battle) = value.get_mut("battle").and_then(Value::as_object_mut) {
+        let enemies = battle.remove("enemy").into_iter().collect();
+        battle.insert(String::from("enemies"), Value::Array(enemies));
+        battle.insert(String::from("defeated"), Value::Array(Vec::new()));
+    }
+}
+
+/// Version 3 changes stats through a list of modifiers, where version 2 added
+/// defending straight onto defense and applied equipment when it was used.
+///
+/// # Params
+/// - `value` - The version 2 save, which is changed in place.
+fn migrate_v2_to_v3(value: &mut Value) {
+    let Some(battle) = value.get_mut("battle") else {
+        return;
+    };
+
+    // the worn gear becomes modifiers that last until it is taken off
+    if let Some(player) = battle.get_mut("player") {
+        let mut gear_modifiers = Vec::new();
+        for slot in ["weapon", "armor", "accessory"] {
+            if let Some(gear) = player.pointer(&format!("/equipment/{}", slot)) {
+                let name = gear.get("name").cloned().unwrap_or(Value::Null);
+                let modifiers = gear.get("modifiers").and_then(Value::as_array);
+                for modifier in modifiers.into_iter().flatten() {
+                    gear_modifiers.push(serde_json::json!({
+                        "source": { "Equipment": name },
+                        "modifier": modifier,
+                        "turns_left": null,
+                    }));
+                }
+            }
+        }
+
+        if let Some(stats) = player.get_mut("stats") {
+            migrate_defending_v2_to_v3(stats);
+            push_modifiers_v3(stats, gear_modifiers);
+        }
+    }
+
+    for list in ["enemies", "defeated"] {
+        if let Some(enemies) = battle.get_mut(list).and_then(Value::as_array_mut) {
+            for stats in enemies
+                .iter_mut()
+                .filter_map(|enemy| enemy.get_mut("stats"))
+            {
+                migrate_defending_v2_to_v3(stats);
+            }
+        }
+    }
+}
+
+/// Moves the defense added by defending in a version 2 save into a modifier.
+///
+/// # Params
+/// - `stats` - The stats of an entity, which are changed in place.
+fn migrate_defending_v2_to_v3(stats: &mut Value) {
+    let Some(stats_object) = stats.as_object_mut() else {
+        return;
+    };
+
+    let was_defending = stats_object
+        .remove("is_defending")
+        .and_then(|is_defending| is_defending.as_bool())
+        .unwrap_or(false);
+    if !was_defending {
+        return;
+    }
+
+    // version 2 added 50 defense while defending
+    let defense = stats_object
+        .get("defense")
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+    stats_object.insert(
+        String::from("defense"),
+        Value::from(defense.saturating_sub(50)),
+    );
+
+    push_modifiers_v3(
+        stats,
+        vec![serde_json::json!({
+            "source": "Defending",
+            "modifier": { "stat": "Defense", "kind": "Flat", "amount": 50 },
+            "turns_left": 1,
+        })],
+    );
+}
+
+/// Adds modifiers to the stats of an entity in a version 3 save.
+///
+/// # Params
+/// - `stats` - The stats of an entity, which are changed in place.
+/// - `modifiers` - The modifiers to add.
+fn push_modifiers_v3(stats: &mut Value, modifiers: Vec<Value>) {
+    if let Some(stats_object) = stats.as_object_mut() {
+        let list = stats_object
+            .entry("modifiers")
+            .or_insert_with(|| Value::Array(Vec::new()));
+        if let Some(list) = list.as_array_mut() {
+            list.extend(modifiers);
+        }
+    }
+}
+
+/// Version 4 keeps health and mana as bounded resources, where version 3 had
+/// separate fields for the current amount, the max and the shield.
+///
+/// # Params
+/// - `value` - The version 3 save, which is changed in place.
+fn migrate_v3_to_v4(value: &mut Value) {
+    let Some(battle) = value.get_mut("battle") else {
+        return;
+    };
+
+    if let Some(player) = battle.get_mut("player") {
+        migrate_resources_v3_to_v4(player);
+    }
+    for list in ["enemies", "defeated"] {
+        if let Some(enemies) = battle.get_mut(list).and_then(Value::as_array_mut) {
+            enemies.iter_mut().for_each(migrate_resources_v3_to_v4);
+        }
+    }
+}
+
+/// Moves the health and mana of an entity in a version 3 save into resources.
+///
+/// # Params
+/// - `entity` - The player or enemy, which is changed in place.
+fn migrate_resources_v3_to_v4(entity: &mut Value) {
+    let Some(entity_object) = entity.as_object_mut() else {
+        return;
+    };
+
+    let mut take = |field: &str| {
+        entity_object
+            .remove(field)
+            .and_then(|amount| amount.as_u64())
+            .unwrap_or(0)
+    };
+    let (health, max_health, shield) = (take("health"), take("max_health"), take("shield"));
+    let (mana, max_mana) = (take("mana"), take("max_mana"));
+
+    // version 3 could heal past the max, which resources don't allow
+    entity_object.insert(
+        String::from("health"),
+        serde_json::json!({
+            "current": health.min(max_health),
+            "max": max_health,
+            "shield": shield,
+        }),
+    );
+    entity_object.insert(
+        String::from("mana"),
+        serde_json::json!({ "current": mana.min(max_mana), "max": max_mana }),
+    );
+}
+
+/// Checks if there is a save to continue from.
+///
+/// # Params
+/// - `path` - The path of the save file.
+pub fn save_exists(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::Content;
+    use crate::entity_components::{
+        entity::Entity,
+        equipment::{EquipSlot, Gear},
+        player::Player,
+        resource::Resource,
+        stats::{ModifierKind, ModifierSource, StatModifier, StatType},
+    };
+
+    // saves written by the versions of the game that used each old format
+    const SAVE_V1: &str = include_str!("../tests/fixtures/save_v1.json");
+    const SAVE_V2: &str = include_str!("../tests/fixtures/save_v2.json");
+    const SAVE_V3: &str = include_str!("../tests/fixtures/save_v3.json");
+
+    /// Loads a save into a full `Battle`.
+    fn load(contents: &str) -> (Battle, VecDeque<String>) {
+        let save = SaveFile::from_json(contents).unwrap();
+        assert_eq!(save.version, SAVE_VERSION);
+        (
+            Battle::from_saved(save.battle, Content::built_in()),
+            save.log,
+        )
+    }
+
+    /// Gets the stats and amounts of the modifiers from a source on an entity.
+    fn modifiers_from(entity: &dyn Entity, source: &ModifierSource) -> Vec<(StatType, i32)> {
+        entity
+            .stats()
+            .modifiers()
+            .iter()
+            .filter(|active| active.source() == source)
+            .map(|active| (active.modifier().stat(), active.modifier().amount()))
+            .collect()
+    }
+
+    #[test]
+    fn v1_save_moves_its_enemy_into_a_group() {
+        let (battle, log) = load(SAVE_V1);
+
+        assert_eq!(log, ["Louie did 11 damage to Spider"]);
+        assert_eq!(battle.enemies().len(), 1);
+        assert_eq!(battle.enemies()[0].name(), "Spider");
+        assert_eq!(battle.enemies()[0].health_bar().to_string(), "16/27");
+    }
+
+    #[test]
+    fn v1_save_moves_defending_into_a_modifier() {
+        let (battle, _) = load(SAVE_V1);
+        let player = battle.player();
+
+        assert_eq!(
+            modifiers_from(player, &ModifierSource::Defending),
+            [(StatType::Defense, 50)]
+        );
+        // the 50 defense from defending is no longer in the base stat
+        assert_eq!(player.stats().get_defense(), 50);
+        assert_eq!(*player.health_bar(), {
+            let mut health = Resource::new(55);
+            health.spend(2);
+            health
+        });
+    }
+
+    #[test]
+    fn v2_save_moves_worn_gear_into_modifiers() {
+        let (battle, _) = load(SAVE_V2);
+        let player = battle.player();
+
+        assert_eq!(
+            modifiers_from(
+                player,
+                &ModifierSource::Equipment(String::from("Bone Sword"))
+            ),
+            [(StatType::Strength, 3)]
+        );
+        assert_eq!(player.stats().get_strength(), 13);
+        assert_eq!(
+            modifiers_from(player, &ModifierSource::Defending),
+            [(StatType::Defense, 50)]
+        );
+        assert_eq!(player.inventory().stacks().len(), 2);
+        assert_eq!(battle.enemies()[0].health_bar().to_string(), "13/27");
+    }
+
+    #[test]
+    fn v3_save_moves_health_and_mana_into_resources() {
+        let (battle, _) = load(SAVE_V3);
+        let player = battle.player();
+
+        // version 3 healed the player to 78/55, which is capped at the max
+        assert_eq!(player.health_bar().current(), 55);
+        assert_eq!(player.health_bar().max(), 55);
+        assert_eq!(player.health_bar().shield(), 25);
+        assert_eq!(player.mana_bar().to_string(), "23/25");
+        assert_eq!(player.level(), 3);
+        assert_eq!(player.experience(), 40);
+
+        let enemies: Vec<String> = battle.enemies().iter().map(|enemy| enemy.name()).collect();
+        assert_eq!(enemies, ["Spider A", "Spider B", "Spider C"]);
+        assert!(battle
+            .enemies()
+            .iter()
+            .all(|enemy| enemy.health_bar().to_string() == "27/27"));
+    }
+
+    #[test]
+    fn current_save_round_trips() {
+        let (battle, log) = load(SAVE_V3);
+        let contents = serde_json::to_string(&SaveFileRef {
+            version: SAVE_VERSION,
+            battle: &battle,
+            log: &log,
+        })
+        .unwrap();
+
+        let (reloaded, reloaded_log) = load(&contents);
+        assert_eq!(reloaded_log, log);
+        assert_eq!(reloaded.player().health_bar(), battle.player().health_bar());
+        assert_eq!(reloaded.enemies().len(), battle.enemies().len());
+    }
+
+    #[test]
+    fn worn_gear_is_saved() {
+        let mut player = Player::default();
+        player.add_gear(Gear::new(
+            String::from("Bone Sword"),
+            EquipSlot::Weapon,
+            vec![StatModifier::new(StatType::Strength, ModifierKind::Flat, 3)],
+        ));
+        player.add_gear(Gear::new(
+            String::from("Plate"),
+            EquipSlot::Armor,
+            Vec::new(),
+        ));
+        player.equip(0);
+        let battle = Battle::new(player, None, 1, Content::built_in());
+        let contents = serde_json::to_string(&SaveFileRef {
+            version: SAVE_VERSION,
+            battle: &battle,
+            log: &VecDeque::new(),
+        })
+        .unwrap();
+
+        let (reloaded, _) = load(&contents);
+        let player = reloaded.player();
+
+        assert_eq!(
+            player
+                .equipment()
+                .equipped(EquipSlot::Weapon)
+                .map(Gear::name)
+                .as_deref(),
+            Some("Bone Sword")
+        );
+        assert_eq!(player.equipment().bag().len(), 1);
+        assert_eq!(
+            modifiers_from(
+                player,
+                &ModifierSource::Equipment(String::from("Bone Sword"))
+            ),
+            [(StatType::Strength, 3)]
+        );
+        assert_eq!(
+            player.stats().get_strength(),
+            battle.player().stats().get_strength()
+        );
+    }
+
+    #[test]
+    fn future_version_is_rejected() {
+        let contents = SAVE_V3.replacen(
+            "\"version\": 3",
+            &format!("\"version\": {}", SAVE_VERSION + 1),
+            1,
+        );
+
+        assert!(matches!(
+            SaveFile::from_json(&contents),
+            Err(SaveError::UnsupportedVersion { version }) if version == SAVE_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn missing_version_is_rejected() {
+        assert!(matches!(
+            SaveFile::from_json("{\"battle\": {}, \"log\": []}"),
+            Err(SaveError::MissingVersion)
+        ));
+    }
+}